@@ -4,12 +4,20 @@ no-vcs = No VCS found.
 creator-person-prompt = Who are you? (q to skip)
 creator-custom-person-prompt = State who you are (`Name (Email)`)
 creator-has-org-prompt = Are you creating this document on behalf of an organisation?
+creator-org-select-prompt = Which organisation are you creating this document for? (q to skip)
 creator-org-prompt = State the name of your organisation (`Name (Email)`)
+workspace-found = Found a { $kind } workspace with { $count } members.
+workspace-prompt = How should the workspace be described?
+workspace-documents = One document per member
+workspace-packages = One document with a package for every member
+workspace-root = Only the project as a whole
 name-prompt = What is the name of this project?
 version-prompt = What version are you creating this file for? (leave empty if version independent)
 doc-name-prompt = What should the name of this document be? (leave default if unsure)
 doc-host-available-prompt = Do you have a central place where you host SPDX files?
 doc-host-prompt = Specify the base URL of your SPDX file host
+doc-host-invalid = Please enter an absolute URL without "#"
+namespace-deterministic-prompt = Derive the document namespace from the package name, version and repository, so that it stays the same when the document is created again for this release?
 select-supplier-prompt = Who distributes this package? (person or organisation) (q to skip)
 input-supplier-prompt = Enter the name of the package supplier
 ask-different-originator-prompt = Was the package originally created by someone else?
@@ -19,16 +27,112 @@ ask-person-or-org = Is this a person or an organisation?
 person = Person
 org = Organisation
 go-back = Go Back
+go-back-hint = To go back to the previous question, press Esc or enter < in a text field.
+resume-prompt = An unfinished session with { $count } answers was found. Do you want to continue where you left off?
+aborted = Aborted. Run spdx-guide again to continue where you left off.
+no-terminal = spdx-guide asks questions and needs to run in a terminal.
+source-date-epoch-invalid = SOURCE_DATE_EPOCH must be a Unix timestamp, not `{ $value }`
+reproducible-no-time = --reproducible needs SOURCE_DATE_EPOCH or a git repository with at least one commit to take the creation time from.
 download-select-prompt = Where can this package be downloaded?
 download-rev-select-prompt = At which branch, tag or revision can this package be located? (q to skip)
 download-rev-input-prompt = Specify the branch, tag or revision
 other-download-prompt = Enter a URL at which this package can be downloaded
+homepage-select-prompt = What is the homepage of this package? (q to skip)
+homepage-input-prompt = Enter the URL of the package homepage (leave empty to skip)
+source-info-prompt = Where does the source of this package come from? (leave empty to skip)
+source-info-suggestion = Built from revision { $rev } of { $repo }
+external-refs-prompt = Which external references should be added to the package? (space to toggle)
+purl-prompt = What is the package URL (purl) of this package? (leave empty to skip)
+ask-cpe-prompt = Do you want to add a CPE name? Vulnerability databases use it to identify software.
+cpe-vendor-prompt = Who is the vendor of this package? (person or organisation)
+cpe-product-prompt = What is the product name of this package?
+cpe-version-prompt = Which version does the CPE name apply to? (leave empty for any version)
+cpe-confirm-prompt = CPE name (edit if necessary)
+checksum-select-prompt = Do you want to record checksums of the released package?
+checksum-none = No
+checksum-files = Yes, of the release artifacts (tarballs, .crate files, wheels, ...)
+checksum-git-archive = Yes, of an archive of a revision as created by `git archive`
+checksum-file-prompt = Enter the path of the release artifact (leave empty to skip)
+checksum-more-files-prompt = Enter the path of another release artifact (leave empty to continue)
+checksum-file-missing = File does not exist
+checksum-rev-select-prompt = Which revision should be archived? (q to skip)
+checksum-archive-computed = Recorded the checksums of { $file }. Publish the file created by { $command } to let others verify them; other versions of git may compress it differently.
+license-method-prompt = Under which license is this package distributed?
+license-detected = Found { $license } in { $file } ({ $confidence }% match)
+license-method-detected = Use the detected license: { $expression }
+license-method-search = Search the SPDX license list
+license-method-expression = Enter an SPDX license expression
+license-method-skip = Skip (specify the license later)
+license-search-prompt = Type to search for a license
+    [OSI] = approved by the Open Source Initiative, [FSF] = free according to the Free Software Foundation
+license-ref-item = Custom license that is not on the SPDX license list (LicenseRef-...)
+license-ref-prompt = Identifier of the custom license
+license-ref-invalid = The identifier must start with LicenseRef- followed by letters, numbers, dots or dashes.
+license-ref-info = { $license } is not on the SPDX license list, so its text has to be included in the document.
+license-ref-text-prompt = Which file contains the license text?
+license-ref-text-unreadable = This file does not exist or is not a text file.
+license-ref-name-prompt = Full name of the license
+license-ref-urls-prompt = URLs where the license text can be found (separated by spaces, optional)
+license-ref-comment-prompt = Comment on the license (optional)
+exception-search-prompt = Type to search for a license exception
+ask-exception-prompt = Does { $license } come with an exception (e.g. a linking exception)?
+license-combine-prompt = Current license: { $expression }. Does another license apply?
+license-combine-done = No, that's it
+license-combine-and = Yes, both licenses apply (AND)
+license-combine-or = Yes, users can choose between the licenses (OR)
+license-confirm-prompt = License expression (edit if necessary)
+ask-scan-headers = Do you want to scan your source files for SPDX-License-Identifier headers?
+scan-result = Scanned { $files } files, { $unlicensed } of which have no SPDX-License-Identifier header:
+scan-more-files = ...and { $count } more
+reuse-loading = Reading REUSE information...
+reuse-compliant = Your project is compliant with the REUSE specification.
+reuse-not-compliant = Your project is not compliant with the REUSE specification ({ $count } problems):
+reuse-bad-license = { $file } is not named after an SPDX license identifier or LicenseRef-
+reuse-invalid-expression = Invalid license expression `{ $expression }` in { $source }
+reuse-deprecated-license = The license { $license } is deprecated
+reuse-missing-license = The license { $license } is used, but its text is missing from LICENSES/
+reuse-unused-license = The license text of { $license } in LICENSES/ is not used by any file
+reuse-missing-copyright = { $file } has no copyright information
+reuse-missing-licensing = { $file } has no licensing information
+reuse-invalid-config = Could not read { $source }: { $message }
+reuse-continue-prompt = Do you want to create the SPDX document anyway?
+snippet-added = Added snippet { $id } to the SPDX file.
+snippet-missing-range = Please specify the location of the snippet with --bytes and/or --lines.
+snippet-invalid-range = { $start }:{ $end } is not a valid range (it must start at 1 or later and must not end before it starts)
+snippet-out-of-bounds = The range { $start }:{ $end } exceeds the end of the file ({ $size })
+snippet-invalid-license = Invalid license expression `{ $expression }`
+snippet-undescribed-file = { $file } has no file section in the SPDX file. Create the SPDX file with information about every file first.
+config-syntax = { $file } is not a valid TOML file: { $message }
+config-not-a-string = `{ $key }` in { $file } must be a string
+config-not-strings = `{ $key }` in { $file } must be a string or an array of strings
+config-invalid-supplier = Invalid supplier `{ $supplier }` in { $file }. It must be NOASSERTION or start with "Person: " or "Organization: ".
+config-invalid-license = Invalid license expression `{ $expression }` in { $file }
+config-invalid-namespace = Invalid namespace `{ $namespace }` in { $file }. It must be an absolute URI without "#".
+config-unknown-format = Unknown output format `{ $format }` in { $file }. Supported formats: { $formats }
+config-unknown-profile = There is no profile `{ $profile }` in { $file }
 license-input-prompt = Please specify the SPDX license identifier for this package.
     Not sure what this means? Check out https://github.com/david-a-wheeler/spdx-tutorial#spdx-license-identifiers
+concluded-license-prompt = Which license applies to the package after your own analysis? (NOASSERTION if you haven't checked)
+copyright-prompt = Who holds the copyright to this package? (NOASSERTION if unsure)
+license-comments-prompt = Anything else to note about the licensing of this package? (leave empty to skip)
 ask-verif-code = Do you want to generate a verification code from the current state of this package?
     This only makes sense if you're creating this document specifically for the current state.
+ask-file-sections = Do you want to include information about every single file of the package (checksums, licenses, copyright)?
+dependencies-found = Found { $count } dependencies in { $lockfile }.
+ask-dependencies = Do you want to include the dependencies in the document?
+ask-relationships = Do you want to state further relationships between the package and its files (e.g. DEPENDS_ON, GENERATED_FROM)?
+ask-another-relationship = Do you want to add another relationship?
+relationship-element-prompt = Which element is the relationship about?
+relationship-type-prompt = What kind of relationship is it?
+relationship-related-prompt = { $element } { $relationship } ...
 filename-prompt = What should the name of the SPDX file be?
 creating-file = Information collected. Creating file...
+review-header = This is the document that will be written:
+review-prompt = Select a field to change it, or write the file
+review-write = Write { $file }
+review-edit-prompt = New value for { $tag }
+overwrite-prompt = { $file } already exists. Overwrite it?
+file-not-written = { $file } was not written.
 error = Error
 other = Other...
 no-assertion = No Assertion
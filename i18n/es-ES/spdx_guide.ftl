@@ -4,12 +4,20 @@ no-vcs = No se ha encontrado VCS.
 creator-person-prompt = ¿Quién eres? (q para saltar)
 creator-custom-person-prompt = Introduce quién eres (`Nombre (Correo electrónico)`)
 creator-has-org-prompt = ¿Estás creando este documento en nombre de una organización?
+creator-org-select-prompt = ¿Para qué organización estás creando este documento? (q para saltar)
 creator-org-prompt = Introduce el nombre de tu organización (`Nombre (Correo electrónico)`)
+workspace-found = Se encontró un workspace de { $kind } con { $count } miembros.
+workspace-prompt = ¿Cómo se debería describir el workspace?
+workspace-documents = Un documento por miembro
+workspace-packages = Un documento con un paquete por cada miembro
+workspace-root = Solo el proyecto en su conjunto
 name-prompt = ¿Cuál es el nombre de este proyecto?
 version-prompt = ¿Para qué versión estás creando este documento? (deja este campo vacío si la versión es independiente)
 doc-name-prompt = ¿Cómo debería llamarse este documento? (déjalo en predeterminado si no estás seguro)
 doc-host-available-prompt = ¿Tienes un lugar central dónde almacenas archivos SPDX?
 doc-host-prompt = Especifica la URL base de tu host de archivos SPDX
+doc-host-invalid = Introduce una URL absoluta sin "#"
+namespace-deterministic-prompt = ¿Derivar el espacio de nombres del documento del nombre, la versión y el repositorio del paquete, para que no cambie si se vuelve a crear el documento para esta versión?
 select-supplier-prompt = ¿Quién distribuye este paquete? (persona u organización) (q para saltar)
 input-supplier-prompt = Introduce el nombre del proveedor del paquete
 ask-different-originator-prompt = ¿Fue el paquete originalmente creado por otra persona?
@@ -19,16 +27,112 @@ ask-person-or-org = ¿Es este una persona o una organización?
 person = Persona
 org = Organización
 go-back = Volver atrás
+go-back-hint = Para volver a la pregunta anterior, pulsa Esc o introduce < en un campo de texto.
+resume-prompt = Se ha encontrado una sesión sin terminar con { $count } respuestas. ¿Quieres continuar donde lo dejaste?
+aborted = Cancelado. Ejecuta spdx-guide de nuevo para continuar donde lo dejaste.
+no-terminal = spdx-guide hace preguntas y necesita ejecutarse en una terminal.
+source-date-epoch-invalid = SOURCE_DATE_EPOCH debe ser una marca de tiempo Unix, no `{ $value }`
+reproducible-no-time = --reproducible necesita SOURCE_DATE_EPOCH o un repositorio git con al menos un commit del que tomar la fecha de creación.
 download-select-prompt = ¿Dónde se puede descargar este paquete?
 download-rev-select-prompt = ¿En qué rama, etiqueta o revisión se encuentra este paquete? (q para saltar)
 download-rev-input-prompt = Especifica la rama, etiqueta o revisión
 other-download-prompt = Introduce una URL en la que se pueda descargar este paquete
+homepage-select-prompt = ¿Cuál es la página principal de este paquete? (q para saltar)
+homepage-input-prompt = Introduce la URL de la página principal del paquete (deja vacío para saltar)
+source-info-prompt = ¿De dónde procede el código fuente de este paquete? (deja vacío para saltar)
+source-info-suggestion = Compilado a partir de la revisión { $rev } de { $repo }
+external-refs-prompt = ¿Qué referencias externas quieres añadir al paquete? (espacio para marcar)
+purl-prompt = ¿Cuál es la URL de paquete (purl) de este paquete? (deja vacío para saltar)
+ask-cpe-prompt = ¿Quieres añadir un nombre CPE? Las bases de datos de vulnerabilidades lo usan para identificar software.
+cpe-vendor-prompt = ¿Quién es el proveedor de este paquete? (persona u organización)
+cpe-product-prompt = ¿Cuál es el nombre de producto de este paquete?
+cpe-version-prompt = ¿A qué versión se aplica el nombre CPE? (deja vacío para cualquier versión)
+cpe-confirm-prompt = Nombre CPE (edítalo si es necesario)
+checksum-select-prompt = ¿Quieres registrar sumas de verificación del paquete publicado?
+checksum-none = No
+checksum-files = Sí, de los artefactos publicados (tarballs, archivos .crate, wheels, ...)
+checksum-git-archive = Sí, de un archivo de una revisión creado con `git archive`
+checksum-file-prompt = Introduce la ruta del artefacto publicado (deja vacío para saltar)
+checksum-more-files-prompt = Introduce la ruta de otro artefacto publicado (deja vacío para continuar)
+checksum-file-missing = El archivo no existe
+checksum-rev-select-prompt = ¿Qué revisión quieres archivar? (q para saltar)
+checksum-archive-computed = Se han registrado las sumas de verificación de { $file }. Publica el archivo creado por { $command } para que otros puedan verificarlas; otras versiones de git pueden comprimirlo de otra forma.
+license-method-prompt = ¿Bajo qué licencia se distribuye este paquete?
+license-detected = Se ha encontrado { $license } en { $file } ({ $confidence }% de coincidencia)
+license-method-detected = Usar la licencia detectada: { $expression }
+license-method-search = Buscar en la lista de licencias SPDX
+license-method-expression = Introducir una expresión de licencia SPDX
+license-method-skip = Saltar (especificar la licencia más tarde)
+license-search-prompt = Escribe para buscar una licencia
+    [OSI] = aprobada por la Open Source Initiative, [FSF] = libre según la Free Software Foundation
+license-ref-item = Licencia personalizada que no está en la lista de licencias SPDX (LicenseRef-...)
+license-ref-prompt = Identificador de la licencia personalizada
+license-ref-invalid = El identificador debe empezar con LicenseRef- seguido de letras, números, puntos o guiones.
+license-ref-info = { $license } no está en la lista de licencias SPDX, así que su texto tiene que incluirse en el documento.
+license-ref-text-prompt = ¿Qué archivo contiene el texto de la licencia?
+license-ref-text-unreadable = Este archivo no existe o no es un archivo de texto.
+license-ref-name-prompt = Nombre completo de la licencia
+license-ref-urls-prompt = URLs donde se encuentra el texto de la licencia (separadas por espacios, opcional)
+license-ref-comment-prompt = Comentario sobre la licencia (opcional)
+exception-search-prompt = Escribe para buscar una excepción de licencia
+ask-exception-prompt = ¿{ $license } viene con una excepción (p. ej. una excepción de enlazado)?
+license-combine-prompt = Licencia actual: { $expression }. ¿Se aplica otra licencia?
+license-combine-done = No, eso es todo
+license-combine-and = Sí, se aplican ambas licencias (AND)
+license-combine-or = Sí, los usuarios pueden elegir entre las licencias (OR)
+license-confirm-prompt = Expresión de licencia (edítala si es necesario)
+ask-scan-headers = ¿Quieres buscar cabeceras SPDX-License-Identifier en tus archivos fuente?
+scan-result = Se han analizado { $files } archivos, { $unlicensed } de ellos no tienen cabecera SPDX-License-Identifier:
+scan-more-files = ...y { $count } más
+reuse-loading = Leyendo la información REUSE...
+reuse-compliant = Tu proyecto cumple la especificación REUSE.
+reuse-not-compliant = Tu proyecto no cumple la especificación REUSE ({ $count } problemas):
+reuse-bad-license = { $file } no lleva el nombre de un identificador de licencia SPDX o LicenseRef-
+reuse-invalid-expression = Expresión de licencia no válida `{ $expression }` en { $source }
+reuse-deprecated-license = La licencia { $license } está obsoleta
+reuse-missing-license = Se usa la licencia { $license }, pero falta su texto en LICENSES/
+reuse-unused-license = Ningún archivo usa el texto de la licencia { $license } en LICENSES/
+reuse-missing-copyright = { $file } no tiene información de derechos de autor
+reuse-missing-licensing = { $file } no tiene información de licencia
+reuse-invalid-config = No se ha podido leer { $source }: { $message }
+reuse-continue-prompt = ¿Quieres crear el documento SPDX de todos modos?
+snippet-added = Se ha añadido el fragmento { $id } al archivo SPDX.
+snippet-missing-range = Indica la ubicación del fragmento con --bytes y/o --lines.
+snippet-invalid-range = { $start }:{ $end } no es un rango válido (debe empezar en 1 o más y no puede terminar antes de empezar)
+snippet-out-of-bounds = El rango { $start }:{ $end } supera el final del archivo ({ $size })
+snippet-invalid-license = Expresión de licencia no válida `{ $expression }`
+snippet-undescribed-file = { $file } no tiene una sección de archivo en el archivo SPDX. Crea primero el archivo SPDX con información sobre cada archivo.
+config-syntax = { $file } no es un archivo TOML válido: { $message }
+config-not-a-string = `{ $key }` en { $file } debe ser una cadena
+config-not-strings = `{ $key }` en { $file } debe ser una cadena o un array de cadenas
+config-invalid-supplier = Proveedor `{ $supplier }` no válido en { $file }. Debe ser NOASSERTION o empezar por "Person: " u "Organization: ".
+config-invalid-license = Expresión de licencia `{ $expression }` no válida en { $file }
+config-invalid-namespace = Espacio de nombres `{ $namespace }` no válido en { $file }. Debe ser una URI absoluta sin "#".
+config-unknown-format = Formato de salida `{ $format }` desconocido en { $file }. Formatos soportados: { $formats }
+config-unknown-profile = No hay ningún perfil `{ $profile }` en { $file }
 license-input-prompt = Por favor, especifica el identificador de licencias SPDX para este paquete.
     ¿No sabes qué significa esto? Entra en la página https://github.com/david-a-wheeler/spdx-tutorial#spdx-license-identifiers
+concluded-license-prompt = ¿Qué licencia se aplica al paquete según tu propio análisis? (NOASSERTION si no lo has comprobado)
+copyright-prompt = ¿Quién posee los derechos de autor de este paquete? (NOASSERTION si no estás seguro)
+license-comments-prompt = ¿Algo más que añadir sobre la licencia de este paquete? (deja vacío para saltar)
 ask-verif-code = ¿Quieres generar un código de verificación del actual estado del paquete?
     Esto solo tiene sentido si estás creando este documento específicamente para el estado actual.
+ask-file-sections = ¿Quieres incluir información sobre cada archivo del paquete (sumas de verificación, licencias, derechos de autor)?
+dependencies-found = Se encontraron { $count } dependencias en { $lockfile }.
+ask-dependencies = ¿Quieres incluir las dependencias en el documento?
+ask-relationships = ¿Quieres indicar más relaciones entre el paquete y sus archivos (p. ej. DEPENDS_ON, GENERATED_FROM)?
+ask-another-relationship = ¿Quieres añadir otra relación?
+relationship-element-prompt = ¿A qué elemento se refiere la relación?
+relationship-type-prompt = ¿Qué tipo de relación es?
+relationship-related-prompt = { $element } { $relationship } ...
 filename-prompt = ¿Cuál quieres que sea el nombre del archivo SPDX?
 creating-file = Información recolectada. Creando archivo...
+review-header = Este es el documento que se escribirá:
+review-prompt = Selecciona un campo para cambiarlo, o escribe el archivo
+review-write = Escribir { $file }
+review-edit-prompt = Nuevo valor para { $tag }
+overwrite-prompt = { $file } ya existe. ¿Sobrescribirlo?
+file-not-written = { $file } no se ha escrito.
 error = Error
 other = Otro...
 no-assertion = No hay aserción
@@ -4,12 +4,20 @@ no-vcs = Nenhum VCS encontrado.
 creator-person-prompt = Quem é você? (q para pular)
 creator-custom-person-prompt = Diga quem você é (`Nome (Email)`)
 creator-has-org-prompt = Você está criando esse documento em nome de uma organização?
+creator-org-select-prompt = Para qual organização você está criando esse documento? (q para pular)
 creator-org-prompt = Digite o nome da sua organização (`Nome (Email)`)
+workspace-found = Foi encontrado um workspace de { $kind } com { $count } membros.
+workspace-prompt = Como o workspace deve ser descrito?
+workspace-documents = Um documento por membro
+workspace-packages = Um documento com um pacote para cada membro
+workspace-root = Apenas o projeto como um todo
 name-prompt = Qual o nome desse projeto?
 version-prompt = Esse arquivo que você está criando pertence a qual versão? (Ignore se a versão for independente)
 doc-name-prompt = What should the name of this document be? (leave default if unsure) Qual deve ser o nome desse documento? (deixe o padrão se tiver incerto)
 doc-host-available-prompt = Você tem um local central onde hospeda os arquivos SPDX?
 doc-host-prompt = Especifique a URL base do seu SPDX file host
+doc-host-invalid = Digite uma URL absoluta sem "#"
+namespace-deterministic-prompt = Derivar o namespace do documento do nome, da versão e do repositório do pacote, para que ele continue o mesmo quando o documento for criado novamente para essa versão?
 select-supplier-prompt = Who distributes this package? (person or organisation) (q to skip) Quem distribui esse pacote? (pessoa ou organização) (q para pular)
 input-supplier-prompt = Insira o nome do fornecedor do pacote
 ask-different-originator-prompt = O pacote foi criado originalmente por outra pessoa?
@@ -19,16 +27,112 @@ ask-person-or-org = Isto é uma pessoa ou organização?
 person = Pessoa
 org = Organização
 go-back = Voltar
+go-back-hint = Para voltar à pergunta anterior, pressione Esc ou digite < em um campo de texto.
+resume-prompt = Foi encontrada uma sessão não concluída com { $count } respostas. Deseja continuar de onde parou?
+aborted = Cancelado. Execute o spdx-guide novamente para continuar de onde parou.
+no-terminal = O spdx-guide faz perguntas e precisa ser executado em um terminal.
+source-date-epoch-invalid = SOURCE_DATE_EPOCH deve ser um timestamp Unix, não `{ $value }`
+reproducible-no-time = --reproducible precisa de SOURCE_DATE_EPOCH ou de um repositório git com pelo menos um commit do qual obter a data de criação.
 download-select-prompt = Onde esse pacote pode ser baixado?
 download-rev-select-prompt = Em qual branch, tag ou revisão esse pacote pode ser localizado? (q para pular)
 download-rev-input-prompt = Especifique a branch, tag ou revisão
 other-download-prompt = Insira a URL no qual esse pacote pode ser baixado
+homepage-select-prompt = Qual é a página inicial desse pacote? (q para pular)
+homepage-input-prompt = Insira a URL da página inicial do pacote (deixe vazio para pular)
+source-info-prompt = De onde vem o código-fonte desse pacote? (deixe vazio para pular)
+source-info-suggestion = Compilado a partir da revisão { $rev } de { $repo }
+external-refs-prompt = Quais referências externas devem ser adicionadas ao pacote? (espaço para marcar)
+purl-prompt = Qual é a URL de pacote (purl) desse pacote? (deixe vazio para pular)
+ask-cpe-prompt = Você quer adicionar um nome CPE? Bancos de dados de vulnerabilidades o usam para identificar software.
+cpe-vendor-prompt = Quem é o fornecedor desse pacote? (pessoa ou organização)
+cpe-product-prompt = Qual é o nome de produto desse pacote?
+cpe-version-prompt = A qual versão o nome CPE se aplica? (deixe vazio para qualquer versão)
+cpe-confirm-prompt = Nome CPE (edite se necessário)
+checksum-select-prompt = Você quer registrar checksums do pacote publicado?
+checksum-none = Não
+checksum-files = Sim, dos artefatos publicados (tarballs, arquivos .crate, wheels, ...)
+checksum-git-archive = Sim, de um arquivo de uma revisão criado com `git archive`
+checksum-file-prompt = Insira o caminho do artefato publicado (deixe vazio para pular)
+checksum-more-files-prompt = Insira o caminho de outro artefato publicado (deixe vazio para continuar)
+checksum-file-missing = O arquivo não existe
+checksum-rev-select-prompt = Qual revisão deve ser arquivada? (q para pular)
+checksum-archive-computed = Os checksums de { $file } foram registrados. Publique o arquivo criado por { $command } para que outros possam verificá-los; outras versões do git podem comprimi-lo de outra forma.
+license-method-prompt = Sob qual licença esse pacote é distribuído?
+license-detected = { $license } encontrada em { $file } ({ $confidence }% de correspondência)
+license-method-detected = Usar a licença detectada: { $expression }
+license-method-search = Pesquisar na lista de licenças SPDX
+license-method-expression = Inserir uma expressão de licença SPDX
+license-method-skip = Pular (especificar a licença depois)
+license-search-prompt = Digite para pesquisar uma licença
+    [OSI] = aprovada pela Open Source Initiative, [FSF] = livre segundo a Free Software Foundation
+license-ref-item = Licença personalizada que não está na lista de licenças SPDX (LicenseRef-...)
+license-ref-prompt = Identificador da licença personalizada
+license-ref-invalid = O identificador deve começar com LicenseRef- seguido de letras, números, pontos ou hífens.
+license-ref-info = { $license } não está na lista de licenças SPDX, então seu texto precisa ser incluído no documento.
+license-ref-text-prompt = Qual arquivo contém o texto da licença?
+license-ref-text-unreadable = Este arquivo não existe ou não é um arquivo de texto.
+license-ref-name-prompt = Nome completo da licença
+license-ref-urls-prompt = URLs onde o texto da licença pode ser encontrado (separados por espaços, opcional)
+license-ref-comment-prompt = Comentário sobre a licença (opcional)
+exception-search-prompt = Digite para pesquisar uma exceção de licença
+ask-exception-prompt = { $license } vem com uma exceção (por exemplo, uma exceção de linkagem)?
+license-combine-prompt = Licença atual: { $expression }. Outra licença se aplica?
+license-combine-done = Não, é só isso
+license-combine-and = Sim, ambas as licenças se aplicam (AND)
+license-combine-or = Sim, os usuários podem escolher entre as licenças (OR)
+license-confirm-prompt = Expressão de licença (edite se necessário)
+ask-scan-headers = Você quer procurar cabeçalhos SPDX-License-Identifier nos seus arquivos fonte?
+scan-result = { $files } arquivos analisados, { $unlicensed } dos quais não têm cabeçalho SPDX-License-Identifier:
+scan-more-files = ...e mais { $count }
+reuse-loading = Lendo as informações REUSE...
+reuse-compliant = Seu projeto está em conformidade com a especificação REUSE.
+reuse-not-compliant = Seu projeto não está em conformidade com a especificação REUSE ({ $count } problemas):
+reuse-bad-license = { $file } não tem o nome de um identificador de licença SPDX ou LicenseRef-
+reuse-invalid-expression = Expressão de licença inválida `{ $expression }` em { $source }
+reuse-deprecated-license = A licença { $license } está obsoleta
+reuse-missing-license = A licença { $license } é usada, mas o seu texto está faltando em LICENSES/
+reuse-unused-license = O texto da licença { $license } em LICENSES/ não é usado por nenhum arquivo
+reuse-missing-copyright = { $file } não tem informações de direitos autorais
+reuse-missing-licensing = { $file } não tem informações de licença
+reuse-invalid-config = Não foi possível ler { $source }: { $message }
+reuse-continue-prompt = Você quer criar o documento SPDX mesmo assim?
+snippet-added = O trecho { $id } foi adicionado ao arquivo SPDX.
+snippet-missing-range = Especifique a localização do trecho com --bytes e/ou --lines.
+snippet-invalid-range = { $start }:{ $end } não é um intervalo válido (deve começar em 1 ou depois e não pode terminar antes de começar)
+snippet-out-of-bounds = O intervalo { $start }:{ $end } ultrapassa o fim do arquivo ({ $size })
+snippet-invalid-license = Expressão de licença inválida `{ $expression }`
+snippet-undescribed-file = { $file } não tem uma seção de arquivo no arquivo SPDX. Crie primeiro o arquivo SPDX com informações sobre cada arquivo.
+config-syntax = { $file } não é um arquivo TOML válido: { $message }
+config-not-a-string = `{ $key }` em { $file } deve ser uma string
+config-not-strings = `{ $key }` em { $file } deve ser uma string ou um array de strings
+config-invalid-supplier = Fornecedor `{ $supplier }` inválido em { $file }. Deve ser NOASSERTION ou começar com "Person: " ou "Organization: ".
+config-invalid-license = Expressão de licença `{ $expression }` inválida em { $file }
+config-invalid-namespace = Namespace `{ $namespace }` inválido em { $file }. Deve ser uma URI absoluta sem "#".
+config-unknown-format = Formato de saída `{ $format }` desconhecido em { $file }. Formatos suportados: { $formats }
+config-unknown-profile = Não existe nenhum perfil `{ $profile }` em { $file }
 license-input-prompt = Por favor, especifique o identificador da licença SPDX para esse pacote
     Não sabe o que isso significa? Veja em https://github.com/david-a-wheeler/spdx-tutorial#spdx-license-identifiers
+concluded-license-prompt = Qual licença se aplica ao pacote segundo a sua própria análise? (NOASSERTION se você não verificou)
+copyright-prompt = Quem detém os direitos autorais desse pacote? (NOASSERTION se não tiver certeza)
+license-comments-prompt = Algo mais a observar sobre o licenciamento desse pacote? (deixe vazio para pular)
 ask-verif-code = Você que gerar um código de verificação pelo estado atual desse pacote?
     Isso apenas faz sentido se você está criando esse documento especificamente para o estado atual
+ask-file-sections = Você quer incluir informações sobre cada arquivo do pacote (checksums, licenças, direitos autorais)?
+dependencies-found = Foram encontradas { $count } dependências em { $lockfile }.
+ask-dependencies = Você quer incluir as dependências no documento?
+ask-relationships = Você quer indicar mais relações entre o pacote e seus arquivos (p. ex. DEPENDS_ON, GENERATED_FROM)?
+ask-another-relationship = Você quer adicionar outra relação?
+relationship-element-prompt = A qual elemento a relação se refere?
+relationship-type-prompt = Que tipo de relação é?
+relationship-related-prompt = { $element } { $relationship } ...
 filename-prompt = Qual deve ser o nome do arquivo SPDX?
 creating-file = Informação coletada. Criando arquivos...
+review-header = Este é o documento que será escrito:
+review-prompt = Selecione um campo para alterá-lo, ou escreva o arquivo
+review-write = Escrever { $file }
+review-edit-prompt = Novo valor para { $tag }
+overwrite-prompt = { $file } já existe. Sobrescrevê-lo?
+file-not-written = { $file } não foi escrito.
 error = Error
 other = Outro...
 no-assertion = Nenhuma afirmação
@@ -0,0 +1,195 @@
+// Copyright (C) 2022  JohnnyJayJay
+
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::fs::File;
+use std::io;
+use std::io::Read;
+use std::path::Path;
+use blake2::Blake2b;
+use blake2::digest::consts::U32;
+use sha1::{Digest, Sha1};
+use sha1::digest::DynDigest;
+use sha2::{Sha256, Sha512};
+
+/// A checksum algorithm supported by SPDX 2.3.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Algorithm {
+    Sha1,
+    Sha256,
+    Sha512,
+    Blake2b256,
+}
+
+impl Algorithm {
+    pub const ALL: [Algorithm; 4] = [Algorithm::Sha1, Algorithm::Sha256, Algorithm::Sha512, Algorithm::Blake2b256];
+
+    fn hasher(&self) -> Box<dyn DynDigest> {
+        match self {
+            Algorithm::Sha1 => Box::new(Sha1::new()),
+            Algorithm::Sha256 => Box::new(Sha256::new()),
+            Algorithm::Sha512 => Box::new(Sha512::new()),
+            Algorithm::Blake2b256 => Box::new(Blake2b::<U32>::new()),
+        }
+    }
+}
+
+impl Display for Algorithm {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Algorithm::Sha1 => "SHA1",
+            Algorithm::Sha256 => "SHA256",
+            Algorithm::Sha512 => "SHA512",
+            Algorithm::Blake2b256 => "BLAKE2b-256",
+        })
+    }
+}
+
+/// Value of a `PackageChecksum` or `FileChecksum` entry, e.g. `SHA1: 85ed0817af83a24ad8da68c2b5094de69833983c`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Checksum {
+    pub algorithm: Algorithm,
+    pub value: String,
+}
+
+impl Display for Checksum {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.algorithm, self.value)
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn base64_decode(encoded: &str) -> Option<Vec<u8>> {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut bytes = Vec::with_capacity(encoded.len() * 3 / 4);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for c in encoded.trim_end_matches('=').bytes() {
+        let value = ALPHABET.iter().position(|a| *a == c)? as u32;
+        buffer = (buffer << 6) | value;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+        }
+    }
+    Some(bytes)
+}
+
+/// Converts a Subresource Integrity value as found in npm lockfiles (e.g. `sha512-<base64>`) to checksums.
+/// Hashes with algorithms that SPDX doesn't support are ignored.
+pub fn from_integrity(integrity: &str) -> Vec<Checksum> {
+    integrity.split_whitespace()
+        .filter_map(|hash| {
+            let (algorithm, value) = hash.split_once('-')?;
+            let algorithm = match algorithm {
+                "sha1" => Algorithm::Sha1,
+                "sha256" => Algorithm::Sha256,
+                "sha512" => Algorithm::Sha512,
+                _ => return None
+            };
+            Some(Checksum { algorithm, value: to_hex(&base64_decode(value)?) })
+        })
+        .collect()
+}
+
+/// Reads `reader` to the end once, computing the checksums of all given algorithms along the way.
+pub fn compute<R: Read>(mut reader: R, algorithms: &[Algorithm]) -> io::Result<Vec<Checksum>> {
+    let mut hashers: Vec<_> = algorithms.iter().map(Algorithm::hasher).collect();
+    let mut buffer = [0u8; 8192];
+    loop {
+        let read = reader.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        for hasher in hashers.iter_mut() {
+            hasher.update(&buffer[..read]);
+        }
+    }
+    Ok(algorithms.iter().zip(hashers)
+        .map(|(algorithm, hasher)| Checksum { algorithm: *algorithm, value: to_hex(&hasher.finalize()) })
+        .collect())
+}
+
+/// The package verification code (SPDX 2.3, section 7.9): the SHA1 of the sorted and concatenated SHA1 checksums of the
+/// files, followed by the names of the files that were left out, if any.
+pub fn verification_code(mut sha1s: Vec<String>, excludes: &[String]) -> String {
+    sha1s.sort();
+    let code = to_hex(&Sha1::digest(sha1s.concat().as_bytes()));
+    if excludes.is_empty() {
+        code
+    } else {
+        format!("{} (excludes: {})", code, excludes.join(", "))
+    }
+}
+
+pub fn compute_file(path: &Path, algorithms: &[Algorithm]) -> io::Result<Vec<Checksum>> {
+    compute(File::open(path)?, algorithms)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn base64_padding() {
+        let cases: [(&str, Option<&[u8]>); 6] = [
+            ("", Some(b"")),
+            ("AQ==", Some(&[1])),
+            ("AQI=", Some(&[1, 2])),
+            ("AQID", Some(&[1, 2, 3])),
+            ("AQ", Some(&[1])),
+            ("AQ-_", None),
+        ];
+        for (encoded, expected) in cases {
+            assert_eq!(base64_decode(encoded).as_deref(), expected, "{}", encoded);
+        }
+    }
+
+    #[test]
+    fn subresource_integrity() {
+        let checksum = |algorithm, value: &str| Checksum { algorithm, value: value.to_string() };
+        let cases = [
+            // SHA-1 of the empty string
+            ("sha1-2jmj7l5rSw0yVb/vlWAYkK/YBwk=", vec![checksum(Algorithm::Sha1, "da39a3ee5e6b4b0d3255bfef95601890afd80709")]),
+            // SHA-256 and SHA-512 of `abc`
+            ("sha256-ungWv48Bz+pBQUDeXa4iI7ADYaOWF3qctBD/YfIAFa0=",
+                vec![checksum(Algorithm::Sha256, "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad")]),
+            ("sha512-3a81oZNherrMQXNJriBBMRLm+k6JqX6iCp7u5ktV05ohkpkqJ0/BqDa6PCOj/uu9RU1EI2Q86A4qmslPpUyknw==",
+                vec![checksum(Algorithm::Sha512, "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a\
+                    2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f")]),
+            // Several hashes, of which SPDX doesn't support SHA-384
+            ("sha384-AAAA sha1-2iNhTgJGmg18e9G9q1ycR0sZBNw=", vec![checksum(Algorithm::Sha1, "da23614e02469a0d7c7bd1bdab5c9c474b1904dc")]),
+            ("sha1-not*base64", vec![]),
+            ("2jmj7l5rSw0yVb/vlWAYkK/YBwk=", vec![]),
+        ];
+        for (integrity, expected) in cases {
+            assert_eq!(from_integrity(integrity), expected, "{}", integrity);
+        }
+    }
+
+    #[test]
+    fn computed_checksums() {
+        let checksums = compute(&b"abc"[..], &[Algorithm::Sha1, Algorithm::Sha256]).unwrap();
+        assert_eq!(checksums.iter().map(Checksum::to_string).collect::<Vec<_>>(), vec![
+            "SHA1: a9993e364706816aba3e25717850c26c9cd0d89d",
+            "SHA256: ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+        ]);
+    }
+
+    #[test]
+    fn verification_codes() {
+        // SHA-1 of the files `a` and `b`, in any order
+        let sha1s = || vec!["e9d71f5ee7c92d6dc9e92ffdad17b8bd49418f98".to_string(), "86f7e437faa5a7fce15d1ddcb9eaeaea377667b8".to_string()];
+        assert_eq!(verification_code(sha1s(), &[]), "5463504435e4dbf2b93a3a8a00ca78e36ea40e24");
+        assert_eq!(verification_code(sha1s(), &["./package.spdx".to_string()]),
+            "5463504435e4dbf2b93a3a8a00ca78e36ea40e24 (excludes: ./package.spdx)");
+        assert_eq!(verification_code(sha1s(), &["./package.spdx".to_string(), "./vendor/lib.js".to_string()]),
+            "5463504435e4dbf2b93a3a8a00ca78e36ea40e24 (excludes: ./package.spdx, ./vendor/lib.js)");
+        // Without any files, it is the SHA-1 of the empty string
+        assert_eq!(verification_code(Vec::new(), &[]), "da39a3ee5e6b4b0d3255bfef95601890afd80709");
+    }
+}
@@ -0,0 +1,246 @@
+// Copyright (C) 2022  JohnnyJayJay
+
+//! The project configuration file, `.spdx-guide.toml`, which teams can commit to fix answers for everyone,
+//! and the user configuration, with profiles whose values are suggested first:
+//!
+//! ```toml
+//! [creator]
+//! organization = "ACME Inc."
+//!
+//! [package]
+//! supplier = "Organization: ACME Inc."
+//! license = "MIT"
+//!
+//! [document]
+//! namespace = "https://spdx.acme.com/spdxdocs"
+//!
+//! [output]
+//! file = "acme.spdx"
+//! format = "tag-value"
+//!
+//! [verification-code]
+//! exclude = ["vendor/**", "*.lock"]
+//!
+//! [authors]
+//! ignore = ["*[bot]", "renovate*"]
+//! ```
+//!
+//! ```toml
+//! # $XDG_CONFIG_HOME/spdx-guide/config.toml
+//! default-profile = "work"
+//!
+//! [profile.work]
+//! person = "Jane Doe (jane@acme.com)"
+//! organization = "ACME Inc."
+//! supplier = "Organization: ACME Inc."
+//! namespace = "https://spdx.acme.com/spdxdocs"
+//!
+//! [profile.personal]
+//! person = "Jane Doe (jane@example.com)"
+//! ```
+
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use toml::Value;
+use crate::glob;
+use crate::vcs::User;
+
+pub const CONFIG_FILE: &str = ".spdx-guide.toml";
+/// Location of the user configuration, relative to the user's configuration directory.
+const USER_CONFIG_FILE: &str = "spdx-guide/config.toml";
+/// Output formats spdx-guide can write. Only tag-value is supported so far.
+pub const FORMATS: [&str; 1] = ["tag-value"];
+
+#[derive(Debug)]
+pub enum ConfigError {
+    /// The file is not valid TOML
+    Syntax(String),
+    /// A key whose value should be a string
+    NotAString(String),
+    /// A key whose value should be a string or an array of strings
+    NotStrings(String),
+    /// A supplier that is neither `NOASSERTION` nor starts with `Person: ` or `Organization: `
+    InvalidSupplier(String),
+    InvalidLicense(String),
+    /// A namespace that is not an absolute URI without `#`
+    InvalidNamespace(String),
+    UnknownFormat(String),
+    /// A profile that is selected, but not defined in the user configuration
+    UnknownProfile(String),
+    Io(io::Error),
+}
+
+impl From<io::Error> for ConfigError {
+    fn from(e: io::Error) -> Self {
+        ConfigError::Io(e)
+    }
+}
+
+/// Answers fixed by the project configuration. Everything that is not configured is asked for as usual.
+#[derive(Debug, Clone, Default)]
+pub struct Config {
+    /// Organisation that is added as a creator of the document
+    pub creator_organization: Option<String>,
+    /// Supplier of the package, e.g. `Organization: ACME Inc.`
+    pub supplier: Option<String>,
+    /// Declared license of the package
+    pub license: Option<String>,
+    /// Base URL of the document namespace, without a trailing slash
+    pub namespace: Option<String>,
+    /// SPDX file to generate, relative to the project directory. `--file` takes precedence.
+    pub file: Option<String>,
+    /// Patterns of files that are left out of the package verification code
+    pub excluded: Vec<String>,
+    /// Patterns of author names or email addresses, e.g. of bots, that are never suggested as supplier or originator
+    pub ignored_authors: Vec<String>,
+}
+
+/// The value at the given path of keys, e.g. `["package", "supplier"]`.
+fn get<'a>(config: &'a Value, path: &[&str]) -> Option<&'a Value> {
+    path.iter().try_fold(config, |value, key| value.get(key))
+}
+
+/// The string at the given path of keys, if there is one.
+fn string(config: &Value, path: &[&str]) -> Result<Option<String>, ConfigError> {
+    match get(config, path) {
+        None => Ok(None),
+        Some(Value::String(value)) => Ok(Some(value.clone())),
+        Some(_) => Err(ConfigError::NotAString(path.join(".")))
+    }
+}
+
+/// The strings at the given path of keys, which may be a single string or an array of strings.
+fn strings(config: &Value, path: &[&str]) -> Result<Vec<String>, ConfigError> {
+    match get(config, path) {
+        None => Ok(vec![]),
+        Some(Value::String(value)) => Ok(vec![value.clone()]),
+        Some(Value::Array(values)) => values.iter()
+            .map(|value| value.as_str().map(String::from))
+            .collect::<Option<Vec<String>>>()
+            .ok_or_else(|| ConfigError::NotStrings(path.join("."))),
+        Some(_) => Err(ConfigError::NotStrings(path.join(".")))
+    }
+}
+
+/// The supplier at the given path of keys, which must be `NOASSERTION` or start with `Person: ` or `Organization: `.
+fn supplier(config: &Value, path: &[&str]) -> Result<Option<String>, ConfigError> {
+    match string(config, path)? {
+        Some(value) if value != "NOASSERTION" && !["Person: ", "Organization: "].iter()
+            .any(|prefix| value.strip_prefix(prefix).is_some_and(|name| !name.trim().is_empty())) =>
+            Err(ConfigError::InvalidSupplier(value)),
+        value => Ok(value)
+    }
+}
+
+/// Whether `url` can be the start of a document namespace, which must be an absolute URI without `#`.
+pub fn is_namespace_base(url: &str) -> bool {
+    url.contains("://") && !url.contains('#')
+}
+
+/// The namespace base URL at the given path of keys, without a trailing slash.
+fn namespace(config: &Value, path: &[&str]) -> Result<Option<String>, ConfigError> {
+    match string(config, path)? {
+        Some(value) if !is_namespace_base(&value) => Err(ConfigError::InvalidNamespace(value)),
+        value => Ok(value.map(|value| value.trim_end_matches('/').to_string()))
+    }
+}
+
+fn parse_toml(content: &str) -> Result<Value, ConfigError> {
+    content.parse().map_err(|err: toml::de::Error| ConfigError::Syntax(err.to_string()))
+}
+
+impl Config {
+    /// Reads the configuration file in `dir`. A missing file is the same as an empty one.
+    pub fn load(dir: &Path) -> Result<Config, ConfigError> {
+        match fs::read_to_string(dir.join(CONFIG_FILE)) {
+            Ok(content) => Config::parse(&content),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Config::default()),
+            Err(e) => Err(e.into())
+        }
+    }
+
+    pub fn parse(content: &str) -> Result<Config, ConfigError> {
+        let config = parse_toml(content)?;
+        let license = string(&config, &["package", "license"])?;
+        if let Some(license) = license.as_ref().filter(|license| spdx::Expression::parse(license).is_err()) {
+            return Err(ConfigError::InvalidLicense(license.clone()));
+        }
+        if let Some(format) = string(&config, &["output", "format"])?.filter(|format| !FORMATS.contains(&format.as_str())) {
+            return Err(ConfigError::UnknownFormat(format));
+        }
+        Ok(Config {
+            creator_organization: string(&config, &["creator", "organization"])?,
+            supplier: supplier(&config, &["package", "supplier"])?,
+            license,
+            namespace: namespace(&config, &["document", "namespace"])?,
+            file: string(&config, &["output", "file"])?,
+            excluded: strings(&config, &["verification-code", "exclude"])?,
+            ignored_authors: strings(&config, &["authors", "ignore"])?,
+        })
+    }
+
+    /// Whether the file at `path` (relative to the project directory) is left out of the verification code.
+    pub fn is_excluded(&self, path: &Path) -> bool {
+        let path = glob::slash_path(path);
+        self.excluded.iter().any(|pattern| glob::glob_match(pattern.as_bytes(), path.as_bytes(), false))
+    }
+
+    pub fn is_ignored_author(&self, user: &User) -> bool {
+        self.ignored_authors.iter().any(|pattern| std::iter::once(&user.name).chain(&user.email)
+            .any(|value| glob::glob_match(pattern.as_bytes(), value.as_bytes(), true)))
+    }
+}
+
+/// Values of one of the user's roles, e.g. at work or in personal projects,
+/// which are suggested first in the questions they belong to.
+#[derive(Debug, Clone, Default)]
+pub struct Profile {
+    /// Creator person, e.g. `Jane Doe (jane@acme.com)`
+    pub person: Option<String>,
+    /// Creator organisation
+    pub organization: Option<String>,
+    /// Supplier of the package, e.g. `Organization: ACME Inc.`
+    pub supplier: Option<String>,
+    /// Base URL of the document namespace, used unless the project configures one
+    pub namespace: Option<String>,
+}
+
+/// Path of the user configuration: `$XDG_CONFIG_HOME/spdx-guide/config.toml`,
+/// or `~/.config/spdx-guide/config.toml` if `XDG_CONFIG_HOME` is not set.
+pub fn user_config_file() -> Option<PathBuf> {
+    env::var_os("XDG_CONFIG_HOME").map(PathBuf::from).filter(|dir| dir.is_absolute())
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .map(|dir| dir.join(USER_CONFIG_FILE))
+}
+
+impl Profile {
+    /// Reads the profile `name` from the user configuration `file`, or the `default-profile` if no name is given.
+    /// Without either, the profile is empty.
+    pub fn load(file: Option<&Path>, name: Option<&str>) -> Result<Profile, ConfigError> {
+        let content = match file.map(fs::read_to_string) {
+            Some(Ok(content)) => content,
+            Some(Err(e)) if e.kind() != io::ErrorKind::NotFound => return Err(e.into()),
+            _ => String::new()
+        };
+        Profile::parse(&content, name)
+    }
+
+    pub fn parse(content: &str, name: Option<&str>) -> Result<Profile, ConfigError> {
+        let config = parse_toml(content)?;
+        let name = match name.map(String::from).or(string(&config, &["default-profile"])?) {
+            Some(name) => name,
+            None => return Ok(Profile::default())
+        };
+        if !get(&config, &["profile", &name]).is_some_and(Value::is_table) {
+            return Err(ConfigError::UnknownProfile(name));
+        }
+        Ok(Profile {
+            person: string(&config, &["profile", &name, "person"])?,
+            organization: string(&config, &["profile", &name, "organization"])?,
+            supplier: supplier(&config, &["profile", &name, "supplier"])?,
+            namespace: namespace(&config, &["profile", &name, "namespace"])?,
+        })
+    }
+}
@@ -0,0 +1,230 @@
+// Copyright (C) 2022  JohnnyJayJay
+
+/// Number of components of a CPE 2.3 formatted string, including the `cpe` and `2.3` prefix.
+const COMPONENT_COUNT: usize = 13;
+/// Index of the `language` component, which has its own syntax.
+const LANGUAGE_INDEX: usize = 8;
+
+/// Escapes a user-provided value for use as a component of a CPE 2.3 formatted string.
+/// Values are lowercased and whitespace is replaced by underscores, as recommended by NIST.
+pub fn escape(value: &str) -> String {
+    let value = value.trim();
+    if value.is_empty() {
+        return "*".to_string();
+    }
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.to_lowercase().chars() {
+        match c {
+            'a'..='z' | '0'..='9' | '-' | '.' | '_' => escaped.push(c),
+            c if c.is_whitespace() => escaped.push('_'),
+            c => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+        }
+    }
+    escaped
+}
+
+/// Builds the CPE 2.3 formatted string of an application.
+pub fn application(vendor: &str, product: &str, version: &str) -> String {
+    format!("cpe:2.3:a:{}:{}:{}:*:*:*:*:*:*:*", escape(vendor), escape(product), escape(version))
+}
+
+/// Splits a formatted string on every `:` that is not escaped by a backslash.
+fn split_components(cpe: &str) -> Vec<&str> {
+    let mut components = Vec::new();
+    let mut start = 0;
+    let mut escaped = false;
+    for (i, c) in cpe.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            ':' => {
+                components.push(&cpe[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    components.push(&cpe[start..]);
+    components
+}
+
+/// Whether the character at byte `index` of `value` is escaped, which it is after an odd number of backslashes.
+fn is_escaped(value: &str, index: usize) -> bool {
+    value[..index].bytes().rev().take_while(|byte| *byte == b'\\').count() % 2 == 1
+}
+
+fn is_valid_value(value: &str) -> bool {
+    if value == "*" || value == "-" {
+        return true;
+    }
+    // Wildcards: a single `*` or any number of `?` at the start and end of the value
+    let body = match value.strip_prefix('*') {
+        Some(rest) => rest,
+        None => value.trim_start_matches('?')
+    };
+    let body = match body.strip_suffix('*') {
+        Some(rest) if !is_escaped(body, rest.len()) => rest,
+        _ => {
+            let mut end = body.len();
+            while body[..end].ends_with('?') && !is_escaped(body, end - 1) {
+                end -= 1;
+            }
+            &body[..end]
+        }
+    };
+    if body.is_empty() {
+        return false;
+    }
+    let mut chars = body.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '.' | '_' => {}
+            '\\' => match chars.next() {
+                Some(escaped) if escaped.is_ascii_punctuation() => {}
+                _ => return false
+            },
+            _ => return false
+        }
+    }
+    true
+}
+
+fn is_valid_language(value: &str) -> bool {
+    if value == "*" || value == "-" {
+        return true;
+    }
+    let (language, region) = value.split_once('-').map_or((value, None), |(l, r)| (l, Some(r)));
+    (2..=3).contains(&language.len()) && language.chars().all(|c| c.is_ascii_alphabetic())
+        && region.is_none_or(|r| (r.len() == 2 && r.chars().all(|c| c.is_ascii_alphabetic()))
+        || (r.len() == 3 && r.chars().all(|c| c.is_ascii_digit())))
+}
+
+/// Checks a string against the CPE 2.3 formatted string binding (NISTIR 7695).
+pub fn validate(cpe: &str) -> Result<(), String> {
+    let components = split_components(cpe);
+    if components.len() != COMPONENT_COUNT {
+        return Err(format!("A CPE 2.3 name has {} components separated by `:`, found {}", COMPONENT_COUNT, components.len()));
+    }
+    if components[0] != "cpe" || components[1] != "2.3" {
+        return Err("A CPE 2.3 name must start with `cpe:2.3:`".to_string());
+    }
+    if !matches!(components[2], "a" | "o" | "h" | "*" | "-") {
+        return Err(format!("Invalid CPE part `{}`, expected one of a, o, h", components[2]));
+    }
+    for (index, component) in components.iter().enumerate().skip(3) {
+        let valid = if index == LANGUAGE_INDEX { is_valid_language(component) } else { is_valid_value(component) };
+        if !valid {
+            return Err(format!("Invalid CPE component `{}`", component));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn values_are_escaped() {
+        let cases = [
+            ("Acme Inc.", "acme_inc."),
+            ("1.0-beta_2", "1.0-beta_2"),
+            ("  ", "*"),
+            ("", "*"),
+            ("a:b", "a\\:b"),
+            ("C++", "c\\+\\+"),
+            ("50% off*", "50\\%_off\\*"),
+            ("what?", "what\\?"),
+        ];
+        for (value, expected) in cases {
+            assert_eq!(escape(value), expected, "{}", value);
+        }
+        let cpe = application("Acme Inc.", "Widget: Pro", "");
+        assert_eq!(cpe, "cpe:2.3:a:acme_inc.:widget\\:_pro:*:*:*:*:*:*:*:*");
+        assert_eq!(validate(&cpe), Ok(()));
+    }
+
+    #[test]
+    fn wildcards() {
+        let cases = [
+            ("*", true),
+            ("-", true),
+            ("foo", true),
+            ("*foo", true),
+            ("foo*", true),
+            ("*foo*", true),
+            ("??foo", true),
+            ("foo??", true),
+            ("?foo*", true),
+            ("*foo??", true),
+            ("foo\\*", true),
+            ("foo\\?", true),
+            ("foo\\:bar", true),
+            // An escaped backslash followed by a wildcard
+            ("foo\\\\*", true),
+            ("foo\\\\?", true),
+            ("", false),
+            ("?", false),
+            ("**", false),
+            ("**foo", false),
+            ("*?foo", false),
+            ("?*foo", false),
+            ("fo*o", false),
+            ("fo?o", false),
+            ("foo\\", false),
+            ("foo\\\\\\", false),
+            ("foo\\a", false),
+            ("foo bar", false),
+            ("Ünicode", false),
+        ];
+        for (value, valid) in cases {
+            assert_eq!(is_valid_value(value), valid, "{}", value);
+        }
+    }
+
+    #[test]
+    fn languages() {
+        let cases = [
+            ("*", true),
+            ("-", true),
+            ("en", true),
+            ("eng", true),
+            ("en-us", true),
+            ("en-GB", true),
+            ("es-419", true),
+            ("e", false),
+            ("engl", false),
+            ("en-u", false),
+            ("en-41", false),
+            ("en-4190", false),
+            ("e1", false),
+        ];
+        for (value, valid) in cases {
+            assert_eq!(is_valid_language(value), valid, "{}", value);
+        }
+    }
+
+    #[test]
+    fn formatted_strings() {
+        let cases = [
+            ("cpe:2.3:a:acme:widget:1.0:*:*:*:*:*:*:*", true),
+            ("cpe:2.3:o:linux:linux_kernel:6.1:-:*:en-us:*:*:x64:*", true),
+            ("cpe:2.3:a:acme:widget\\:pro:1.*:*:*:*:*:*:*:*", true),
+            ("cpe:2.3:*:acme:widget:*:*:*:*:*:*:*:*", true),
+            ("cpe:2.3:a:acme:widget:1.0:*:*:*:*:*:*", false),
+            ("cpe:2.3:a:acme:widget:1.0:*:*:*:*:*:*:*:*", false),
+            ("cpe:2.2:a:acme:widget:1.0:*:*:*:*:*:*:*", false),
+            ("cpe:/a:acme:widget:1.0", false),
+            ("cpe:2.3:x:acme:widget:1.0:*:*:*:*:*:*:*", false),
+            ("cpe:2.3:a:acme:widget:1.0:*:*:english:*:*:*:*", false),
+            ("cpe:2.3:a:acme:wid get:1.0:*:*:*:*:*:*:*", false),
+            ("cpe:2.3:a::widget:1.0:*:*:*:*:*:*:*", false),
+        ];
+        for (cpe, valid) in cases {
+            assert_eq!(validate(cpe).is_ok(), valid, "{}", cpe);
+        }
+    }
+}
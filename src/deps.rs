@@ -0,0 +1,132 @@
+// Copyright (C) 2022  JohnnyJayJay
+
+use std::path::{Path, PathBuf};
+use crate::checksum::Checksum;
+
+pub(crate) mod cargo;
+pub(crate) mod go;
+pub(crate) mod maven;
+pub(crate) mod npm;
+pub(crate) mod python;
+
+/// How the project (or another package) uses a dependency.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DependencyKind {
+    Normal,
+    Dev,
+    Build,
+    Optional,
+    Test,
+    /// Expected to be provided by the environment at runtime
+    Provided,
+    /// Only needed at runtime, not for compilation
+    Runtime,
+}
+
+/// A package pinned in a lockfile.
+#[derive(Debug, Clone)]
+pub struct Dependency {
+    pub name: String,
+    pub version: String,
+    pub purl: Option<String>,
+    pub checksums: Vec<Checksum>,
+    /// Where the package can be downloaded from, in SPDX `DownloadLocation` syntax
+    pub download_location: Option<String>,
+    /// Declared license expression, if it could be found offline
+    pub license: Option<String>,
+    /// Indices of the packages this package depends on
+    pub dependencies: Vec<usize>,
+}
+
+/// All packages from a single lockfile.
+#[derive(Debug, Default)]
+pub struct DependencyGraph {
+    /// Path of the lockfile, relative to the project directory
+    pub lockfile: PathBuf,
+    pub packages: Vec<Dependency>,
+    /// Indices of the packages the project depends on directly
+    pub direct: Vec<(usize, DependencyKind)>,
+}
+
+impl DependencyGraph {
+    /// Drops the packages that can't be reached from the direct dependencies, such as those of other workspace members.
+    fn reachable(mut self) -> Self {
+        let mut reachable = vec![false; self.packages.len()];
+        let mut pending: Vec<usize> = self.direct.iter().map(|(index, _)| *index).collect();
+        while let Some(index) = pending.pop() {
+            if !reachable[index] {
+                reachable[index] = true;
+                pending.extend(&self.packages[index].dependencies);
+            }
+        }
+        // New index of every package that is kept
+        let mut kept = 0;
+        let indices: Vec<Option<usize>> = reachable.iter()
+            .map(|reachable| reachable.then(|| {
+                kept += 1;
+                kept - 1
+            }))
+            .collect();
+        self.packages = self.packages.into_iter().zip(&reachable)
+            .filter(|(_, reachable)| **reachable)
+            .map(|(mut package, _)| {
+                package.dependencies = package.dependencies.iter().filter_map(|index| indices[*index]).collect();
+                package
+            })
+            .collect();
+        self.direct = self.direct.iter().filter_map(|(index, kind)| Some((indices[*index]?, *kind))).collect();
+        self
+    }
+}
+
+/// Turns a license expression from package metadata into a valid SPDX expression, if possible.
+/// Custom `LicenseRef-`s are dropped since their texts are not available.
+pub fn normalise_license(license: &str) -> Option<String> {
+    // Old Cargo manifests use `/` instead of `OR`
+    let license = license.split('/').map(str::trim).collect::<Vec<_>>().join(" OR ");
+    let expression = spdx::Expression::parse(&license).ok()?;
+    if expression.requirements().all(|req| req.req.license.id().is_some()) {
+        Some(license)
+    } else {
+        None
+    }
+}
+
+/// A new directory with the given files, for the tests of the lockfile readers and manifest lookups.
+#[cfg(test)]
+pub(crate) fn project(name: &str, files: &[(&str, &str)]) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("spdx-guide-test-{}-{}", name, std::process::id()));
+    let _result = std::fs::remove_dir_all(&dir);
+    for (path, content) in files {
+        let path = dir.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+    dir
+}
+
+/// Reads the dependencies of the package in `member`, a directory relative to `dir` that is empty for the project itself.
+/// Cargo, npm, Yarn and pnpm workspaces keep a single lockfile at the root, which is read from `dir` and narrowed down
+/// to the packages the member depends on. Other lockfiles are read from the directory of the member.
+pub fn read_lockfiles(dir: &Path, member: &Path) -> Vec<DependencyGraph> {
+    let workspace_readers: [fn(&Path, &Path) -> Option<DependencyGraph>; 4] = [
+        cargo::read,
+        npm::read_package_lock,
+        npm::read_yarn_lock,
+        npm::read_pnpm_lock,
+    ];
+    let readers: [fn(&Path) -> Option<DependencyGraph>; 6] = [
+        python::read_poetry_lock,
+        python::read_uv_lock,
+        python::read_requirements,
+        go::read,
+        maven::read_dependency_list,
+        maven::read_gradle_lockfile,
+    ];
+    let package_dir = dir.join(member);
+    workspace_readers.iter()
+        .filter_map(|read| read(dir, member))
+        .map(DependencyGraph::reachable)
+        .chain(readers.iter().filter_map(|read| read(&package_dir)))
+        .collect()
+}
@@ -0,0 +1,159 @@
+// Copyright (C) 2022  JohnnyJayJay
+
+use std::collections::HashSet;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml::Value;
+use crate::checksum::{Algorithm, Checksum};
+use crate::purl;
+use super::{normalise_license, Dependency, DependencyGraph, DependencyKind};
+
+const LOCKFILE: &str = "Cargo.lock";
+const CRATES_IO: [&str; 2] = ["registry+https://github.com/rust-lang/crates.io-index", "sparse+https://index.crates.io/"];
+
+struct LockedPackage {
+    name: String,
+    version: String,
+    source: Option<String>,
+    checksum: Option<String>,
+    dependencies: Vec<String>,
+}
+
+fn string(table: &Value, key: &str) -> Option<String> {
+    table.get(key)?.as_str().map(str::to_string)
+}
+
+fn read_packages(lockfile: &Value) -> Vec<LockedPackage> {
+    let packages = lockfile.get("package").and_then(Value::as_array).map(Vec::as_slice).unwrap_or_default();
+    // Version 1 lockfiles keep the checksums in a separate table
+    let metadata = lockfile.get("metadata").and_then(Value::as_table);
+    packages.iter()
+        .filter_map(|package| {
+            let name = string(package, "name")?;
+            let version = string(package, "version")?;
+            let source = string(package, "source");
+            let checksum = string(package, "checksum").or_else(|| {
+                let key = format!("checksum {} {} ({})", name, version, source.as_deref()?);
+                metadata?.get(&key)?.as_str().map(str::to_string)
+            });
+            let dependencies = package.get("dependencies").and_then(Value::as_array).into_iter().flatten()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect();
+            Some(LockedPackage { name, version, source, checksum, dependencies })
+        })
+        .collect()
+}
+
+/// Finds the package a `dependencies` entry refers to. Entries have the form `name`, `name version` or `name version (source)`.
+fn resolve(packages: &[LockedPackage], reference: &str) -> Option<usize> {
+    let mut parts = reference.splitn(3, ' ');
+    let name = parts.next()?;
+    let version = parts.next();
+    let source = parts.next().map(|source| source.trim_start_matches('(').trim_end_matches(')'));
+    packages.iter().position(|package| package.name == name
+        && version.is_none_or(|version| package.version == version)
+        && source.is_none_or(|source| package.source.as_deref() == Some(source)))
+}
+
+fn download_location(package: &LockedPackage) -> Option<String> {
+    let source = package.source.as_deref()?;
+    if CRATES_IO.contains(&source) {
+        Some(format!("https://crates.io/api/v1/crates/{}/{}/download", package.name, package.version))
+    } else if let Some(git) = source.strip_prefix("git+") {
+        let (url, commit) = git.split_once('#')?;
+        let url = url.split('?').next().unwrap_or(url);
+        Some(format!("git+{}@{}", url, commit))
+    } else {
+        None
+    }
+}
+
+fn cargo_home() -> Option<PathBuf> {
+    env::var_os("CARGO_HOME").map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").or_else(|| env::var_os("USERPROFILE")).map(|home| Path::new(&home).join(".cargo")))
+}
+
+/// Reads the `license` field from the manifest of a crate in the local registry cache.
+fn registry_license(registry_sources: &[PathBuf], package: &LockedPackage) -> Option<String> {
+    let crate_dir = format!("{}-{}", package.name, package.version);
+    registry_sources.iter()
+        .map(|registry| registry.join(&crate_dir).join("Cargo.toml"))
+        .filter_map(|manifest| fs::read_to_string(manifest).ok())
+        .filter_map(|manifest| manifest.parse::<Value>().ok())
+        .find_map(|manifest| string(manifest.get("package")?, "license"))
+        .and_then(|license| normalise_license(&license))
+}
+
+/// Names of the crates in a dependency table of the manifest, taking renamed dependencies into account.
+fn dependency_names(manifest: &Value, table: &str) -> HashSet<String> {
+    manifest.get(table).and_then(Value::as_table).into_iter().flatten()
+        .map(|(key, value)| value.get("package").and_then(Value::as_str).unwrap_or(key).to_string())
+        .collect()
+}
+
+/// Reads the dependencies of the crate in `member` from `Cargo.lock`. In a workspace, the direct dependencies are those
+/// of the member's package. Otherwise, packages without a source (the crate and its path dependencies) are considered
+/// part of the project, so their dependencies are the direct dependencies of the project.
+pub fn read(dir: &Path, member: &Path) -> Option<DependencyGraph> {
+    let lockfile: Value = fs::read_to_string(dir.join(LOCKFILE)).ok()?.parse().ok()?;
+    let locked = read_packages(&lockfile);
+    let read_manifest = |dir: &Path| -> Option<Value> { fs::read_to_string(dir.join("Cargo.toml")).ok()?.parse().ok() };
+    let in_workspace = read_manifest(dir).is_some_and(|root| root.get("workspace").is_some());
+    let manifest = read_manifest(&dir.join(member));
+    let normal = manifest.as_ref().map(|manifest| dependency_names(manifest, "dependencies")).unwrap_or_default();
+    let dev = manifest.as_ref().map(|manifest| dependency_names(manifest, "dev-dependencies")).unwrap_or_default();
+    let build = manifest.as_ref().map(|manifest| dependency_names(manifest, "build-dependencies")).unwrap_or_default();
+    let name = manifest.as_ref().and_then(|manifest| string(manifest.get("package")?, "name")).filter(|_| in_workspace);
+    let is_project = |package: &LockedPackage| package.source.is_none()
+        && name.as_ref().is_none_or(|name| &package.name == name);
+
+    let registry_sources: Vec<PathBuf> = cargo_home()
+        .and_then(|home| fs::read_dir(home.join("registry").join("src")).ok())
+        .into_iter().flatten()
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .collect();
+
+    // Indices of external packages in `locked` mapped to indices in the graph
+    let external: Vec<usize> = (0..locked.len()).filter(|i| locked[*i].source.is_some()).collect();
+    let graph_index = |locked_index: usize| external.iter().position(|i| *i == locked_index);
+
+    let mut graph = DependencyGraph { lockfile: PathBuf::from(LOCKFILE), ..Default::default() };
+    for i in &external {
+        let package = &locked[*i];
+        let from_crates_io = package.source.as_deref().is_some_and(|source| CRATES_IO.contains(&source));
+        graph.packages.push(Dependency {
+            name: package.name.clone(),
+            version: package.version.clone(),
+            purl: Some(format!("pkg:cargo/{}@{}", purl::percent_encode(&package.name), purl::percent_encode(&package.version)))
+                .filter(|_| from_crates_io),
+            checksums: package.checksum.iter()
+                .map(|checksum| Checksum { algorithm: Algorithm::Sha256, value: checksum.clone() })
+                .collect(),
+            download_location: download_location(package),
+            license: registry_license(&registry_sources, package),
+            dependencies: package.dependencies.iter()
+                .filter_map(|reference| resolve(&locked, reference))
+                .filter_map(graph_index)
+                .collect(),
+        });
+    }
+    for package in locked.iter().filter(|package| is_project(package)) {
+        for index in package.dependencies.iter().filter_map(|reference| resolve(&locked, reference)).filter_map(graph_index) {
+            let name = &locked[external[index]].name;
+            let kind = if normal.contains(name) || (!dev.contains(name) && !build.contains(name)) {
+                DependencyKind::Normal
+            } else if build.contains(name) {
+                DependencyKind::Build
+            } else {
+                DependencyKind::Dev
+            };
+            if !graph.direct.iter().any(|(direct, _)| *direct == index) {
+                graph.direct.push((index, kind));
+            }
+        }
+    }
+    Some(graph)
+}
@@ -0,0 +1,178 @@
+// Copyright (C) 2022  JohnnyJayJay
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::purl;
+use super::{Dependency, DependencyGraph, DependencyKind};
+
+const PROXY: &str = "https://proxy.golang.org";
+
+/// A `require` directive of `go.mod`.
+struct Requirement {
+    path: String,
+    version: String,
+    indirect: bool,
+}
+
+/// A `replace` directive of `go.mod`. Without a version on the left side, it applies to all versions.
+/// Without a version on the right side, the replacement is a local directory.
+struct Replacement {
+    path: String,
+    version: Option<String>,
+    new_path: String,
+    new_version: Option<String>,
+}
+
+/// Escapes a module path or version for the module proxy protocol, where upper case letters become `!` and the lower case letter.
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if c.is_ascii_uppercase() {
+            escaped.push('!');
+            escaped.push(c.to_ascii_lowercase());
+        } else {
+            escaped.push(c);
+        }
+    }
+    escaped
+}
+
+/// Splits `go.mod` into directives, expanding blocks like `require ( ... )`. Returns pairs of verb and arguments.
+pub(crate) fn directives(content: &str) -> Vec<(String, String)> {
+    let mut directives = Vec::new();
+    let mut block: Option<String> = None;
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with("//") {
+            continue;
+        }
+        match block {
+            Some(_) if line == ")" => block = None,
+            Some(ref verb) => directives.push((verb.clone(), line.to_string())),
+            None => {
+                let (verb, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+                if rest.trim() == "(" {
+                    block = Some(verb.to_string());
+                } else {
+                    directives.push((verb.to_string(), rest.trim().to_string()));
+                }
+            }
+        }
+    }
+    directives
+}
+
+fn parse_requirement(arguments: &str) -> Option<Requirement> {
+    let (arguments, comment) = arguments.split_once("//").unwrap_or((arguments, ""));
+    let mut parts = arguments.split_whitespace();
+    Some(Requirement {
+        path: parts.next()?.trim_matches('"').to_string(),
+        version: parts.next()?.to_string(),
+        indirect: comment.trim() == "indirect",
+    })
+}
+
+fn parse_replacement(arguments: &str) -> Option<Replacement> {
+    let (old, new) = arguments.split_once("=>")?;
+    let mut old = old.split_whitespace();
+    let mut new = new.split_whitespace();
+    Some(Replacement {
+        path: old.next()?.to_string(),
+        version: old.next().map(str::to_string),
+        new_path: new.next()?.to_string(),
+        new_version: new.next().map(str::to_string),
+    })
+}
+
+/// Reads the requirements of `go.mod`, applying `replace` directives. `go.sum` is not read since it also lists
+/// modules that are no longer required and modules of which only `go.mod` was needed.
+/// Modules replaced by local directories are considered part of the project.
+pub fn read(dir: &Path) -> Option<DependencyGraph> {
+    let directives = directives(&fs::read_to_string(dir.join("go.mod")).ok()?);
+    let replacements: Vec<Replacement> = directives.iter()
+        .filter(|(verb, _)| verb == "replace")
+        .filter_map(|(_, arguments)| parse_replacement(arguments))
+        .collect();
+    let requirements: Vec<Requirement> = directives.iter()
+        .filter(|(verb, _)| verb == "require")
+        .filter_map(|(_, arguments)| parse_requirement(arguments))
+        .collect();
+
+    let mut graph = DependencyGraph { lockfile: PathBuf::from("go.mod"), ..Default::default() };
+    for requirement in requirements {
+        let replacement = replacements.iter().find(|replacement| replacement.path == requirement.path
+            && replacement.version.as_ref().is_none_or(|version| *version == requirement.version));
+        let (path, version) = match replacement {
+            Some(Replacement { new_version: None, .. }) => continue,
+            Some(Replacement { new_path, new_version: Some(new_version), .. }) => (new_path.clone(), new_version.clone()),
+            None => (requirement.path, requirement.version)
+        };
+        if !requirement.indirect {
+            graph.direct.push((graph.packages.len(), DependencyKind::Normal));
+        }
+        graph.packages.push(Dependency {
+            purl: Some(format!("pkg:golang/{}@{}", purl::percent_encode(&path), purl::percent_encode(&version))),
+            download_location: Some(format!("{}/{}/@v/{}.zip", PROXY, escape(&path), escape(&version))),
+            name: path,
+            version,
+            checksums: Vec::new(),
+            license: None,
+            dependencies: Vec::new(),
+        });
+    }
+    Some(graph)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deps::project;
+
+    const GO_MOD: &str = "module example.com/app
+
+go 1.21
+
+require (
+\tgithub.com/BurntSushi/toml v1.3.2
+\tgolang.org/x/text v0.14.0 // indirect
+\texample.com/local v0.0.0
+\t\"example.com/quoted\" v1.0.0
+)
+
+require example.com/old v1.0.0
+
+replace example.com/old v1.0.0 => example.com/new v2.0.0
+replace example.com/local => ../local
+";
+
+    #[test]
+    fn directives_expand_blocks() {
+        let directives = directives(GO_MOD);
+        assert_eq!(directives[0], ("module".to_string(), "example.com/app".to_string()));
+        assert_eq!(directives.iter().filter(|(verb, _)| verb == "require").count(), 5);
+        assert_eq!(directives.iter().filter(|(verb, _)| verb == "replace").count(), 2);
+    }
+
+    #[test]
+    fn requirements_after_replacements() {
+        let dir = project("go-modules", &[
+            ("go.mod", GO_MOD),
+            // Only the modules required in go.mod are dependencies
+            ("go.sum", "github.com/BurntSushi/toml v1.3.2 h1:AAAA=\n\
+                github.com/BurntSushi/toml v1.3.1 h1:BBBB=\n\
+                example.com/unused v1.0.0/go.mod h1:CCCC=\n"),
+        ]);
+        let graph = read(&dir).unwrap();
+        let packages: Vec<(&str, &str, Option<&str>)> = graph.packages.iter()
+            .map(|package| (package.name.as_str(), package.version.as_str(), package.download_location.as_deref()))
+            .collect();
+        assert_eq!(packages, vec![
+            ("github.com/BurntSushi/toml", "v1.3.2", Some("https://proxy.golang.org/github.com/!burnt!sushi/toml/@v/v1.3.2.zip")),
+            ("golang.org/x/text", "v0.14.0", Some("https://proxy.golang.org/golang.org/x/text/@v/v0.14.0.zip")),
+            ("example.com/quoted", "v1.0.0", Some("https://proxy.golang.org/example.com/quoted/@v/v1.0.0.zip")),
+            ("example.com/new", "v2.0.0", Some("https://proxy.golang.org/example.com/new/@v/v2.0.0.zip")),
+        ]);
+        assert_eq!(graph.packages[0].purl.as_deref(), Some("pkg:golang/github.com/BurntSushi/toml@v1.3.2"));
+        assert_eq!(graph.direct, vec![(0, DependencyKind::Normal), (2, DependencyKind::Normal), (3, DependencyKind::Normal)]);
+    }
+}
@@ -0,0 +1,202 @@
+// Copyright (C) 2022  JohnnyJayJay
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::checksum;
+use crate::checksum::Algorithm;
+use crate::purl;
+use super::{Dependency, DependencyGraph, DependencyKind};
+
+const CENTRAL: &str = "https://repo.maven.apache.org/maven2";
+/// Files that may contain the output of `mvn dependency:list -DoutputFile=...`.
+const DEPENDENCY_LISTS: [&str; 2] = ["dependency-list.txt", "dependencies.txt"];
+
+/// Maven coordinates of an artifact.
+struct Artifact {
+    group: String,
+    artifact: String,
+    extension: String,
+    classifier: Option<String>,
+    version: String,
+}
+
+impl Artifact {
+    /// Path of the artifact in a Maven repository.
+    fn repository_path(&self) -> String {
+        let classifier = self.classifier.as_ref().map_or_else(String::new, |classifier| format!("-{}", classifier));
+        format!("{}/{}/{}/{}-{}{}.{}", self.group.replace('.', "/"), self.artifact, self.version,
+                self.artifact, self.version, classifier, self.extension)
+    }
+
+    fn purl(&self) -> String {
+        let mut qualifiers = Vec::new();
+        if self.extension != "jar" {
+            qualifiers.push(format!("type={}", purl::percent_encode(&self.extension)));
+        }
+        if let Some(classifier) = &self.classifier {
+            qualifiers.push(format!("classifier={}", purl::percent_encode(classifier)));
+        }
+        let purl = format!("pkg:maven/{}/{}@{}", purl::percent_encode(&self.group),
+                           purl::percent_encode(&self.artifact), purl::percent_encode(&self.version));
+        if qualifiers.is_empty() { purl } else { format!("{}?{}", purl, qualifiers.join("&")) }
+    }
+
+    /// Creates the package, with checksums computed from the local Maven repository if the artifact has been downloaded.
+    fn into_dependency(self, local_repository: Option<&Path>) -> Dependency {
+        let path = self.repository_path();
+        let checksums = local_repository.map(|repository| repository.join(&path))
+            .filter(|file| file.is_file())
+            .and_then(|file| checksum::compute_file(&file, &[Algorithm::Sha1, Algorithm::Sha256]).ok())
+            .unwrap_or_default();
+        Dependency {
+            purl: Some(self.purl()),
+            download_location: Some(format!("{}/{}", CENTRAL, path)),
+            name: format!("{}:{}", self.group, self.artifact),
+            version: self.version,
+            checksums,
+            license: None,
+            dependencies: Vec::new(),
+        }
+    }
+}
+
+fn local_repository() -> Option<PathBuf> {
+    let home = env::var_os("HOME").or_else(|| env::var_os("USERPROFILE"))?;
+    Some(Path::new(&home).join(".m2").join("repository")).filter(|repository| repository.is_dir())
+}
+
+fn scope_kind(scope: &str) -> DependencyKind {
+    match scope {
+        "test" => DependencyKind::Test,
+        "provided" => DependencyKind::Provided,
+        "runtime" => DependencyKind::Runtime,
+        _ => DependencyKind::Normal
+    }
+}
+
+/// Parses a line like `org.slf4j:slf4j-api:jar:1.7.36:compile -- module org.slf4j` into the artifact and its scope.
+/// Lines copied from the console output may start with `[INFO]`.
+fn parse_list_entry(line: &str) -> Option<(Artifact, &str)> {
+    let coordinates = line.trim_start_matches("[INFO]").split_whitespace().next()?;
+    let parts: Vec<&str> = coordinates.split(':').collect();
+    let (classifier, version, scope) = match parts.len() {
+        5 => (None, parts[3], parts[4]),
+        6 => (Some(parts[3].to_string()), parts[4], parts[5]),
+        _ => return None
+    };
+    Some((Artifact {
+        group: parts[0].to_string(),
+        artifact: parts[1].to_string(),
+        extension: parts[2].to_string(),
+        classifier,
+        version: version.to_string(),
+    }, scope))
+}
+
+/// Reads the output of `mvn dependency:list`. The output does not tell direct and transitive dependencies apart,
+/// so all of them are related to the project according to their scope.
+pub fn read_dependency_list(dir: &Path) -> Option<DependencyGraph> {
+    let file = DEPENDENCY_LISTS.iter().find(|file| dir.join(file).is_file())?;
+    let content = fs::read_to_string(dir.join(file)).ok()?;
+    let repository = local_repository();
+    let mut graph = DependencyGraph { lockfile: PathBuf::from(file), ..Default::default() };
+    for (artifact, scope) in content.lines().filter_map(parse_list_entry) {
+        graph.direct.push((graph.packages.len(), scope_kind(scope)));
+        graph.packages.push(artifact.into_dependency(repository.as_deref()));
+    }
+    Some(graph)
+}
+
+/// Reads `gradle.lockfile`, where each line has the form `group:artifact:version=configuration,...`.
+/// Artifacts that are only on test classpaths are test dependencies, those only on runtime classpaths runtime dependencies.
+pub fn read_gradle_lockfile(dir: &Path) -> Option<DependencyGraph> {
+    let content = fs::read_to_string(dir.join("gradle.lockfile")).ok()?;
+    let repository = local_repository();
+    let mut graph = DependencyGraph { lockfile: PathBuf::from("gradle.lockfile"), ..Default::default() };
+    for line in content.lines().filter(|line| !line.starts_with('#') && !line.starts_with("empty=")) {
+        let (coordinates, configurations) = match line.split_once('=') {
+            Some(split) => split,
+            None => continue
+        };
+        let parts: Vec<&str> = coordinates.split(':').collect();
+        if parts.len() != 3 {
+            continue;
+        }
+        let configurations: Vec<&str> = configurations.split(',').map(str::trim).collect();
+        let kind = if configurations.iter().all(|configuration| configuration.starts_with("test")) {
+            DependencyKind::Test
+        } else if configurations.iter().all(|configuration| configuration.to_lowercase().contains("runtime")) {
+            DependencyKind::Runtime
+        } else {
+            DependencyKind::Normal
+        };
+        let artifact = Artifact {
+            group: parts[0].to_string(),
+            artifact: parts[1].to_string(),
+            extension: "jar".to_string(),
+            classifier: None,
+            version: parts[2].to_string(),
+        };
+        graph.direct.push((graph.packages.len(), kind));
+        graph.packages.push(artifact.into_dependency(repository.as_deref()));
+    }
+    Some(graph)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deps::project;
+
+    #[test]
+    fn dependency_list_entries() {
+        let cases = [
+            ("   org.slf4j:slf4j-api:jar:1.7.36:compile -- module org.slf4j", Some(("org.slf4j:slf4j-api", "1.7.36", "compile",
+                "pkg:maven/org.slf4j/slf4j-api@1.7.36", "org/slf4j/slf4j-api/1.7.36/slf4j-api-1.7.36.jar"))),
+            ("[INFO]    io.netty:netty-transport-native-epoll:jar:linux-x86_64:4.1.100.Final:runtime",
+                Some(("io.netty:netty-transport-native-epoll", "4.1.100.Final", "runtime",
+                "pkg:maven/io.netty/netty-transport-native-epoll@4.1.100.Final?classifier=linux-x86_64",
+                "io/netty/netty-transport-native-epoll/4.1.100.Final/netty-transport-native-epoll-4.1.100.Final-linux-x86_64.jar"))),
+            ("   com.acme:bom:pom:1.0:provided", Some(("com.acme:bom", "1.0", "provided",
+                "pkg:maven/com.acme/bom@1.0?type=pom", "com/acme/bom/1.0/bom-1.0.pom"))),
+            ("The following files have been resolved:", None),
+            ("   org.slf4j:slf4j-api:1.7.36", None),
+        ];
+        for (line, expected) in cases {
+            let parsed = parse_list_entry(line).map(|(artifact, scope)| (format!("{}:{}", artifact.group, artifact.artifact),
+                artifact.version.clone(), scope, artifact.purl(), artifact.repository_path()));
+            let parsed = parsed.as_ref().map(|(name, version, scope, purl, path)|
+                (name.as_str(), version.as_str(), *scope, purl.as_str(), path.as_str()));
+            assert_eq!(parsed, expected, "{}", line);
+        }
+    }
+
+    #[test]
+    fn dependency_list_scopes() {
+        let dir = project("maven-list", &[("dependency-list.txt", "\nThe following files have been resolved:\n\
+            \x20  org.slf4j:slf4j-api:jar:1.7.36:compile\n\
+            \x20  junit:junit:jar:4.13.2:test\n\
+            \x20  javax.servlet:servlet-api:jar:2.5:provided\n")]);
+        let graph = read_dependency_list(&dir).unwrap();
+        assert_eq!(graph.lockfile, PathBuf::from("dependency-list.txt"));
+        assert_eq!(graph.packages[0].download_location.as_deref(),
+            Some("https://repo.maven.apache.org/maven2/org/slf4j/slf4j-api/1.7.36/slf4j-api-1.7.36.jar"));
+        assert_eq!(graph.direct, vec![(0, DependencyKind::Normal), (1, DependencyKind::Test), (2, DependencyKind::Provided)]);
+    }
+
+    #[test]
+    fn gradle_lockfile_configurations() {
+        let dir = project("maven-gradle", &[("gradle.lockfile", "\
+# This is a Gradle generated file for dependency locking.
+com.google.guava:guava:32.1.2-jre=compileClasspath,runtimeClasspath
+junit:junit:4.13.2=testCompileClasspath,testRuntimeClasspath
+org.postgresql:postgresql:42.6.0=runtimeClasspath
+empty=annotationProcessor
+")]);
+        let graph = read_gradle_lockfile(&dir).unwrap();
+        let names: Vec<(&str, &str)> = graph.packages.iter().map(|package| (package.name.as_str(), package.version.as_str())).collect();
+        assert_eq!(names, vec![("com.google.guava:guava", "32.1.2-jre"), ("junit:junit", "4.13.2"), ("org.postgresql:postgresql", "42.6.0")]);
+        assert_eq!(graph.direct, vec![(0, DependencyKind::Normal), (1, DependencyKind::Test), (2, DependencyKind::Runtime)]);
+    }
+}
@@ -0,0 +1,821 @@
+// Copyright (C) 2022  JohnnyJayJay
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use serde_json::Value as Json;
+use serde_yaml::Value as Yaml;
+use crate::checksum;
+use crate::checksum::{Algorithm, Checksum};
+use crate::purl::Ecosystem;
+use crate::glob;
+use super::{normalise_license, Dependency, DependencyGraph, DependencyKind};
+
+const REGISTRY: &str = "https://registry.npmjs.org";
+
+/// A package from one of the lockfile formats.
+#[derive(Debug, Default)]
+struct Entry {
+    name: String,
+    version: String,
+    /// Download URL of the package tarball
+    resolved: Option<String>,
+    checksums: Vec<Checksum>,
+    license: Option<String>,
+    /// Where the package is installed, relative to the project directory
+    install_path: PathBuf,
+    dependencies: Vec<usize>,
+}
+
+/// Dependency declarations of a `package.json` (or of the root package in `package-lock.json`), as pairs of name and range.
+#[derive(Debug, Default)]
+struct Manifest {
+    dependencies: Vec<(String, String, DependencyKind)>,
+}
+
+impl Manifest {
+    fn from_json(package: &Json) -> Self {
+        let sections = [
+            ("dependencies", DependencyKind::Normal),
+            ("optionalDependencies", DependencyKind::Optional),
+            ("devDependencies", DependencyKind::Dev),
+        ];
+        let mut dependencies: Vec<(String, String, DependencyKind)> = Vec::new();
+        for (section, kind) in sections {
+            for (name, range) in package.get(section).and_then(Json::as_object).into_iter().flatten() {
+                // A package listed in several sections counts as a regular dependency
+                if !dependencies.iter().any(|(existing, _, _)| existing == name) {
+                    dependencies.push((name.clone(), range.as_str().unwrap_or_default().to_string(), kind));
+                }
+            }
+        }
+        Manifest { dependencies }
+    }
+
+    fn read(dir: &Path) -> Self {
+        fs::read_to_string(dir.join("package.json")).ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .map(|package: Json| Manifest::from_json(&package))
+            .unwrap_or_default()
+    }
+
+    fn kind_of(&self, name: &str) -> DependencyKind {
+        self.dependencies.iter()
+            .find(|(dependency, _, _)| dependency == name)
+            .map_or(DependencyKind::Normal, |(_, _, kind)| *kind)
+    }
+}
+
+/// Splits a specifier such as `@scope/name@^1.0.0` into name and range.
+fn split_spec(spec: &str) -> Option<(&str, &str)> {
+    let at = spec.get(1..)?.find('@')? + 1;
+    Some((&spec[..at], &spec[at + 1..]))
+}
+
+fn tarball_url(name: &str, version: &str) -> String {
+    let base_name = name.rsplit('/').next().unwrap_or(name);
+    format!("{}/{}/-/{}-{}.tgz", REGISTRY, name, base_name, version)
+}
+
+fn json_str(value: &Json, key: &str) -> Option<String> {
+    value.get(key)?.as_str().map(str::to_string)
+}
+
+fn yaml_str(value: &Yaml, key: &str) -> Option<String> {
+    value.get(key)?.as_str().map(str::to_string)
+}
+
+/// The `license` field of a `package.json`, also accepting the deprecated object and `licenses` array forms.
+fn manifest_license(package: &Json) -> Option<String> {
+    let license = match package.get("license") {
+        Some(Json::String(license)) => license.clone(),
+        Some(license) => json_str(license, "type")?,
+        None => {
+            let types: Vec<&str> = package.get("licenses")?.as_array()?.iter()
+                .filter_map(|license| license.get("type")?.as_str())
+                .collect();
+            types.join(" OR ")
+        }
+    };
+    normalise_license(&license)
+}
+
+/// Reads the license of an installed package, provided the installed version is the locked one.
+fn installed_license(dir: &Path, entry: &Entry) -> Option<String> {
+    let content = fs::read_to_string(dir.join(&entry.install_path).join("package.json")).ok()?;
+    let package: Json = serde_json::from_str(&content).ok()?;
+    if package.get("version")?.as_str()? == entry.version {
+        manifest_license(&package)
+    } else {
+        None
+    }
+}
+
+fn into_graph(dir: &Path, lockfile: &str, entries: Vec<Entry>, direct: Vec<(usize, DependencyKind)>) -> DependencyGraph {
+    let packages = entries.into_iter()
+        .map(|entry| Dependency {
+            // The scope of a package is the namespace of its purl
+            purl: Some(Ecosystem::Npm.purl(dir, &entry.name, Some(&entry.version))),
+            download_location: entry.resolved.clone()
+                .filter(|url| url.starts_with("https://") || url.starts_with("http://") || url.starts_with("git+")),
+            license: entry.license.clone().or_else(|| installed_license(dir, &entry)),
+            name: entry.name,
+            version: entry.version,
+            checksums: entry.checksums,
+            dependencies: entry.dependencies,
+        })
+        .collect();
+    let mut unique_direct: Vec<(usize, DependencyKind)> = Vec::new();
+    for (index, kind) in direct {
+        if !unique_direct.iter().any(|(existing, _)| *existing == index) {
+            unique_direct.push((index, kind));
+        }
+    }
+    DependencyGraph { lockfile: PathBuf::from(lockfile), packages, direct: unique_direct }
+}
+
+/// Finds the package `name` would resolve to when required from the package installed at `from`,
+/// following the `node_modules` lookup algorithm of Node.js.
+fn resolve_path(index: &HashMap<String, usize>, from: &str, name: &str) -> Option<usize> {
+    let mut base = from;
+    loop {
+        let candidate = if base.is_empty() {
+            format!("node_modules/{}", name)
+        } else {
+            format!("{}/node_modules/{}", base, name)
+        };
+        if let Some(found) = index.get(&candidate) {
+            return Some(*found);
+        }
+        if base.is_empty() {
+            return None;
+        }
+        base = base.rfind("/node_modules/").map_or("", |pos| &base[..pos]);
+    }
+}
+
+/// Collects the nested `dependencies` of a version 1 `package-lock.json` with their install paths.
+fn flatten_v1<'a>(dependencies: Option<&'a Json>, prefix: &str, out: &mut Vec<(String, &'a Json)>) {
+    for (name, entry) in dependencies.and_then(Json::as_object).into_iter().flatten() {
+        let path = format!("{}node_modules/{}", prefix, name);
+        out.push((path.clone(), entry));
+        flatten_v1(entry.get("dependencies"), &format!("{}/", path), out);
+    }
+}
+
+/// Reads `package-lock.json` (or `npm-shrinkwrap.json`) in the formats of lockfile versions 1 to 3.
+/// Only the dependencies of the workspace member in `member` are direct dependencies.
+pub fn read_package_lock(dir: &Path, member: &Path) -> Option<DependencyGraph> {
+    let lockfile = ["package-lock.json", "npm-shrinkwrap.json"].iter().find(|name| dir.join(name).is_file())?;
+    let lock: Json = serde_json::from_str(&fs::read_to_string(dir.join(lockfile)).ok()?).ok()?;
+    let mut installed: Vec<(String, &Json)> = Vec::new();
+    let member = glob::slash_path(member);
+    // The dependencies of the member, which is a workspace member or the root package
+    let mut manifest = Manifest::default();
+    // Version 1 lists dependencies with `requires`, later versions like package.json
+    let dependency_keys: &[&str] = match lock.get("packages").and_then(Json::as_object) {
+        Some(packages) => {
+            for (path, entry) in packages {
+                if entry.get("link").and_then(Json::as_bool) == Some(true) {
+                    continue;
+                }
+                if path.contains("node_modules/") {
+                    installed.push((path.clone(), entry));
+                } else if *path == member {
+                    manifest = Manifest::from_json(entry);
+                }
+            }
+            &["dependencies", "optionalDependencies"]
+        }
+        None => {
+            flatten_v1(lock.get("dependencies"), "", &mut installed);
+            manifest = Manifest::read(&dir.join(&member));
+            &["requires"]
+        }
+    };
+
+    let index: HashMap<String, usize> = installed.iter().enumerate()
+        .map(|(i, (path, _))| (path.clone(), i))
+        .collect();
+    let entries: Vec<Entry> = installed.iter()
+        .map(|(path, entry)| Entry {
+            name: json_str(entry, "name")
+                .unwrap_or_else(|| path.rsplit("node_modules/").next().unwrap_or(path).to_string()),
+            version: json_str(entry, "version").unwrap_or_default(),
+            resolved: json_str(entry, "resolved"),
+            checksums: json_str(entry, "integrity").map(|integrity| checksum::from_integrity(&integrity)).unwrap_or_default(),
+            license: json_str(entry, "license").and_then(|license| normalise_license(&license)),
+            install_path: PathBuf::from(path),
+            dependencies: dependency_keys.iter()
+                .flat_map(|key| entry.get(*key).and_then(Json::as_object))
+                .flat_map(|dependencies| dependencies.keys())
+                .filter_map(|name| resolve_path(&index, path, name))
+                .collect(),
+        })
+        .collect();
+    let direct = manifest.dependencies.iter()
+        .filter_map(|(name, _, kind)| Some((resolve_path(&index, &member, name)?, *kind)))
+        .collect();
+    Some(into_graph(dir, lockfile, entries, direct))
+}
+
+/// A package in `yarn.lock` with the specifiers that resolve to it and the specifiers of its dependencies.
+type YarnBlock = (Vec<String>, Entry, Vec<String>);
+
+/// A workspace in a Yarn Berry `yarn.lock` with its path (empty for the root) and the specifiers of its dependencies.
+type YarnWorkspace = (String, Vec<String>);
+
+fn unquote(value: &str) -> &str {
+    value.trim().trim_matches('"')
+}
+
+/// Parses a classic (v1) `yarn.lock`. Returns the packages and the specifiers that resolve to each of them.
+fn parse_yarn_classic(content: &str) -> Vec<YarnBlock> {
+    let mut blocks: Vec<YarnBlock> = Vec::new();
+    let mut in_dependencies = false;
+    for line in content.lines().filter(|line| !line.trim().is_empty() && !line.starts_with('#')) {
+        if !line.starts_with(' ') {
+            let specs: Vec<String> = line.trim_end_matches(':').split(", ").map(|spec| unquote(spec).to_string()).collect();
+            let name = specs.first().and_then(|spec| split_spec(spec)).map(|(name, _)| name.to_string()).unwrap_or_default();
+            let install_path = PathBuf::from("node_modules").join(&name);
+            blocks.push((specs, Entry { name, install_path, ..Default::default() }, Vec::new()));
+            continue;
+        }
+        let (_, entry, dependencies) = match blocks.last_mut() {
+            Some(block) => block,
+            None => continue
+        };
+        if line.starts_with("    ") {
+            if in_dependencies {
+                if let Some((name, range)) = line.trim().split_once(' ') {
+                    dependencies.push(format!("{}@{}", unquote(name), unquote(range)));
+                }
+            }
+        } else if let Some(section) = line.trim().strip_suffix(':') {
+            in_dependencies = section == "dependencies" || section == "optionalDependencies";
+        } else if let Some((key, value)) = line.trim().split_once(' ') {
+            in_dependencies = false;
+            let value = unquote(value);
+            match key {
+                "version" => entry.version = value.to_string(),
+                "integrity" => entry.checksums = checksum::from_integrity(value),
+                "resolved" => {
+                    let (url, fragment) = value.split_once('#').unwrap_or((value, ""));
+                    entry.resolved = Some(url.to_string());
+                    if entry.checksums.is_empty() && fragment.len() == 40 {
+                        entry.checksums.push(Checksum { algorithm: Algorithm::Sha1, value: fragment.to_string() });
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    blocks
+}
+
+/// Normalises a range in a Yarn Berry lockfile so it matches the specifiers used as keys, e.g. `^1.0.0` to `npm:^1.0.0`.
+fn berry_range(range: &str) -> String {
+    if range.contains(':') {
+        range.to_string()
+    } else {
+        format!("npm:{}", range)
+    }
+}
+
+/// Parses a Yarn Berry (v2+) `yarn.lock`, which is YAML. Workspace entries are returned separately.
+/// The `checksum` of an entry is left out: it is the hash of the archive in Yarn's cache, not of the downloaded tarball.
+fn parse_yarn_berry(content: &str) -> Option<(Vec<YarnBlock>, Vec<YarnWorkspace>)> {
+    let lock: Yaml = serde_yaml::from_str(content).ok()?;
+    let mut blocks = Vec::new();
+    let mut workspace_dependencies = Vec::new();
+    for (key, value) in lock.as_mapping()? {
+        let key = match key.as_str() {
+            Some("__metadata") | None => continue,
+            Some(key) => key
+        };
+        let resolution = yaml_str(value, "resolution").unwrap_or_default();
+        let dependencies: Vec<String> = ["dependencies", "optionalDependencies"].iter()
+            .filter_map(|section| value.get(*section)?.as_mapping())
+            .flatten()
+            .filter_map(|(name, range)| Some(format!("{}@{}", name.as_str()?, berry_range(range.as_str()?))))
+            .collect();
+        if let Some((_, path)) = resolution.split_once("@workspace:") {
+            let path = if path == "." { "" } else { path };
+            workspace_dependencies.push((path.to_string(), dependencies));
+            continue;
+        }
+        let specs: Vec<String> = key.split(", ").map(|spec| spec.trim().to_string()).collect();
+        let name = split_spec(&resolution).map(|(name, _)| name.to_string()).unwrap_or_default();
+        let version = yaml_str(value, "version").unwrap_or_default();
+        let entry = Entry {
+            resolved: Some(tarball_url(&name, &version)).filter(|_| resolution.contains("@npm:")),
+            install_path: PathBuf::from("node_modules").join(&name),
+            name,
+            version,
+            ..Default::default()
+        };
+        blocks.push((specs, entry, dependencies));
+    }
+    Some((blocks, workspace_dependencies))
+}
+
+/// Reads `yarn.lock` in the classic format of Yarn 1 or the YAML format of Yarn 2 and later.
+/// Only the dependencies of the workspace member in `member` are direct dependencies.
+pub fn read_yarn_lock(dir: &Path, member: &Path) -> Option<DependencyGraph> {
+    let content = fs::read_to_string(dir.join("yarn.lock")).ok()?;
+    let manifest = Manifest::read(&dir.join(member));
+    let (blocks, direct_specs) = if content.contains("__metadata:") {
+        let member = glob::slash_path(member);
+        let (blocks, workspaces) = parse_yarn_berry(&content)?;
+        let specs = workspaces.into_iter()
+            .find(|(path, _)| *path == member)
+            .map(|(_, specs)| specs)
+            .unwrap_or_default();
+        (blocks, specs)
+    } else {
+        let specs = manifest.dependencies.iter().map(|(name, range, _)| format!("{}@{}", name, range)).collect();
+        (parse_yarn_classic(&content), specs)
+    };
+    let index: HashMap<&str, usize> = blocks.iter().enumerate()
+        .flat_map(|(i, (specs, _, _))| specs.iter().map(move |spec| (spec.as_str(), i)))
+        .collect();
+    let direct = direct_specs.iter()
+        .filter_map(|spec| Some((*index.get(spec.as_str())?, manifest.kind_of(split_spec(spec)?.0))))
+        .collect();
+    let entries = blocks.iter()
+        .map(|(_, entry, dependencies)| Entry {
+            name: entry.name.clone(),
+            version: entry.version.clone(),
+            resolved: entry.resolved.clone(),
+            checksums: entry.checksums.clone(),
+            license: entry.license.clone(),
+            install_path: entry.install_path.clone(),
+            dependencies: dependencies.iter().filter_map(|spec| index.get(spec.as_str()).copied()).collect(),
+        })
+        .collect();
+    Some(into_graph(dir, "yarn.lock", entries, direct))
+}
+
+/// Name and version of a package key in `pnpm-lock.yaml`. Version 5 keys look like `/@scope/name/1.0.0_peer@1.0.0`,
+/// later ones like `/@scope/name@1.0.0(peer@1.0.0)` (version 6) or `@scope/name@1.0.0(peer@1.0.0)` (version 9).
+fn pnpm_key(key: &str, v5: bool) -> Option<(String, String)> {
+    let key = key.trim_start_matches('/');
+    if v5 {
+        let slash = key.rfind('/')?;
+        let version = key[slash + 1..].split('_').next()?;
+        Some((key[..slash].to_string(), version.to_string()))
+    } else {
+        let (name, version) = split_spec(key.split('(').next()?)?;
+        Some((name.to_string(), version.to_string()))
+    }
+}
+
+/// Resolves a dependency `name: version` in `pnpm-lock.yaml`, where the version may be a package key for aliases.
+fn pnpm_dependency(name: &str, version: &str, v5: bool) -> Option<(String, String)> {
+    if version.starts_with("link:") || version.starts_with("file:") {
+        None
+    } else if version.starts_with('/') || (!v5 && split_spec(version.split('(').next()?).is_some()) {
+        pnpm_key(version, v5)
+    } else {
+        let version = if v5 { version.split('_').next()? } else { version.split('(').next()? };
+        Some((name.to_string(), version.to_string()))
+    }
+}
+
+/// Where a package in `pnpm-lock.yaml` comes from, given its `resolution`. Only packages from the registry have no type
+/// and no tarball. Local directories have no download location.
+fn pnpm_download_location(resolution: &Yaml, name: &str, version: &str) -> Option<String> {
+    if let Some(tarball) = yaml_str(resolution, "tarball") {
+        return Some(tarball);
+    }
+    match yaml_str(resolution, "type").as_deref() {
+        None => Some(tarball_url(name, version)),
+        Some("git") => {
+            let repo = yaml_str(resolution, "repo").filter(|repo| repo.contains("://"))?;
+            let commit = yaml_str(resolution, "commit").map(|commit| format!("@{}", commit)).unwrap_or_default();
+            Some(format!("git+{}{}", repo, commit))
+        }
+        Some(_) => None
+    }
+}
+
+/// Reads `pnpm-lock.yaml` in the formats of lockfile versions 5, 6 and 9.
+/// Only the dependencies of the importer of `member` are direct dependencies.
+pub fn read_pnpm_lock(dir: &Path, member: &Path) -> Option<DependencyGraph> {
+    let lock: Yaml = serde_yaml::from_str(&fs::read_to_string(dir.join("pnpm-lock.yaml")).ok()?).ok()?;
+    let v5 = match lock.get("lockfileVersion")? {
+        Yaml::Number(version) => version.as_f64().is_some_and(|version| version < 6.0),
+        version => version.as_str().is_some_and(|version| version.starts_with('5')),
+    };
+
+    let mut entries: Vec<Entry> = Vec::new();
+    let mut index: HashMap<(String, String), usize> = HashMap::new();
+    let mut dependency_lists: Vec<Vec<(String, String)>> = Vec::new();
+    let dependencies_of = |value: &Yaml| -> Vec<(String, String)> {
+        ["dependencies", "optionalDependencies"].iter()
+            .filter_map(|section| value.get(*section)?.as_mapping())
+            .flatten()
+            .filter_map(|(name, version)| pnpm_dependency(name.as_str()?, version.as_str()?, v5))
+            .collect()
+    };
+    for (key, value) in lock.get("packages").and_then(Yaml::as_mapping).into_iter().flatten() {
+        let (name, version) = match key.as_str().and_then(|key| pnpm_key(key, v5)) {
+            Some(key) => key,
+            None => continue
+        };
+        let position = *index.entry((name.clone(), version.clone())).or_insert_with(|| {
+            let resolution = value.get("resolution");
+            entries.push(Entry {
+                resolved: resolution.and_then(|resolution| pnpm_download_location(resolution, &name, &version)),
+                checksums: resolution.and_then(|resolution| yaml_str(resolution, "integrity"))
+                    .map(|integrity| checksum::from_integrity(&integrity))
+                    .unwrap_or_default(),
+                install_path: PathBuf::from("node_modules").join(".pnpm")
+                    .join(format!("{}@{}", name.replace('/', "+"), version))
+                    .join("node_modules").join(&name),
+                name: name.clone(),
+                version: version.clone(),
+                ..Default::default()
+            });
+            dependency_lists.push(Vec::new());
+            entries.len() - 1
+        });
+        dependency_lists[position].extend(dependencies_of(value));
+    }
+    // Since version 9, the dependencies of each package are listed separately
+    for (key, value) in lock.get("snapshots").and_then(Yaml::as_mapping).into_iter().flatten() {
+        if let Some(position) = key.as_str().and_then(|key| pnpm_key(key, v5)).and_then(|key| index.get(&key)) {
+            dependency_lists[*position].extend(dependencies_of(value));
+        }
+    }
+    for (entry, dependencies) in entries.iter_mut().zip(dependency_lists) {
+        entry.dependencies = dependencies.iter().filter_map(|key| index.get(key).copied()).collect();
+        entry.dependencies.dedup();
+    }
+
+    // Importers are keyed by the path of the workspace member, with `.` for the root
+    let importer = match glob::slash_path(member).as_str() {
+        "" => lock.get("importers").and_then(|importers| importers.get(".")).unwrap_or(&lock),
+        member => lock.get("importers")?.get(member)?
+    };
+    let sections = [
+        ("dependencies", DependencyKind::Normal),
+        ("optionalDependencies", DependencyKind::Optional),
+        ("devDependencies", DependencyKind::Dev),
+    ];
+    let mut direct = Vec::new();
+    for (section, kind) in sections {
+        for (name, value) in importer.get(section).and_then(Yaml::as_mapping).into_iter().flatten() {
+            // Since version 6, direct dependencies are given with their specifier
+            let version = value.as_str().or_else(|| value.get("version")?.as_str());
+            let key = name.as_str().zip(version).and_then(|(name, version)| pnpm_dependency(name, version, v5));
+            if let Some(found) = key.and_then(|key| index.get(&key)) {
+                direct.push((*found, kind));
+            }
+        }
+    }
+    Some(into_graph(dir, "pnpm-lock.yaml", entries, direct))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deps::project;
+
+    const BERRY_LOCK: &str = r#"__metadata:
+  version: 8
+  cacheKey: 10c0
+
+"@acme/left-pad@npm:^1.0.0":
+  version: 1.0.0
+  resolution: "@acme/left-pad@npm:1.0.0"
+  checksum: 10c0/abababababababababababababababababababababababababababababababababababababababababababababababababababababababababababababababab
+  languageName: node
+  linkType: hard
+
+"app@workspace:.":
+  version: 0.0.0-use.local
+  resolution: "app@workspace:."
+  dependencies:
+    "@acme/left-pad": "npm:^1.0.0"
+  languageName: unknown
+  linkType: soft
+"#;
+
+    #[test]
+    fn yarn_berry_entries() {
+        let (blocks, workspace_dependencies) = parse_yarn_berry(BERRY_LOCK).unwrap();
+        assert_eq!(workspace_dependencies, vec![(String::new(), vec!["@acme/left-pad@npm:^1.0.0".to_string()])]);
+        assert_eq!(blocks.len(), 1);
+        let (specs, entry, dependencies) = &blocks[0];
+        assert_eq!(specs, &vec!["@acme/left-pad@npm:^1.0.0"]);
+        assert_eq!((entry.name.as_str(), entry.version.as_str()), ("@acme/left-pad", "1.0.0"));
+        assert_eq!(entry.resolved.as_deref(), Some("https://registry.npmjs.org/@acme/left-pad/-/left-pad-1.0.0.tgz"));
+        // The checksum of Yarn's cache archive would not match the tarball
+        assert!(entry.checksums.is_empty());
+        assert!(dependencies.is_empty());
+    }
+
+    #[test]
+    fn yarn_berry_licenses_and_scoped_purls() {
+        let dir = project("npm-yarn-berry", &[
+            ("package.json", r#"{ "name": "app", "dependencies": { "@acme/left-pad": "^1.0.0" } }"#),
+            ("yarn.lock", BERRY_LOCK),
+            ("node_modules/@acme/left-pad/package.json", r#"{ "name": "@acme/left-pad", "version": "1.0.0", "license": "MIT" }"#),
+        ]);
+        let graph = read_yarn_lock(&dir, Path::new("")).unwrap();
+        assert_eq!(graph.direct, vec![(0, DependencyKind::Normal)]);
+        let package = &graph.packages[0];
+        assert_eq!(package.purl.as_deref(), Some("pkg:npm/%40acme/left-pad@1.0.0"));
+        assert_eq!(package.license.as_deref(), Some("MIT"));
+    }
+
+    const PNPM_V9_LOCK: &str = r#"lockfileVersion: '9.0'
+
+importers:
+
+  .:
+    dependencies:
+      from-git:
+        specifier: github:acme/from-git#abc
+        version: git+https://github.com/acme/from-git.git#abc
+      from-tarball:
+        specifier: https://example.com/from-tarball-1.0.0.tgz
+        version: https://example.com/from-tarball-1.0.0.tgz
+      left-pad:
+        specifier: ^1.3.0
+        version: 1.3.0
+      local:
+        specifier: file:packages/local
+        version: file:packages/local
+
+packages:
+
+  from-git@git+https://github.com/acme/from-git.git#abc:
+    resolution: {type: git, repo: 'https://github.com/acme/from-git.git', commit: abc}
+    version: 2.0.0
+
+  from-tarball@https://example.com/from-tarball-1.0.0.tgz:
+    resolution: {tarball: https://example.com/from-tarball-1.0.0.tgz}
+    version: 1.0.0
+
+  left-pad@1.3.0:
+    resolution: {integrity: sha1-AAAAAAAAAAAAAAAAAAAAAAAAAAA=}
+
+  local@file:packages/local:
+    resolution: {directory: packages/local, type: directory}
+
+snapshots:
+
+  from-git@git+https://github.com/acme/from-git.git#abc: {}
+
+  from-tarball@https://example.com/from-tarball-1.0.0.tgz: {}
+
+  left-pad@1.3.0: {}
+
+  local@file:packages/local: {}
+"#;
+
+    #[test]
+    fn pnpm_download_locations() {
+        let dir = project("npm-pnpm-v9", &[("pnpm-lock.yaml", PNPM_V9_LOCK)]);
+        let graph = read_pnpm_lock(&dir, Path::new("")).unwrap();
+        let locations: Vec<(&str, Option<&str>)> = graph.packages.iter()
+            .map(|package| (package.name.as_str(), package.download_location.as_deref()))
+            .collect();
+        assert_eq!(locations, vec![
+            ("from-git", Some("git+https://github.com/acme/from-git.git@abc")),
+            ("from-tarball", Some("https://example.com/from-tarball-1.0.0.tgz")),
+            ("left-pad", Some("https://registry.npmjs.org/left-pad/-/left-pad-1.3.0.tgz")),
+            // A local directory can't be downloaded
+            ("local", None),
+        ]);
+        assert_eq!(graph.packages[2].checksums, vec![Checksum { algorithm: Algorithm::Sha1, value: "0".repeat(40) }]);
+        assert_eq!(graph.direct, vec![(0, DependencyKind::Normal), (1, DependencyKind::Normal), (2, DependencyKind::Normal)]);
+    }
+
+    #[test]
+    fn pnpm_importers_of_workspace_members() {
+        let dir = project("npm-pnpm-workspace", &[("pnpm-lock.yaml", r#"lockfileVersion: '9.0'
+
+importers:
+
+  .:
+    devDependencies:
+      left-pad:
+        specifier: ^1.3.0
+        version: 1.3.0
+
+  packages/a:
+    dependencies:
+      right-pad:
+        specifier: ^1.0.0
+        version: 1.0.0
+
+packages:
+
+  left-pad@1.3.0:
+    resolution: {integrity: sha1-AAAAAAAAAAAAAAAAAAAAAAAAAAA=}
+
+  right-pad@1.0.0:
+    resolution: {integrity: sha1-AAAAAAAAAAAAAAAAAAAAAAAAAAA=}
+
+snapshots:
+
+  left-pad@1.3.0: {}
+
+  right-pad@1.0.0: {}
+"#)]);
+        let root = read_pnpm_lock(&dir, Path::new("")).unwrap();
+        assert_eq!(root.direct, vec![(0, DependencyKind::Dev)]);
+        let member = read_pnpm_lock(&dir, Path::new("packages/a")).unwrap();
+        assert_eq!(member.direct, vec![(1, DependencyKind::Normal)]);
+        assert!(read_pnpm_lock(&dir, Path::new("packages/b")).is_none());
+    }
+
+    fn names(graph: &DependencyGraph) -> Vec<(&str, &str)> {
+        graph.packages.iter().map(|package| (package.name.as_str(), package.version.as_str())).collect()
+    }
+
+    #[test]
+    fn specifiers_are_split() {
+        let cases = [
+            ("left-pad@^1.0.0", Some(("left-pad", "^1.0.0"))),
+            ("@acme/left-pad@npm:1.0.0", Some(("@acme/left-pad", "npm:1.0.0"))),
+            ("left-pad", None),
+            ("@acme/left-pad", None),
+        ];
+        for (spec, expected) in cases {
+            assert_eq!(split_spec(spec), expected, "{}", spec);
+        }
+    }
+
+    #[test]
+    fn package_lock_v1() {
+        let dir = project("npm-package-lock-v1", &[
+            ("package.json", r#"{ "name": "app", "dependencies": { "a": "^1.0.0" }, "devDependencies": { "b": "^1.0.0" } }"#),
+            ("package-lock.json", r#"{
+  "name": "app",
+  "lockfileVersion": 1,
+  "dependencies": {
+    "a": {
+      "version": "1.0.0",
+      "resolved": "https://registry.npmjs.org/a/-/a-1.0.0.tgz",
+      "integrity": "sha1-AAAAAAAAAAAAAAAAAAAAAAAAAAA=",
+      "requires": { "c": "^2.0.0" },
+      "dependencies": {
+        "c": { "version": "2.0.0", "resolved": "https://registry.npmjs.org/c/-/c-2.0.0.tgz" }
+      }
+    },
+    "b": { "version": "1.0.0", "dev": true, "resolved": "https://registry.npmjs.org/b/-/b-1.0.0.tgz" },
+    "c": { "version": "1.0.0", "resolved": "https://registry.npmjs.org/c/-/c-1.0.0.tgz" }
+  }
+}"#),
+        ]);
+        let graph = read_package_lock(&dir, Path::new("")).unwrap();
+        assert_eq!(names(&graph), vec![("a", "1.0.0"), ("c", "2.0.0"), ("b", "1.0.0"), ("c", "1.0.0")]);
+        // The copy nested in `a` is the one it requires
+        assert_eq!(graph.packages[0].dependencies, vec![1]);
+        assert_eq!(graph.packages[0].checksums, vec![Checksum { algorithm: Algorithm::Sha1, value: "0".repeat(40) }]);
+        assert_eq!(graph.direct, vec![(0, DependencyKind::Normal), (2, DependencyKind::Dev)]);
+    }
+
+    #[test]
+    fn package_lock_v3() {
+        let dir = project("npm-package-lock-v3", &[("package-lock.json", r#"{
+  "name": "app",
+  "lockfileVersion": 3,
+  "packages": {
+    "": { "name": "app", "dependencies": { "a": "^1.0.0" }, "optionalDependencies": { "b": "^1.0.0" } },
+    "node_modules/a": {
+      "version": "1.0.0",
+      "resolved": "https://registry.npmjs.org/a/-/a-1.0.0.tgz",
+      "license": "MIT",
+      "dependencies": { "c": "^2.0.0" }
+    },
+    "node_modules/a/node_modules/c": { "version": "2.0.0", "resolved": "https://registry.npmjs.org/c/-/c-2.0.0.tgz" },
+    "node_modules/b": { "version": "1.0.0", "resolved": "git+ssh://git@github.com/acme/b.git#abc", "optional": true },
+    "node_modules/local": { "resolved": "packages/local", "link": true },
+    "packages/local": { "name": "local", "version": "0.1.0", "dependencies": { "a": "^1.0.0" } }
+  }
+}"#)]);
+        let graph = read_package_lock(&dir, Path::new("")).unwrap();
+        assert_eq!(names(&graph), vec![("a", "1.0.0"), ("c", "2.0.0"), ("b", "1.0.0")]);
+        assert_eq!(graph.packages[0].license.as_deref(), Some("MIT"));
+        assert_eq!(graph.packages[0].dependencies, vec![1]);
+        assert_eq!(graph.packages[2].download_location.as_deref(), Some("git+ssh://git@github.com/acme/b.git#abc"));
+        assert_eq!(graph.direct, vec![(0, DependencyKind::Normal), (2, DependencyKind::Optional)]);
+        // The workspace member only depends on `a`
+        let member = read_package_lock(&dir, Path::new("packages/local")).unwrap();
+        assert_eq!(member.direct, vec![(0, DependencyKind::Normal)]);
+    }
+
+    #[test]
+    fn yarn_classic() {
+        let dir = project("npm-yarn-classic", &[
+            ("package.json", r#"{ "name": "app", "dependencies": { "a": "^1.0.0" }, "devDependencies": { "@acme/b": "^1.0.0" } }"#),
+            ("yarn.lock", r#"# THIS IS AN AUTOGENERATED FILE. DO NOT EDIT THIS FILE DIRECTLY.
+# yarn lockfile v1
+
+
+"@acme/b@^1.0.0":
+  version "1.0.0"
+  resolved "https://registry.yarnpkg.com/@acme/b/-/b-1.0.0.tgz#0000000000000000000000000000000000000000"
+
+a@^1.0.0, a@^1.0.1:
+  version "1.0.1"
+  resolved "https://registry.yarnpkg.com/a/-/a-1.0.1.tgz#1111111111111111111111111111111111111111"
+  integrity sha512-AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA==
+  dependencies:
+    "@acme/b" "^1.0.0"
+"#),
+        ]);
+        let graph = read_yarn_lock(&dir, Path::new("")).unwrap();
+        assert_eq!(names(&graph), vec![("@acme/b", "1.0.0"), ("a", "1.0.1")]);
+        // Without an integrity, the SHA-1 in the URL fragment is the checksum
+        assert_eq!(graph.packages[0].checksums, vec![Checksum { algorithm: Algorithm::Sha1, value: "0".repeat(40) }]);
+        assert_eq!(graph.packages[0].download_location.as_deref(), Some("https://registry.yarnpkg.com/@acme/b/-/b-1.0.0.tgz"));
+        assert_eq!(graph.packages[1].checksums, vec![Checksum { algorithm: Algorithm::Sha512, value: "0".repeat(128) }]);
+        assert_eq!(graph.packages[1].dependencies, vec![0]);
+        assert_eq!(graph.direct, vec![(1, DependencyKind::Normal), (0, DependencyKind::Dev)]);
+    }
+
+    #[test]
+    fn pnpm_v5_and_v6_keys() {
+        let cases = [
+            ("/left-pad/1.3.0", true, Some(("left-pad", "1.3.0"))),
+            ("/@acme/b/1.0.0_react@18.2.0", true, Some(("@acme/b", "1.0.0"))),
+            ("/left-pad@1.3.0", false, Some(("left-pad", "1.3.0"))),
+            ("/@acme/b@1.0.0(react@18.2.0)", false, Some(("@acme/b", "1.0.0"))),
+            ("@acme/b@1.0.0(react@18.2.0)", false, Some(("@acme/b", "1.0.0"))),
+            ("left-pad", true, None),
+        ];
+        for (key, v5, expected) in cases {
+            let parsed = pnpm_key(key, v5);
+            assert_eq!(parsed.as_ref().map(|(name, version)| (name.as_str(), version.as_str())), expected, "{}", key);
+        }
+    }
+
+    #[test]
+    fn pnpm_v5() {
+        let dir = project("npm-pnpm-v5", &[("pnpm-lock.yaml", r#"lockfileVersion: 5.4
+
+specifiers:
+  a: ^1.0.0
+  b: ^1.0.0
+
+dependencies:
+  a: 1.0.0_react@18.2.0
+
+devDependencies:
+  b: /c/2.0.0
+
+packages:
+
+  /a/1.0.0_react@18.2.0:
+    resolution: {integrity: sha1-AAAAAAAAAAAAAAAAAAAAAAAAAAA=}
+    dependencies:
+      c: 2.0.0
+
+  /c/2.0.0:
+    resolution: {integrity: sha1-AAAAAAAAAAAAAAAAAAAAAAAAAAA=}
+    dev: true
+"#)]);
+        let graph = read_pnpm_lock(&dir, Path::new("")).unwrap();
+        assert_eq!(names(&graph), vec![("a", "1.0.0"), ("c", "2.0.0")]);
+        assert_eq!(graph.packages[0].dependencies, vec![1]);
+        // `b` is an alias of `c`
+        assert_eq!(graph.direct, vec![(0, DependencyKind::Normal), (1, DependencyKind::Dev)]);
+    }
+
+    #[test]
+    fn pnpm_v6() {
+        let dir = project("npm-pnpm-v6", &[("pnpm-lock.yaml", r#"lockfileVersion: '6.0'
+
+dependencies:
+  a:
+    specifier: ^1.0.0
+    version: 1.0.0(react@18.2.0)
+
+optionalDependencies:
+  '@acme/c':
+    specifier: ^2.0.0
+    version: 2.0.0
+
+packages:
+
+  /a@1.0.0(react@18.2.0):
+    resolution: {integrity: sha1-AAAAAAAAAAAAAAAAAAAAAAAAAAA=}
+    dependencies:
+      '@acme/c': 2.0.0
+
+  /@acme/c@2.0.0:
+    resolution: {integrity: sha1-AAAAAAAAAAAAAAAAAAAAAAAAAAA=}
+"#)]);
+        let graph = read_pnpm_lock(&dir, Path::new("")).unwrap();
+        assert_eq!(names(&graph), vec![("a", "1.0.0"), ("@acme/c", "2.0.0")]);
+        assert_eq!(graph.packages[1].download_location.as_deref(), Some("https://registry.npmjs.org/@acme/c/-/c-2.0.0.tgz"));
+        assert_eq!(graph.packages[0].dependencies, vec![1]);
+        assert_eq!(graph.direct, vec![(0, DependencyKind::Normal), (1, DependencyKind::Optional)]);
+    }
+}
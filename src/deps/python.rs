@@ -0,0 +1,422 @@
+// Copyright (C) 2022  JohnnyJayJay
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml::Value;
+use crate::checksum::{Algorithm, Checksum};
+use crate::purl;
+use super::{Dependency, DependencyGraph, DependencyKind};
+
+/// Normalises a distribution name as described in PEP 503, e.g. `Foo_Bar` to `foo-bar`.
+fn normalise_name(name: &str) -> String {
+    let mut normalised = String::with_capacity(name.len());
+    for c in name.trim().chars() {
+        if c == '-' || c == '_' || c == '.' {
+            if !normalised.ends_with('-') {
+                normalised.push('-');
+            }
+        } else {
+            normalised.push(c.to_ascii_lowercase());
+        }
+    }
+    normalised
+}
+
+/// The distribution name at the start of a PEP 508 requirement, e.g. `requests` in `requests[socks]>=2.0`.
+fn requirement_name(requirement: &str) -> String {
+    let end = requirement.find(|c: char| !(c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.'))
+        .unwrap_or(requirement.len());
+    normalise_name(&requirement[..end])
+}
+
+fn sha256(hash: &str) -> Option<Checksum> {
+    hash.strip_prefix("sha256:").map(|value| Checksum { algorithm: Algorithm::Sha256, value: value.to_string() })
+}
+
+fn string(table: &Value, key: &str) -> Option<String> {
+    table.get(key)?.as_str().map(str::to_string)
+}
+
+fn new_dependency(name: &str, version: &str) -> Dependency {
+    let name = normalise_name(name);
+    Dependency {
+        purl: Some(format!("pkg:pypi/{}@{}", purl::percent_encode(&name), purl::percent_encode(version))),
+        name,
+        version: version.to_string(),
+        checksums: Vec::new(),
+        download_location: None,
+        license: None,
+        dependencies: Vec::new(),
+    }
+}
+
+/// Direct dependencies declared in `pyproject.toml`, in PEP 621 or Poetry style.
+fn declared_dependencies(dir: &Path) -> Vec<(String, DependencyKind)> {
+    let pyproject: Value = match fs::read_to_string(dir.join("pyproject.toml")).ok().and_then(|content| content.parse().ok()) {
+        Some(pyproject) => pyproject,
+        None => return Vec::new()
+    };
+    let mut declared: Vec<(String, DependencyKind)> = Vec::new();
+    let mut add = |name: String, kind: DependencyKind| if name != "python" && !declared.iter().any(|(existing, _)| *existing == name) {
+        declared.push((name, kind));
+    };
+    let project = pyproject.get("project");
+    let poetry = pyproject.get("tool").and_then(|tool| tool.get("poetry"));
+    let requirements = |value: Option<&Value>| -> Vec<String> {
+        value.and_then(Value::as_array).into_iter().flatten()
+            .filter_map(Value::as_str)
+            .map(requirement_name)
+            .collect()
+    };
+    let table_keys = |value: Option<&Value>| -> Vec<String> {
+        value.and_then(Value::as_table).into_iter().flatten().map(|(name, _)| normalise_name(name)).collect()
+    };
+
+    for name in requirements(project.and_then(|project| project.get("dependencies"))) {
+        add(name, DependencyKind::Normal);
+    }
+    for name in table_keys(poetry.and_then(|poetry| poetry.get("dependencies"))) {
+        add(name, DependencyKind::Normal);
+    }
+    for extra in project.and_then(|project| project.get("optional-dependencies")).and_then(Value::as_table).into_iter().flatten() {
+        for name in requirements(Some(extra.1)) {
+            add(name, DependencyKind::Optional);
+        }
+    }
+    for name in table_keys(poetry.and_then(|poetry| poetry.get("dev-dependencies"))) {
+        add(name, DependencyKind::Dev);
+    }
+    for group in poetry.and_then(|poetry| poetry.get("group")).and_then(Value::as_table).into_iter().flatten() {
+        for name in table_keys(group.1.get("dependencies")) {
+            add(name, DependencyKind::Dev);
+        }
+    }
+    for group in pyproject.get("dependency-groups").and_then(Value::as_table).into_iter().flatten() {
+        for name in requirements(Some(group.1)) {
+            add(name, DependencyKind::Dev);
+        }
+    }
+    declared
+}
+
+fn into_graph(lockfile: &str, packages: Vec<Dependency>, dependency_names: Vec<Vec<String>>, declared: Vec<(String, DependencyKind)>) -> DependencyGraph {
+    let index: HashMap<String, usize> = packages.iter().enumerate().map(|(i, package)| (package.name.clone(), i)).collect();
+    let mut graph = DependencyGraph { lockfile: PathBuf::from(lockfile), packages, direct: Vec::new() };
+    for (package, names) in graph.packages.iter_mut().zip(dependency_names) {
+        package.dependencies = names.iter().filter_map(|name| index.get(name).copied()).collect();
+    }
+    graph.direct = declared.iter()
+        .filter_map(|(name, kind)| index.get(name).map(|found| (*found, *kind)))
+        .collect();
+    graph
+}
+
+/// Reads `poetry.lock`. Checksum and download location are taken from the source distribution.
+pub fn read_poetry_lock(dir: &Path) -> Option<DependencyGraph> {
+    let lock: Value = fs::read_to_string(dir.join("poetry.lock")).ok()?.parse().ok()?;
+    let mut packages = Vec::new();
+    let mut dependency_names = Vec::new();
+    for package in lock.get("package")?.as_array()? {
+        let (name, version) = match (string(package, "name"), string(package, "version")) {
+            (Some(name), Some(version)) => (name, version),
+            _ => continue
+        };
+        let mut dependency = new_dependency(&name, &version);
+        let files = package.get("files").and_then(Value::as_array).map(Vec::as_slice).unwrap_or_default();
+        if let Some(sdist) = files.iter().find(|file| string(file, "file").is_some_and(|file| file.ends_with(".tar.gz"))) {
+            let file_name = string(sdist, "file").unwrap_or_default();
+            dependency.download_location = Some(format!("https://files.pythonhosted.org/packages/source/{}/{}/{}",
+                name.chars().next().unwrap_or_default(), name, file_name));
+            dependency.checksums.extend(string(sdist, "hash").as_deref().and_then(sha256));
+        }
+        if let Some(source) = package.get("source").filter(|source| string(source, "type").as_deref() == Some("git")) {
+            dependency.purl = None;
+            dependency.download_location = string(source, "url").map(|url| match string(source, "resolved_reference") {
+                Some(commit) => format!("git+{}@{}", url, commit),
+                None => format!("git+{}", url)
+            });
+        }
+        dependency_names.push(table_names(package.get("dependencies")));
+        packages.push(dependency);
+    }
+    let mut declared = declared_dependencies(dir);
+    // Older versions of Poetry record whether a package is only needed for development
+    if declared.is_empty() {
+        for package in lock.get("package")?.as_array()? {
+            if let Some(name) = string(package, "name") {
+                let kind = if string(package, "category").as_deref() == Some("dev") { DependencyKind::Dev } else { DependencyKind::Normal };
+                declared.push((normalise_name(&name), kind));
+            }
+        }
+        let required: Vec<&String> = dependency_names.iter().flatten().collect();
+        declared.retain(|(name, _)| !required.contains(&name));
+    }
+    Some(into_graph("poetry.lock", packages, dependency_names, declared))
+}
+
+fn table_names(table: Option<&Value>) -> Vec<String> {
+    table.and_then(Value::as_table).into_iter().flatten().map(|(name, _)| normalise_name(name)).collect()
+}
+
+/// Names in a uv dependency list such as `dependencies = [{ name = "idna" }]`.
+fn uv_names(list: Option<&Value>) -> Vec<String> {
+    list.and_then(Value::as_array).into_iter().flatten()
+        .filter_map(|dependency| string(dependency, "name"))
+        .map(|name| normalise_name(&name))
+        .collect()
+}
+
+/// Reads `uv.lock`. The project itself is the package with an editable or virtual source.
+pub fn read_uv_lock(dir: &Path) -> Option<DependencyGraph> {
+    let lock: Value = fs::read_to_string(dir.join("uv.lock")).ok()?.parse().ok()?;
+    let mut packages = Vec::new();
+    let mut dependency_names = Vec::new();
+    let mut declared = Vec::new();
+    for package in lock.get("package")?.as_array()? {
+        let name = match string(package, "name") {
+            Some(name) => name,
+            None => continue
+        };
+        let source = package.get("source");
+        let is_project = source.is_some_and(|source| source.get("editable").is_some() || source.get("virtual").is_some());
+        let mut names = uv_names(package.get("dependencies"));
+        if is_project {
+            declared.extend(names.into_iter().map(|name| (name, DependencyKind::Normal)));
+            for extra in package.get("optional-dependencies").and_then(Value::as_table).into_iter().flatten() {
+                declared.extend(uv_names(Some(extra.1)).into_iter().map(|name| (name, DependencyKind::Optional)));
+            }
+            for group in package.get("dev-dependencies").and_then(Value::as_table).into_iter().flatten() {
+                declared.extend(uv_names(Some(group.1)).into_iter().map(|name| (name, DependencyKind::Dev)));
+            }
+            continue;
+        }
+        let mut dependency = new_dependency(&name, &string(package, "version").unwrap_or_default());
+        if let Some(sdist) = package.get("sdist") {
+            dependency.download_location = string(sdist, "url");
+            dependency.checksums.extend(string(sdist, "hash").as_deref().and_then(sha256));
+        }
+        if let Some(git) = source.and_then(|source| string(source, "git")) {
+            dependency.purl = None;
+            dependency.download_location = Some(match git.split_once('#') {
+                Some((url, commit)) => format!("git+{}@{}", url.split('?').next().unwrap_or(url), commit),
+                None => format!("git+{}", git)
+            });
+        }
+        for extra in package.get("optional-dependencies").and_then(Value::as_table).into_iter().flatten() {
+            names.extend(uv_names(Some(extra.1)));
+        }
+        dependency_names.push(names);
+        packages.push(dependency);
+    }
+    if declared.is_empty() {
+        declared = declared_dependencies(dir);
+    }
+    Some(into_graph("uv.lock", packages, dependency_names, declared))
+}
+
+/// Reads a pinned `requirements.txt` as created by `pip freeze` or `pip-compile`.
+/// When `pip-compile` annotations (`# via ...`) are present, they are used to tell direct and transitive dependencies apart.
+pub fn read_requirements(dir: &Path) -> Option<DependencyGraph> {
+    let content = fs::read_to_string(dir.join("requirements.txt")).ok()?;
+    let mut packages: Vec<Dependency> = Vec::new();
+    // Names of the packages that require each package, according to `# via` comments
+    let mut required_by: Vec<Vec<String>> = Vec::new();
+    let mut annotated = false;
+    let mut in_via = false;
+    let mut logical_line = String::new();
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if let Some(comment) = trimmed.strip_prefix('#') {
+            let comment = comment.trim();
+            if let Some(via) = comment.strip_prefix("via") {
+                annotated = true;
+                in_via = via.trim().is_empty();
+                if let Some(requirers) = required_by.last_mut() {
+                    requirers.extend(Some(via.trim()).filter(|via| !via.is_empty()).map(str::to_string));
+                }
+            } else if in_via {
+                if let Some(requirers) = required_by.last_mut() {
+                    requirers.push(comment.to_string());
+                }
+            }
+            continue;
+        }
+        in_via = false;
+        logical_line.push_str(trimmed.trim_end_matches('\\'));
+        logical_line.push(' ');
+        if trimmed.ends_with('\\') {
+            continue;
+        }
+        let requirement = std::mem::take(&mut logical_line);
+        // The `--hash` options are left out since they can't be told apart by the distribution file they belong to
+        let pin = match requirement.split_whitespace().next() {
+            Some(pin) if !pin.starts_with('-') => pin.split(';').next().unwrap_or(pin),
+            _ => continue
+        };
+        let (name, version) = match pin.split_once("==") {
+            Some((name, version)) => (name.split('[').next().unwrap_or(name), version),
+            None => continue
+        };
+        packages.push(new_dependency(name, version));
+        required_by.push(Vec::new());
+    }
+
+    let names: Vec<String> = packages.iter().map(|package| package.name.clone()).collect();
+    let mut dependency_names: Vec<Vec<String>> = vec![Vec::new(); packages.len()];
+    let mut declared = Vec::new();
+    for (name, requirers) in names.iter().zip(&required_by) {
+        let is_direct = !annotated || requirers.iter().any(|requirer| requirer.starts_with("-r") || !names.contains(&normalise_name(requirer)));
+        if is_direct {
+            declared.push((name.clone(), DependencyKind::Normal));
+        }
+        for requirer in requirers {
+            if let Some(position) = names.iter().position(|other| *other == normalise_name(requirer)) {
+                dependency_names[position].push(name.clone());
+            }
+        }
+    }
+    Some(into_graph("requirements.txt", packages, dependency_names, declared))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deps::project;
+
+    fn names(graph: &DependencyGraph) -> Vec<&str> {
+        graph.packages.iter().map(|package| package.name.as_str()).collect()
+    }
+
+    #[test]
+    fn names_are_normalised() {
+        let cases = [("Foo_Bar", "foo-bar"), ("foo.bar", "foo-bar"), ("foo--bar", "foo-bar"), (" requests ", "requests")];
+        for (name, expected) in cases {
+            assert_eq!(normalise_name(name), expected);
+        }
+        assert_eq!(requirement_name("requests[socks]>=2.0"), "requests");
+        assert_eq!(requirement_name("Typing_Extensions; python_version < '3.8'"), "typing-extensions");
+    }
+
+    #[test]
+    fn poetry_lock() {
+        let dir = project("python-poetry", &[
+            ("pyproject.toml", "[tool.poetry.dependencies]\npython = \"^3.8\"\nrequests = \"^2.31\"\n\n\
+                [tool.poetry.group.dev.dependencies]\npytest = \"^7\"\n"),
+            ("poetry.lock", r#"[[package]]
+name = "requests"
+version = "2.31.0"
+files = [
+    {file = "requests-2.31.0-py3-none-any.whl", hash = "sha256:1111"},
+    {file = "requests-2.31.0.tar.gz", hash = "sha256:2222"},
+]
+
+[package.dependencies]
+idna = ">=2.5,<4"
+
+[[package]]
+name = "idna"
+version = "3.4"
+files = []
+
+[[package]]
+name = "pytest"
+version = "7.4.0"
+files = []
+
+[[package]]
+name = "private"
+version = "1.0.0"
+files = []
+
+[package.source]
+type = "git"
+url = "https://example.com/private.git"
+resolved_reference = "abc"
+"#),
+        ]);
+        let graph = read_poetry_lock(&dir).unwrap();
+        assert_eq!(names(&graph), vec!["requests", "idna", "pytest", "private"]);
+        let requests = &graph.packages[0];
+        assert_eq!(requests.download_location.as_deref(),
+            Some("https://files.pythonhosted.org/packages/source/r/requests/requests-2.31.0.tar.gz"));
+        assert_eq!(requests.checksums, vec![Checksum { algorithm: Algorithm::Sha256, value: "2222".to_string() }]);
+        assert_eq!(requests.purl.as_deref(), Some("pkg:pypi/requests@2.31.0"));
+        assert_eq!(requests.dependencies, vec![1]);
+        let private = &graph.packages[3];
+        assert_eq!(private.download_location.as_deref(), Some("git+https://example.com/private.git@abc"));
+        assert_eq!(private.purl, None);
+        assert_eq!(graph.direct, vec![(0, DependencyKind::Normal), (2, DependencyKind::Dev)]);
+    }
+
+    #[test]
+    fn uv_lock() {
+        let dir = project("python-uv", &[("uv.lock", r#"version = 1
+
+[[package]]
+name = "app"
+version = "0.1.0"
+source = { editable = "." }
+dependencies = [{ name = "httpx" }]
+
+[package.optional-dependencies]
+socks = [{ name = "socksio" }]
+
+[[package]]
+name = "httpx"
+version = "0.27.0"
+source = { registry = "https://pypi.org/simple" }
+dependencies = [{ name = "idna" }]
+sdist = { url = "https://files.pythonhosted.org/packages/httpx-0.27.0.tar.gz", hash = "sha256:3333" }
+
+[[package]]
+name = "idna"
+version = "3.7"
+source = { git = "https://github.com/kjd/idna?tag=v3.7#0123abc" }
+
+[[package]]
+name = "socksio"
+version = "1.0.0"
+source = { registry = "https://pypi.org/simple" }
+"#)]);
+        let graph = read_uv_lock(&dir).unwrap();
+        assert_eq!(names(&graph), vec!["httpx", "idna", "socksio"]);
+        assert_eq!(graph.packages[0].download_location.as_deref(), Some("https://files.pythonhosted.org/packages/httpx-0.27.0.tar.gz"));
+        assert_eq!(graph.packages[0].checksums, vec![Checksum { algorithm: Algorithm::Sha256, value: "3333".to_string() }]);
+        assert_eq!(graph.packages[0].dependencies, vec![1]);
+        assert_eq!(graph.packages[1].download_location.as_deref(), Some("git+https://github.com/kjd/idna@0123abc"));
+        assert_eq!(graph.direct, vec![(0, DependencyKind::Normal), (2, DependencyKind::Optional)]);
+    }
+
+    #[test]
+    fn compiled_requirements() {
+        let dir = project("python-requirements", &[("requirements.txt", "\
+certifi==2024.2.2 \\
+    --hash=sha256:4444 \\
+    --hash=sha256:5555
+    # via requests
+Requests[socks]==2.31.0 ; python_version >= '3.8'
+    # via -r requirements.in
+urllib3==2.2.1
+    # via
+    #   requests
+    #   -r requirements.in
+-e ./local
+")]);
+        let graph = read_requirements(&dir).unwrap();
+        assert_eq!(names(&graph), vec!["certifi", "requests", "urllib3"]);
+        assert_eq!(graph.packages[1].version, "2.31.0");
+        // The hashes of the different distribution files can't be told apart
+        assert!(graph.packages.iter().all(|package| package.checksums.is_empty()));
+        assert_eq!(graph.packages[1].dependencies, vec![0, 2]);
+        assert_eq!(graph.direct, vec![(1, DependencyKind::Normal), (2, DependencyKind::Normal)]);
+    }
+
+    #[test]
+    fn frozen_requirements_are_all_direct() {
+        let dir = project("python-freeze", &[("requirements.txt", "idna==3.7\nrequests==2.31.0\nsetuptools\n")]);
+        let graph = read_requirements(&dir).unwrap();
+        assert_eq!(names(&graph), vec!["idna", "requests"]);
+        assert_eq!(graph.direct, vec![(0, DependencyKind::Normal), (1, DependencyKind::Normal)]);
+    }
+}
@@ -0,0 +1,123 @@
+// Copyright (C) 2022  JohnnyJayJay
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use spdx::identifiers;
+
+/// Minimum similarity for a license text to be considered a match.
+const MIN_CONFIDENCE: f64 = 0.6;
+
+/// A license found in one of the license files of a project.
+#[derive(Debug, Clone)]
+pub struct DetectedLicense {
+    /// Path of the license file, relative to the project directory
+    pub file: PathBuf,
+    pub license: String,
+    /// Similarity between the file and the license text, from 0 to 1
+    pub confidence: f64,
+}
+
+/// Files that usually contain license texts: `LICENSE*`, `LICENCE*` and `COPYING*` in `dir` and everything in `dir/LICENSES`.
+pub fn license_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = fs::read_dir(dir).into_iter().flatten()
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && path.extension().is_none_or(|ext| ext != "spdx"))
+        .filter(|path| path.file_name().and_then(|name| name.to_str()).is_some_and(|name| {
+            let name = name.to_uppercase();
+            name.starts_with("LICENSE") || name.starts_with("LICENCE") || name.starts_with("COPYING")
+        }))
+        .collect();
+    files.extend(fs::read_dir(dir.join("LICENSES")).into_iter().flatten()
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file()));
+    files.sort();
+    files
+}
+
+/// Lowercases the text, drops copyright notices (which differ between projects) and splits it into words.
+fn normalise(text: &str) -> Vec<String> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.to_lowercase().starts_with("copyright"))
+        .flat_map(|line| line.split(|c: char| !c.is_alphanumeric()))
+        .filter(|word| !word.is_empty())
+        .map(|word| match word.to_lowercase().as_str() {
+            "licence" => "license".to_string(),
+            "licenced" => "licensed".to_string(),
+            other => other.to_string()
+        })
+        .collect()
+}
+
+/// Hashes of all pairs of consecutive words in the text.
+fn bigrams(text: &str) -> HashSet<u64> {
+    normalise(text).windows(2)
+        .map(|pair| {
+            let mut hasher = DefaultHasher::new();
+            pair.hash(&mut hasher);
+            hasher.finish()
+        })
+        .collect()
+}
+
+/// Sørensen–Dice coefficient of two bigram sets.
+fn dice(a: &HashSet<u64>, b: &HashSet<u64>) -> f64 {
+    if a.is_empty() && b.is_empty() {
+        return 0.0;
+    }
+    2.0 * a.intersection(b).count() as f64 / (a.len() + b.len()) as f64
+}
+
+/// Files in a REUSE-style `LICENSES` directory are named after their license identifier.
+fn license_from_file_name(file: &Path) -> Option<String> {
+    if file.parent()?.file_name()? != "LICENSES" {
+        return None;
+    }
+    let stem = file.file_stem()?.to_str()?;
+    if spdx::license_id(stem).is_some() || stem.starts_with("LicenseRef-") {
+        Some(stem.to_string())
+    } else {
+        None
+    }
+}
+
+/// Compares the license files of the project in `dir` with the texts of the SPDX license list
+/// and returns the best match for each file that is similar enough to a known license.
+pub fn detect_licenses(dir: &Path) -> Vec<DetectedLicense> {
+    let files = license_files(dir);
+    let mut known: Option<Vec<(&str, HashSet<u64>)>> = None;
+    let mut detected = Vec::new();
+    for file in files {
+        let relative = file.strip_prefix(dir).unwrap_or(&file).to_path_buf();
+        if let Some(license) = license_from_file_name(&file) {
+            detected.push(DetectedLicense { file: relative, license, confidence: 1.0 });
+            continue;
+        }
+        let text = match fs::read_to_string(&file) {
+            Ok(text) => text,
+            Err(_) => continue
+        };
+        let file_bigrams = bigrams(&text);
+        // Texts of deprecated identifiers duplicate their replacements, so they are never suggested
+        let known = known.get_or_insert_with(|| identifiers::LICENSES.iter()
+            .filter(|(_, _, flags)| flags & identifiers::IS_DEPRECATED == 0)
+            .filter_map(|(id, _, _)| spdx::license_id(id))
+            .map(|id| (id.name, bigrams(id.text())))
+            .collect());
+        let best = known.iter()
+            .map(|(id, license_bigrams)| (*id, dice(&file_bigrams, license_bigrams)))
+            .fold(None, |best: Option<(&str, f64)>, (id, score)| match best {
+                Some((_, best_score)) if best_score >= score => best,
+                _ => Some((id, score))
+            });
+        if let Some((license, confidence)) = best.filter(|(_, confidence)| *confidence >= MIN_CONFIDENCE) {
+            detected.push(DetectedLicense { file: relative, license: license.to_string(), confidence });
+        }
+    }
+    detected
+}
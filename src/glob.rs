@@ -0,0 +1,117 @@
+// Copyright (C) 2022  JohnnyJayJay
+
+use std::path::Path;
+
+/// Path with forward slashes, as used in glob patterns and SPDX file names.
+pub fn slash_path(path: &Path) -> String {
+    path.components().map(|c| c.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/")
+}
+
+/// Matches a path against a glob pattern supporting `*`, `**`, `?` and `\` escapes.
+/// `*` and `?` only match within a directory unless `star_matches_slash` is set, and `**/` may also match no directory at all.
+///
+/// Whether the pattern from each position on matches the path from each position on is worked out from the end of
+/// the pattern backwards, so every pair is only looked at once however many stars the pattern has.
+pub fn glob_match(pattern: &[u8], path: &[u8], star_matches_slash: bool) -> bool {
+    let in_directory = |c: u8| star_matches_slash || c != b'/';
+    // matches[i][j]: pattern[i..] matches path[j..]
+    let mut matches = vec![vec![false; path.len() + 1]; pattern.len() + 1];
+    matches[pattern.len()][path.len()] = true;
+    for i in (0..pattern.len()).rev() {
+        let (done, rest) = matches.split_at_mut(i + 1);
+        let row = &mut done[i];
+        match &pattern[i..] {
+            [b'*', b'*', tail @ ..] => {
+                let after = &rest[1];
+                let skip_directory = tail.first() == Some(&b'/');
+                let mut any_after = false;
+                for j in (0..=path.len()).rev() {
+                    any_after |= after[j];
+                    row[j] = any_after || (skip_directory && rest[2][j]);
+                }
+            }
+            [b'*', ..] => {
+                let after = &rest[0];
+                row[path.len()] = after[path.len()];
+                for j in (0..path.len()).rev() {
+                    row[j] = after[j] || (in_directory(path[j]) && row[j + 1]);
+                }
+            }
+            [b'?', ..] => {
+                for j in 0..path.len() {
+                    row[j] = in_directory(path[j]) && rest[0][j + 1];
+                }
+            }
+            [b'\\', c, ..] => {
+                for j in 0..path.len() {
+                    row[j] = path[j] == *c && rest[1][j + 1];
+                }
+            }
+            [c, ..] => {
+                for j in 0..path.len() {
+                    row[j] = path[j] == *c && rest[0][j + 1];
+                }
+            }
+            [] => unreachable!()
+        }
+    }
+    matches[0][0]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn patterns() {
+        let cases = [
+            ("src/main.rs", "src/main.rs", false, true),
+            ("src/main.rs", "src/main.rss", false, false),
+            ("*.rs", "main.rs", false, true),
+            ("*.rs", "src/main.rs", false, false),
+            ("*.rs", "src/main.rs", true, true),
+            ("src/*", "src/main.rs", false, true),
+            ("src/*", "src/bin/main.rs", false, false),
+            ("src/?ain.rs", "src/main.rs", false, true),
+            ("src?main.rs", "src/main.rs", false, false),
+            ("src?main.rs", "src/main.rs", true, true),
+            ("**", "src/bin/main.rs", false, true),
+            ("**/main.rs", "src/bin/main.rs", false, true),
+            // `**/` also matches no directory at all
+            ("**/main.rs", "main.rs", false, true),
+            ("src/**/main.rs", "src/main.rs", false, true),
+            ("src/**/main.rs", "src/bin/main.rs", false, true),
+            ("src/**", "src", false, false),
+            ("src/**.rs", "src/bin/main.rs", false, true),
+            ("\\*.rs", "*.rs", false, true),
+            ("\\*.rs", "main.rs", false, false),
+            ("a\\?", "a?", false, true),
+            ("a\\?", "ab", false, false),
+            // A backslash at the end has nothing to escape
+            ("a\\", "a\\", false, true),
+            ("", "", false, true),
+            ("", "a", false, false),
+            ("*", "", false, true),
+            ("?", "", false, false),
+        ];
+        for (pattern, path, star_matches_slash, expected) in cases {
+            assert_eq!(glob_match(pattern.as_bytes(), path.as_bytes(), star_matches_slash), expected, "{} {}", pattern, path);
+        }
+    }
+
+    #[test]
+    fn many_stars_are_not_exponential() {
+        let path = "a".repeat(200);
+        assert!(!glob_match("*a*a*a*a*a*a*a*a*a*a*a*a*a*a*a*a*b".as_bytes(), path.as_bytes(), false));
+        assert!(!glob_match("**a**a**a**a**a**a**a**a**a**a**b".as_bytes(), path.as_bytes(), false));
+        let path = "a/".repeat(100);
+        assert!(!glob_match("**/**/**/**/**/**/**/**/**/**/b".as_bytes(), path.as_bytes(), false));
+    }
+
+    #[test]
+    fn slash_paths() {
+        assert_eq!(slash_path(Path::new("src/bin/main.rs")), "src/bin/main.rs");
+        assert_eq!(slash_path(Path::new("src//main.rs/")), "src/main.rs");
+        assert_eq!(slash_path(Path::new("")), "");
+    }
+}
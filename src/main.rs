@@ -1,21 +1,40 @@
 // Copyright (C) 2022  JohnnyJayJay
 extern crate core;
 
+mod checksum;
+mod config;
+mod cpe;
+mod deps;
+mod detect;
+mod glob;
 mod model;
+mod prompt;
+mod purl;
+mod reuse;
+mod scan;
+mod session;
+mod snippet;
 mod steps;
 mod vcs;
+mod workspace;
 
 use std::io;
-use std::path::{PathBuf};
-use clap::Parser;
+use std::env;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use clap::{Parser, Subcommand};
 use console::{style, Term};
+use dialoguer::Confirm;
 use dialoguer::theme::ColorfulTheme;
 use i18n_embed::DesktopLanguageRequester;
 use i18n_embed::fluent::{fluent_language_loader, FluentLanguageLoader};
 use i18n_embed_fl::fl;
 use rust_embed::RustEmbed;
+use crate::config::{Config, ConfigError, Profile, CONFIG_FILE, FORMATS};
 use crate::model::SpdxDocument;
-use crate::steps::SetupData;
+use crate::session::Session;
+use crate::snippet::{SnippetError, SnippetRequest};
+use crate::steps::{SetupData, StepError};
 use crate::steps::initial_step;
 use crate::vcs::git::Git;
 use crate::vcs::{Vcs, VcsInfo};
@@ -29,22 +48,70 @@ struct Args {
     #[clap(short, long, default_value = ".")]
     pub dir: PathBuf,
 
-    /// SPDX file to generate/update, relative to --dir
-    #[clap(short, long, default_value = "LICENSE.spdx")]
-    file: String,
+    /// SPDX file to generate/update, relative to --dir [default: output.file from .spdx-guide.toml or LICENSE.spdx]
+    #[clap(short, long)]
+    file: Option<String>,
 
     /// Update the existing .spdx file (e.g. for a new version)
     #[clap(short, long)]
     update: bool,
 
+    /// Check the project for REUSE compliance and take per-file licensing information
+    /// from LICENSES/, .reuse/dep5, REUSE.toml, file headers and .license files
+    #[clap(long)]
+    reuse: bool,
+
+    /// Profile of the user configuration ($XDG_CONFIG_HOME/spdx-guide/config.toml) whose values are suggested first
+    /// [default: default-profile from the user configuration]
+    #[clap(long)]
+    profile: Option<String>,
+
+    /// Write the same file every time for the same answers and commit: the creation time is taken from
+    /// SOURCE_DATE_EPOCH or the HEAD commit, and the namespace is derived from the content
+    #[clap(long)]
+    reproducible: bool,
+
+    #[clap(subcommand)]
+    command: Option<Command>,
+
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Add a snippet (e.g. vendored code under a different license) to an existing SPDX file
+    Snippet {
+        /// File that contains the snippet, relative to --dir. It must already have a file section in the SPDX file
+        path: PathBuf,
+
+        /// Byte range of the snippet, e.g. 310:420 (starting at 1, inclusive)
+        #[clap(long, value_parser = snippet::parse_range)]
+        bytes: Option<(usize, usize)>,
+
+        /// Line range of the snippet, e.g. 5:23 (starting at 1, inclusive)
+        #[clap(long, value_parser = snippet::parse_range)]
+        lines: Option<(usize, usize)>,
+
+        /// License expression the snippet is available under
+        #[clap(short, long)]
+        license: String,
 
+        /// Copyright notice of the snippet
+        #[clap(short, long)]
+        copyright: Option<String>,
+
+        /// Name of the snippet, e.g. the project it was copied from
+        #[clap(short, long)]
+        name: Option<String>,
+    },
 }
 
+const DEFAULT_FILE: &str = "LICENSE.spdx";
+
 #[derive(RustEmbed)]
 #[folder = "i18n"]
 struct Localizations;
 
-fn detect_vcs(path: &PathBuf) -> Option<VcsInfo> {
+fn detect_vcs(path: &Path) -> Option<VcsInfo> {
     let supported_vcs = [Git::open_at];
     for vcs_open in supported_vcs {
         if let Some(vcs) = vcs_open(path) {
@@ -54,11 +121,80 @@ fn detect_vcs(path: &PathBuf) -> Option<VcsInfo> {
     None
 }
 
+fn describe_snippet_error(error: &SnippetError, i18n: &FluentLanguageLoader) -> String {
+    match error {
+        SnippetError::MissingRange => fl!(i18n, "snippet-missing-range"),
+        SnippetError::InvalidRange(start, end) => {
+            let (start, end) = (*start, *end);
+            fl!(i18n, "snippet-invalid-range", start = start, end = end)
+        }
+        SnippetError::OutOfBounds { range: (start, end), size } => {
+            let (start, end, size) = (*start, *end, *size);
+            fl!(i18n, "snippet-out-of-bounds", start = start, end = end, size = size)
+        }
+        SnippetError::InvalidLicense(license) => fl!(i18n, "snippet-invalid-license", expression = license.as_str()),
+        SnippetError::UndescribedFile(file) => fl!(i18n, "snippet-undescribed-file", file = file.display().to_string()),
+        SnippetError::Io(e) => e.to_string(),
+    }
+}
+
+/// Describes an error in the configuration `file`.
+fn describe_config_error(error: &ConfigError, file: &str, i18n: &FluentLanguageLoader) -> String {
+    match error {
+        ConfigError::Syntax(message) => fl!(i18n, "config-syntax", file = file, message = message.as_str()),
+        ConfigError::NotAString(key) => fl!(i18n, "config-not-a-string", file = file, key = key.as_str()),
+        ConfigError::NotStrings(key) => fl!(i18n, "config-not-strings", file = file, key = key.as_str()),
+        ConfigError::InvalidSupplier(supplier) => fl!(i18n, "config-invalid-supplier", file = file, supplier = supplier.as_str()),
+        ConfigError::InvalidLicense(license) => fl!(i18n, "config-invalid-license", file = file, expression = license.as_str()),
+        ConfigError::InvalidNamespace(namespace) => fl!(i18n, "config-invalid-namespace", file = file, namespace = namespace.as_str()),
+        ConfigError::UnknownFormat(format) => fl!(i18n, "config-unknown-format", file = file, format = format.as_str(), formats = FORMATS.join(", ")),
+        ConfigError::UnknownProfile(profile) => fl!(i18n, "config-unknown-profile", file = file, profile = profile.as_str()),
+        ConfigError::Io(e) => format!("{}: {}", file, e),
+    }
+}
+
+fn exit_with_config_error(error: &ConfigError, file: &str, i18n: &FluentLanguageLoader) -> ! {
+    eprintln!("{}: {}", fl!(i18n, "error"), style(describe_config_error(error, file, i18n)).red().bold());
+    // EX_CONFIG
+    std::process::exit(78);
+}
+
+/// The time the document is created at. In reproducible mode, this is `SOURCE_DATE_EPOCH` if it is set,
+/// otherwise the time of the HEAD commit.
+fn creation_time(reproducible: bool, vcs_info: Option<&VcsInfo>, i18n: &FluentLanguageLoader) -> i64 {
+    if !reproducible {
+        return SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |time| time.as_secs() as i64);
+    }
+    let error = match env::var("SOURCE_DATE_EPOCH") {
+        Ok(epoch) => match epoch.trim().parse() {
+            Ok(time) => return time,
+            Err(_) => fl!(i18n, "source-date-epoch-invalid", value = epoch.as_str())
+        },
+        Err(_) => match vcs_info.and_then(|info| info.head_commit_time) {
+            Some(time) => return time,
+            None => fl!(i18n, "reproducible-no-time")
+        }
+    };
+    eprintln!("{}: {}", fl!(i18n, "error"), style(error).red().bold());
+    // EX_USAGE
+    std::process::exit(64);
+}
+
+fn add_snippet(dir: &Path, spdx_file: &Path, request: SnippetRequest, i18n: &FluentLanguageLoader) {
+    match snippet::register(dir, spdx_file, request) {
+        Ok(snippet) => println!("{}", fl!(i18n, "snippet-added", id = format!("{}", style(&snippet.id).green()))),
+        Err(e) => {
+            eprintln!("{}: {}", fl!(i18n, "error"), style(describe_snippet_error(&e, i18n)).red().bold());
+            std::process::exit(1);
+        }
+    }
+}
+
 fn main() -> io::Result<()> {
     let mut args = Args::parse();
     args.dir = args.dir.canonicalize().expect("Unable to canonicalize --dir path");
 
-    let mut term = Term::stderr();
+    let term = Term::stderr();
 
     let path = args.dir;
     let language_loader: FluentLanguageLoader = fluent_language_loader!();
@@ -67,37 +203,83 @@ fn main() -> io::Result<()> {
     let _result = i18n_embed::select(&language_loader, &Localizations, &requested_languages);
     language_loader.set_use_isolating(false);
 
+    let config = Config::load(&path)
+        .unwrap_or_else(|e| exit_with_config_error(&e, CONFIG_FILE, &language_loader));
+    let user_config = config::user_config_file();
+    let profile = Profile::load(user_config.as_deref(), args.profile.as_deref())
+        .unwrap_or_else(|e| exit_with_config_error(&e, &user_config.map(|file| file.display().to_string()).unwrap_or_default(), &language_loader));
+    // Command line arguments take precedence over the configuration
+    let filename = args.file.or_else(|| config.file.clone()).unwrap_or_else(|| DEFAULT_FILE.to_string());
+
+    if let Some(Command::Snippet { path: file, bytes, lines, license, copyright, name }) = args.command {
+        let file = file.strip_prefix(&path).map_or(file.clone(), Path::to_path_buf);
+        let request = SnippetRequest { file, bytes, lines, license, copyright, name };
+        add_snippet(&path, &path.join(&filename), request, &language_loader);
+        return Ok(());
+    }
+
     println!("{}", fl!(language_loader, "detecting-vcs", dir = format!("{}", style(path.display()).blue())));
-    let vcs_info = detect_vcs(&path);
+    let mut vcs_info = detect_vcs(&path);
+    if let Some(info) = vcs_info.as_mut() {
+        info.active_project_authors.retain(|user| !config.is_ignored_author(user));
+        info.oldest_project_authors.retain(|user| !config.is_ignored_author(user));
+    }
     let result = match &vcs_info {
         None => fl!(language_loader, "no-vcs"),
         Some(info) => fl!(language_loader, "found-vcs", name = format!("{}", style(&info.vcs_name).green()))
     };
     println!("{}", result);
+    let created = creation_time(args.reproducible, vcs_info.as_ref(), &language_loader);
 
     //dbg!(&vcs_info);
 
+    // Without a terminal, the prompts would wait for a key press forever
+    if !term.is_term() {
+        let error = StepError::Io(io::Error::new(io::ErrorKind::NotConnected, fl!(language_loader, "no-terminal")));
+        eprintln!("{}: {}", fl!(language_loader, "error"), style(&error).red().bold());
+        std::process::exit(error.exit_code());
+    }
+
     let theme = ColorfulTheme::default();
+    let mut session = Session::default();
+    if let Some(answers) = Session::load(&path) {
+        let count = answers.len();
+        let resume = Confirm::with_theme(&theme)
+            .with_prompt(fl!(language_loader, "resume-prompt", count = count))
+            .default(true)
+            .interact_on(&term)?;
+        if resume {
+            session = Session::resume(answers);
+        }
+    }
     let mut doc = SpdxDocument::default();
     let mut data = SetupData {
         vcs: vcs_info,
         doc: &mut doc,
         creators: Vec::new(),
-        term: &mut term,
+        header_scan: None,
+        reuse_mode: args.reuse,
+        reproducible: args.reproducible,
+        created,
+        reuse: None,
+        config: &config,
+        profile: &profile,
+        prompter: &term,
         dir: &path,
-        filename: args.file,
+        filename,
         i18n: &language_loader,
-        theme: &theme
+        theme: &theme,
+        session,
+        workspace: None,
     };
-    let mut wrapped_step = Some(initial_step());
-    while let Some(ref step) = wrapped_step {
-        match step.run(&mut data) {
-            Ok(next) => { wrapped_step = next; }
-            Err(e) => {
-                data.term.clear_line()?;
-                data.term.write_line(&format!("{}: {}", fl!(data.i18n, "error"), style(e).red().bold()))?;
-            }
+    println!("{}", style(fl!(language_loader, "go-back-hint")).dim());
+    if let Err(e) = steps::run(&mut data, initial_step()) {
+        data.prompter.clear_line()?;
+        match &e {
+            StepError::Aborted => data.prompter.write_line(&fl!(data.i18n, "aborted"))?,
+            e => data.prompter.write_line(&format!("{}: {}", fl!(data.i18n, "error"), style(e).red().bold()))?,
         }
+        std::process::exit(e.exit_code());
     }
     Ok(())
 
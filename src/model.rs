@@ -1,11 +1,17 @@
 // Copyright (C) 2022  JohnnyJayJay
 
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use crate::scan;
+
 #[cfg(windows)]
-const LINE_ENDING : &str = "\r\n";
+pub const LINE_ENDING : &str = "\r\n";
 #[cfg(not(windows))]
-const LINE_ENDING : &str = "\n";
+pub const LINE_ENDING : &str = "\n";
+
+pub const SNIPPET_HEADER: &str = "##### Snippet Information";
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct SpdxEntry {
     tag: String,
     value: String,
@@ -19,14 +25,13 @@ impl SpdxEntry {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum SpdxLine {
-    Empty,
     Comment(String),
     Entry(SpdxEntry),
 }
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone)]
 pub struct SpdxSection {
     lines: Vec<SpdxLine>
 }
@@ -53,52 +58,399 @@ impl SpdxSection {
     }
 
     pub fn find(&self, tag: &str) -> Vec<&str> {
-        (&self.lines).into_iter()
+        self.lines.iter()
             .filter_map(|line| self.value_with_tag(line, tag))
             .collect()
     }
 
+    /// Tag and value of every entry, in order.
+    pub fn entries(&self) -> Vec<(&str, &str)> {
+        self.lines.iter()
+            .filter_map(|line| match line {
+                SpdxLine::Entry(SpdxEntry { tag, value }) => Some((tag.as_str(), value.as_str())),
+                SpdxLine::Comment(_) => None
+            })
+            .collect()
+    }
+
+    /// Replaces the value of the entry at `index` in [`SpdxSection::entries`].
+    pub fn set_value<V: Into<String>>(&mut self, index: usize, value: V) {
+        let entry = self.lines.iter_mut()
+            .filter_map(|line| match line {
+                SpdxLine::Entry(entry) => Some(entry),
+                SpdxLine::Comment(_) => None
+            })
+            .nth(index);
+        if let Some(entry) = entry {
+            entry.value = value.into();
+        }
+    }
+
 }
 
-impl ToString for SpdxSection {
-    fn to_string(&self) -> String {
-        let mut str = String::new();
+impl Display for SpdxSection {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         for line in &self.lines {
             match line {
-                SpdxLine::Empty => {},
-                SpdxLine::Comment(comment) => {
-                    str.push('#');
-                    str.push_str(comment);
-                }
-                SpdxLine::Entry(SpdxEntry { tag, value }) => {
-                    str.push_str(tag);
-                    str.push_str(": ");
-                    str.push_str(value);
-                }
+                SpdxLine::Comment(comment) => write!(f, "#{}", comment)?,
+                SpdxLine::Entry(SpdxEntry { tag, value }) => write!(f, "{}: {}", tag, value)?,
             }
-            str.push_str(LINE_ENDING);
+            f.write_str(LINE_ENDING)?;
+        }
+        Ok(())
+    }
+
+}
+
+/// Value of an `ExternalRef` entry, e.g. `PACKAGE-MANAGER purl pkg:cargo/spdx@0.9.0`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExternalRef {
+    pub category: &'static str,
+    pub ref_type: &'static str,
+    pub locator: String,
+}
+
+impl ExternalRef {
+    pub fn new<L: Into<String>>(category: &'static str, ref_type: &'static str, locator: L) -> Self {
+        ExternalRef {
+            category, ref_type, locator: locator.into()
+        }
+    }
+}
+
+impl Display for ExternalRef {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} {}", self.category, self.ref_type, self.locator)
+    }
+}
+
+/// The type of a relationship between two SPDX elements, as listed in clause 11.1 of the SPDX 2.3 specification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RelationshipType {
+    Describes,
+    DescribedBy,
+    Contains,
+    ContainedBy,
+    DependsOn,
+    DependencyOf,
+    DependencyManifestOf,
+    BuildDependencyOf,
+    DevDependencyOf,
+    OptionalDependencyOf,
+    ProvidedDependencyOf,
+    TestDependencyOf,
+    RuntimeDependencyOf,
+    ExampleOf,
+    Generates,
+    GeneratedFrom,
+    AncestorOf,
+    DescendantOf,
+    VariantOf,
+    DistributionArtifact,
+    PatchFor,
+    PatchApplied,
+    CopyOf,
+    FileAdded,
+    FileDeleted,
+    FileModified,
+    ExpandedFromArchive,
+    DynamicLink,
+    StaticLink,
+    DataFileOf,
+    TestCaseOf,
+    BuildToolOf,
+    DevToolOf,
+    TestOf,
+    TestToolOf,
+    DocumentationOf,
+    OptionalComponentOf,
+    MetafileOf,
+    PackageOf,
+    Amends,
+    PrerequisiteFor,
+    HasPrerequisite,
+    RequirementDescriptionFor,
+    SpecificationFor,
+    Other,
+}
+
+impl RelationshipType {
+    pub const ALL: [RelationshipType; 45] = [
+        RelationshipType::Describes,
+        RelationshipType::DescribedBy,
+        RelationshipType::Contains,
+        RelationshipType::ContainedBy,
+        RelationshipType::DependsOn,
+        RelationshipType::DependencyOf,
+        RelationshipType::DependencyManifestOf,
+        RelationshipType::BuildDependencyOf,
+        RelationshipType::DevDependencyOf,
+        RelationshipType::OptionalDependencyOf,
+        RelationshipType::ProvidedDependencyOf,
+        RelationshipType::TestDependencyOf,
+        RelationshipType::RuntimeDependencyOf,
+        RelationshipType::ExampleOf,
+        RelationshipType::Generates,
+        RelationshipType::GeneratedFrom,
+        RelationshipType::AncestorOf,
+        RelationshipType::DescendantOf,
+        RelationshipType::VariantOf,
+        RelationshipType::DistributionArtifact,
+        RelationshipType::PatchFor,
+        RelationshipType::PatchApplied,
+        RelationshipType::CopyOf,
+        RelationshipType::FileAdded,
+        RelationshipType::FileDeleted,
+        RelationshipType::FileModified,
+        RelationshipType::ExpandedFromArchive,
+        RelationshipType::DynamicLink,
+        RelationshipType::StaticLink,
+        RelationshipType::DataFileOf,
+        RelationshipType::TestCaseOf,
+        RelationshipType::BuildToolOf,
+        RelationshipType::DevToolOf,
+        RelationshipType::TestOf,
+        RelationshipType::TestToolOf,
+        RelationshipType::DocumentationOf,
+        RelationshipType::OptionalComponentOf,
+        RelationshipType::MetafileOf,
+        RelationshipType::PackageOf,
+        RelationshipType::Amends,
+        RelationshipType::PrerequisiteFor,
+        RelationshipType::HasPrerequisite,
+        RelationshipType::RequirementDescriptionFor,
+        RelationshipType::SpecificationFor,
+        RelationshipType::Other
+    ];
+}
+
+impl Display for RelationshipType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            RelationshipType::Describes => "DESCRIBES",
+            RelationshipType::DescribedBy => "DESCRIBED_BY",
+            RelationshipType::Contains => "CONTAINS",
+            RelationshipType::ContainedBy => "CONTAINED_BY",
+            RelationshipType::DependsOn => "DEPENDS_ON",
+            RelationshipType::DependencyOf => "DEPENDENCY_OF",
+            RelationshipType::DependencyManifestOf => "DEPENDENCY_MANIFEST_OF",
+            RelationshipType::BuildDependencyOf => "BUILD_DEPENDENCY_OF",
+            RelationshipType::DevDependencyOf => "DEV_DEPENDENCY_OF",
+            RelationshipType::OptionalDependencyOf => "OPTIONAL_DEPENDENCY_OF",
+            RelationshipType::ProvidedDependencyOf => "PROVIDED_DEPENDENCY_OF",
+            RelationshipType::TestDependencyOf => "TEST_DEPENDENCY_OF",
+            RelationshipType::RuntimeDependencyOf => "RUNTIME_DEPENDENCY_OF",
+            RelationshipType::ExampleOf => "EXAMPLE_OF",
+            RelationshipType::Generates => "GENERATES",
+            RelationshipType::GeneratedFrom => "GENERATED_FROM",
+            RelationshipType::AncestorOf => "ANCESTOR_OF",
+            RelationshipType::DescendantOf => "DESCENDANT_OF",
+            RelationshipType::VariantOf => "VARIANT_OF",
+            RelationshipType::DistributionArtifact => "DISTRIBUTION_ARTIFACT",
+            RelationshipType::PatchFor => "PATCH_FOR",
+            RelationshipType::PatchApplied => "PATCH_APPLIED",
+            RelationshipType::CopyOf => "COPY_OF",
+            RelationshipType::FileAdded => "FILE_ADDED",
+            RelationshipType::FileDeleted => "FILE_DELETED",
+            RelationshipType::FileModified => "FILE_MODIFIED",
+            RelationshipType::ExpandedFromArchive => "EXPANDED_FROM_ARCHIVE",
+            RelationshipType::DynamicLink => "DYNAMIC_LINK",
+            RelationshipType::StaticLink => "STATIC_LINK",
+            RelationshipType::DataFileOf => "DATA_FILE_OF",
+            RelationshipType::TestCaseOf => "TEST_CASE_OF",
+            RelationshipType::BuildToolOf => "BUILD_TOOL_OF",
+            RelationshipType::DevToolOf => "DEV_TOOL_OF",
+            RelationshipType::TestOf => "TEST_OF",
+            RelationshipType::TestToolOf => "TEST_TOOL_OF",
+            RelationshipType::DocumentationOf => "DOCUMENTATION_OF",
+            RelationshipType::OptionalComponentOf => "OPTIONAL_COMPONENT_OF",
+            RelationshipType::MetafileOf => "METAFILE_OF",
+            RelationshipType::PackageOf => "PACKAGE_OF",
+            RelationshipType::Amends => "AMENDS",
+            RelationshipType::PrerequisiteFor => "PREREQUISITE_FOR",
+            RelationshipType::HasPrerequisite => "HAS_PREREQUISITE",
+            RelationshipType::RequirementDescriptionFor => "REQUIREMENT_DESCRIPTION_FOR",
+            RelationshipType::SpecificationFor => "SPECIFICATION_FOR",
+            RelationshipType::Other => "OTHER",
+        })
+    }
+}
+
+/// Value of a `Relationship` entry, e.g. `SPDXRef-DOCUMENT DESCRIBES SPDXRef-Package-spdx-guide`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Relationship {
+    pub element: String,
+    pub relationship_type: RelationshipType,
+    pub related_element: String,
+}
+
+impl Relationship {
+    pub fn new<E: Into<String>, R: Into<String>>(element: E, relationship_type: RelationshipType, related_element: R) -> Self {
+        Relationship {
+            element: element.into(), relationship_type, related_element: related_element.into()
+        }
+    }
+}
+
+impl Display for Relationship {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} {}", self.element, self.relationship_type, self.related_element)
+    }
+}
+
+/// A part of a file that has different licensing than the rest of it, e.g. code copied from another project.
+#[derive(Debug, Clone)]
+pub struct SnippetSection {
+    pub id: String,
+    /// SPDX identifier of the file the snippet is part of
+    pub file_id: String,
+    /// First and last byte of the snippet, starting at 1
+    pub byte_range: (usize, usize),
+    /// First and last line of the snippet, starting at 1
+    pub line_range: Option<(usize, usize)>,
+    pub license_concluded: String,
+    pub copyright_text: Option<String>,
+    pub name: Option<String>,
+}
+
+impl Display for SnippetSection {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut section = SpdxSection::default();
+        section.add_entry("SnippetSPDXID", &self.id);
+        section.add_entry("SnippetFromFileSPDXID", &self.file_id);
+        section.add_entry("SnippetByteRange", format!("{}:{}", self.byte_range.0, self.byte_range.1));
+        if let Some((start, end)) = self.line_range {
+            section.add_entry("SnippetLineRange", format!("{}:{}", start, end));
+        }
+        section.add_entry("SnippetLicenseConcluded", &self.license_concluded);
+        for id in scan::license_ids(&self.license_concluded) {
+            section.add_entry("LicenseInfoInSnippet", id);
+        }
+        section.add_entry("SnippetCopyrightText", self.copyright_text.as_ref()
+            .map_or_else(|| "NOASSERTION".to_string(), |text| format!("<text>{}</text>", text)));
+        if let Some(name) = &self.name {
+            section.add_entry("SnippetName", name);
         }
-        str
+        write!(f, "{}", section)
     }
+}
+
+/// A license that is not on the SPDX license list, referenced as `LicenseRef-...` in license expressions.
+#[derive(Debug, Clone)]
+pub struct ExtractedLicense {
+    pub id: String,
+    pub text: String,
+    pub name: String,
+    pub cross_references: Vec<String>,
+    pub comment: Option<String>,
+}
 
+impl Display for ExtractedLicense {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut section = SpdxSection::default();
+        section.add_entry("LicenseID", &self.id);
+        section.add_entry("ExtractedText", format!("<text>{}</text>", self.text));
+        section.add_entry("LicenseName", &self.name);
+        for url in &self.cross_references {
+            section.add_entry("LicenseCrossReference", url);
+        }
+        if let Some(comment) = &self.comment {
+            section.add_entry("LicenseComment", format!("<text>{}</text>", comment));
+        }
+        write!(f, "{}", section)
+    }
 }
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone)]
 pub struct SpdxDocument {
     pub document_section: SpdxSection,
     pub package_section: SpdxSection,
+    /// Workspace members that were described before the one in `package_section`, in order
+    pub member_sections: Vec<SpdxSection>,
+    /// Packages the described package depends on
+    pub dependency_sections: Vec<SpdxSection>,
+    pub file_sections: Vec<SpdxSection>,
+    pub snippet_sections: Vec<SnippetSection>,
+    pub relationships: Vec<Relationship>,
+    pub extracted_licenses: Vec<ExtractedLicense>,
 }
 
-impl ToString for SpdxDocument {
-    fn to_string(&self) -> String {
-        let mut str = String::new();
-        str.push_str("##### Document Information");
-        str.push_str(LINE_ENDING);
-        str.push_str(&self.document_section.to_string());
-        str.push_str(&LINE_ENDING.repeat(2));
-        str.push_str("##### Package Information");
-        str.push_str(LINE_ENDING);
-        str.push_str(&self.package_section.to_string());
-        str
+impl SpdxDocument {
+    /// SPDX identifiers of the packages, their dependencies and all files described in the document.
+    pub fn element_ids(&self) -> Vec<&str> {
+        self.member_sections.iter().flat_map(|section| section.find("SPDXID"))
+            .chain(self.package_section.find("SPDXID"))
+            .chain(self.dependency_sections.iter().flat_map(|section| section.find("SPDXID")))
+            .chain(self.file_sections.iter().flat_map(|section| section.find("SPDXID")))
+            .collect()
     }
-}
\ No newline at end of file
+
+    /// Whether the file is one of the workspace members in `member_sections` CONTAINS.
+    fn is_member_file(&self, file: &SpdxSection) -> bool {
+        let members: Vec<&str> = self.member_sections.iter().flat_map(|section| section.find("SPDXID")).collect();
+        self.relationships.iter().any(|relationship| relationship.relationship_type == RelationshipType::Contains
+            && members.contains(&relationship.element.as_str())
+            && file.find("SPDXID").contains(&relationship.related_element.as_str()))
+    }
+
+    /// The file sections of the files the package with the SPDX identifier `id` CONTAINS.
+    fn contained_files<'a>(&'a self, id: &'a str) -> impl Iterator<Item = &'a SpdxSection> {
+        self.file_sections.iter().filter(move |file| self.relationships.iter()
+            .any(|relationship| relationship.relationship_type == RelationshipType::Contains
+                && relationship.element == id
+                && file.find("SPDXID").contains(&relationship.related_element.as_str())))
+    }
+
+    /// Sorts the dependencies, files, snippets, relationships and extracted licenses,
+    /// so that their order does not depend on the order in which they were found.
+    pub fn sort(&mut self) {
+        self.dependency_sections.sort_by(|a, b| (a.find("PackageName"), a.find("PackageVersion"))
+            .cmp(&(b.find("PackageName"), b.find("PackageVersion"))));
+        self.file_sections.sort_by(|a, b| a.find("FileName").cmp(&b.find("FileName")));
+        self.snippet_sections.sort_by(|a, b| a.id.cmp(&b.id));
+        self.relationships.sort_by_key(Relationship::to_string);
+        self.extracted_licenses.sort_by(|a, b| a.id.cmp(&b.id));
+    }
+}
+
+/// Writes the sections under a heading, separated by empty lines. Nothing is written if there are no sections.
+fn write_sections<'a, S: Display + 'a>(f: &mut Formatter<'_>, heading: &str, sections: impl IntoIterator<Item = &'a S>) -> fmt::Result {
+    for (i, section) in sections.into_iter().enumerate() {
+        if i == 0 {
+            f.write_str(&LINE_ENDING.repeat(2))?;
+            f.write_str(heading)?;
+            f.write_str(LINE_ENDING)?;
+        } else {
+            f.write_str(LINE_ENDING)?;
+        }
+        write!(f, "{}", section)?;
+    }
+    Ok(())
+}
+
+impl Display for SpdxDocument {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("##### Document Information")?;
+        f.write_str(LINE_ENDING)?;
+        write!(f, "{}", self.document_section)?;
+        // Every workspace member is followed by its own files
+        for section in &self.member_sections {
+            write_sections(f, "##### Package Information", std::iter::once(section))?;
+            let id = section.find("SPDXID").first().copied().unwrap_or_default();
+            write_sections(f, "##### File Information", self.contained_files(id))?;
+        }
+        f.write_str(&LINE_ENDING.repeat(2))?;
+        f.write_str("##### Package Information")?;
+        f.write_str(LINE_ENDING)?;
+        write!(f, "{}", self.package_section)?;
+        for relationship in &self.relationships {
+            write!(f, "Relationship: {}", relationship)?;
+            f.write_str(LINE_ENDING)?;
+        }
+        // In tag-value, files belong to the package before them, so the dependencies have to come after them
+        write_sections(f, "##### File Information", self.file_sections.iter().filter(|file| !self.is_member_file(file)))?;
+        write_sections(f, SNIPPET_HEADER, &self.snippet_sections)?;
+        write_sections(f, "##### Dependency Information", &self.dependency_sections)?;
+        write_sections(f, "##### Other Licensing Information Detected", &self.extracted_licenses)
+    }
+}
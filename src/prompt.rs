@@ -0,0 +1,55 @@
+// Copyright (C) 2022  JohnnyJayJay
+
+use std::io;
+use console::Term;
+use dialoguer::{Confirm, FuzzySelect, Input, MultiSelect, Select};
+
+/// Shows prompts and returns the answers, along with the messages in between. The wizard asks all its questions
+/// and writes all its output through a prompter, so it can be driven by scripted answers instead of a terminal.
+/// `None` means that the user pressed Esc.
+pub trait Prompter {
+    fn select(&self, select: &Select) -> io::Result<Option<usize>>;
+
+    fn fuzzy_select(&self, select: &FuzzySelect) -> io::Result<Option<usize>>;
+
+    fn multi_select(&self, select: &MultiSelect) -> io::Result<Option<Vec<usize>>>;
+
+    fn confirm(&self, confirm: &Confirm) -> io::Result<Option<bool>>;
+
+    fn input(&self, input: &mut Input<String>) -> io::Result<String>;
+
+    fn write_line(&self, line: &str) -> io::Result<()>;
+
+    /// Clears the current line, which may hold what is left of a prompt that was cancelled.
+    fn clear_line(&self) -> io::Result<()>;
+}
+
+impl Prompter for Term {
+    fn select(&self, select: &Select) -> io::Result<Option<usize>> {
+        select.interact_on_opt(self)
+    }
+
+    fn fuzzy_select(&self, select: &FuzzySelect) -> io::Result<Option<usize>> {
+        select.interact_on_opt(self)
+    }
+
+    fn multi_select(&self, select: &MultiSelect) -> io::Result<Option<Vec<usize>>> {
+        select.interact_on_opt(self)
+    }
+
+    fn confirm(&self, confirm: &Confirm) -> io::Result<Option<bool>> {
+        confirm.interact_on_opt(self)
+    }
+
+    fn input(&self, input: &mut Input<String>) -> io::Result<String> {
+        input.interact_on(self)
+    }
+
+    fn write_line(&self, line: &str) -> io::Result<()> {
+        Term::write_line(self, line)
+    }
+
+    fn clear_line(&self) -> io::Result<()> {
+        Term::clear_line(self)
+    }
+}
@@ -0,0 +1,257 @@
+// Copyright (C) 2022  JohnnyJayJay
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+/// A package ecosystem that can be recognised by its manifest file and has a purl type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ecosystem {
+    Cargo,
+    Npm,
+    PyPI,
+    Golang,
+    Maven,
+    Gem,
+    Composer,
+    Hex,
+    Pub,
+    NuGet,
+}
+
+impl Ecosystem {
+    /// Detects the ecosystem of the project in `dir` by looking for well-known manifest files.
+    pub fn detect(dir: &Path) -> Option<Self> {
+        const MANIFESTS: [(&str, Ecosystem); 11] = [
+            ("Cargo.toml", Ecosystem::Cargo),
+            ("package.json", Ecosystem::Npm),
+            ("pyproject.toml", Ecosystem::PyPI),
+            ("setup.py", Ecosystem::PyPI),
+            ("setup.cfg", Ecosystem::PyPI),
+            ("go.mod", Ecosystem::Golang),
+            ("pom.xml", Ecosystem::Maven),
+            ("Gemfile", Ecosystem::Gem),
+            ("composer.json", Ecosystem::Composer),
+            ("mix.exs", Ecosystem::Hex),
+            ("pubspec.yaml", Ecosystem::Pub),
+        ];
+        MANIFESTS.iter()
+            .find(|(file, _)| dir.join(file).is_file())
+            .map(|(_, ecosystem)| *ecosystem)
+            .or_else(|| fs::read_dir(dir).ok()?
+                .filter_map(|entry| entry.ok())
+                .find(|entry| entry.path().extension().is_some_and(|ext| ext == "csproj"))
+                .map(|_| Ecosystem::NuGet))
+    }
+
+    pub fn purl_type(&self) -> &'static str {
+        match self {
+            Ecosystem::Cargo => "cargo",
+            Ecosystem::Npm => "npm",
+            Ecosystem::PyPI => "pypi",
+            Ecosystem::Golang => "golang",
+            Ecosystem::Maven => "maven",
+            Ecosystem::Gem => "gem",
+            Ecosystem::Composer => "composer",
+            Ecosystem::Hex => "hex",
+            Ecosystem::Pub => "pub",
+            Ecosystem::NuGet => "nuget",
+        }
+    }
+
+    /// Builds a purl for the package `name` in this ecosystem.
+    /// Namespaces that can't be derived from the name (Go module path, Maven group id) are read from the manifest in `dir`.
+    pub fn purl(&self, dir: &Path, name: &str, version: Option<&str>) -> String {
+        let (namespace, name) = match self {
+            Ecosystem::Npm | Ecosystem::Composer => match name.rsplit_once('/') {
+                Some((namespace, name)) => (Some(namespace.to_string()), name.to_string()),
+                None => (None, name.to_string())
+            },
+            Ecosystem::PyPI => (None, name.to_lowercase().replace('_', "-")),
+            Ecosystem::Golang => match go_module_path(dir).as_deref().and_then(|path| path.rsplit_once('/')) {
+                Some((namespace, name)) => (Some(namespace.to_string()), name.to_string()),
+                None => (None, name.to_string())
+            },
+            Ecosystem::Maven => (maven_group_id(dir), name.to_string()),
+            _ => (None, name.to_string())
+        };
+        let namespace = namespace
+            .map(|ns| format!("{}/", ns.split('/').map(percent_encode).collect::<Vec<_>>().join("/")))
+            .unwrap_or_default();
+        let version = version.map(|v| format!("@{}", percent_encode(v))).unwrap_or_default();
+        format!("pkg:{}/{}{}{}", self.purl_type(), namespace, percent_encode(&name), version)
+    }
+}
+
+fn go_module_path(dir: &Path) -> Option<String> {
+    fs::read_to_string(dir.join("go.mod")).ok()?
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("module "))
+        .map(|path| path.trim().trim_matches('"').to_string())
+}
+
+fn maven_group_id(dir: &Path) -> Option<String> {
+    let pom = fs::read_to_string(dir.join("pom.xml")).ok()?;
+    let group_id = |xml: &str| xml.split_once("<groupId>")
+        .and_then(|(_, rest)| rest.split_once("</groupId>"))
+        .map(|(id, _)| id.trim().to_string());
+    // The project's own group id takes precedence over the one inherited from <parent>
+    match (pom.split_once("<parent>"), pom.split_once("</parent>")) {
+        (Some((before, _)), Some((_, after))) => group_id(before).or_else(|| group_id(after)).or_else(|| group_id(&pom)),
+        _ => group_id(&pom)
+    }
+}
+
+/// Percent-encodes everything except unreserved characters, `:` and `/`, as required for purl components.
+pub fn percent_encode(str: &str) -> String {
+    let mut encoded = String::with_capacity(str.len());
+    for byte in str.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b':' | b'/' => encoded.push(byte as char),
+            _ => encoded.push_str(&format!("%{:02X}", byte))
+        }
+    }
+    encoded
+}
+
+fn check_percent_encoding(component: &str, what: &str) -> Result<(), String> {
+    let bytes = component.as_bytes();
+    for (i, byte) in bytes.iter().enumerate() {
+        if *byte == b'%' && !(i + 2 < bytes.len() && bytes[i + 1].is_ascii_hexdigit() && bytes[i + 2].is_ascii_hexdigit()) {
+            return Err(format!("Invalid percent-encoding in {}", what));
+        }
+        if byte.is_ascii_whitespace() {
+            return Err(format!("Unencoded whitespace in {}", what));
+        }
+    }
+    Ok(())
+}
+
+/// Checks a purl against the syntax rules of the purl specification.
+pub fn validate(purl: &str) -> Result<(), String> {
+    let rest = purl.strip_prefix("pkg:").ok_or("A purl must start with `pkg:`")?;
+    let rest = rest.trim_start_matches('/');
+    let (rest, subpath) = rest.split_once('#').map_or((rest, None), |(r, s)| (r, Some(s)));
+    let (rest, qualifiers) = rest.split_once('?').map_or((rest, None), |(r, q)| (r, Some(q)));
+
+    let (purl_type, rest) = rest.split_once('/').ok_or("A purl must contain a type and a name")?;
+    if !purl_type.starts_with(|c: char| c.is_ascii_alphabetic())
+        || !purl_type.chars().all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '+' || c == '-') {
+        return Err(format!("Invalid purl type `{}`", purl_type));
+    }
+
+    let (path, version) = rest.rsplit_once('@').map_or((rest, None), |(p, v)| (p, Some(v)));
+    if let Some(version) = version {
+        if version.is_empty() {
+            return Err("The purl version must not be empty".to_string());
+        }
+        check_percent_encoding(version, "version")?;
+    }
+    let path = path.trim_end_matches('/');
+    let name = path.rsplit('/').next().unwrap_or_default();
+    if name.is_empty() {
+        return Err("The purl name must not be empty".to_string());
+    }
+    check_percent_encoding(path, "namespace or name")?;
+
+    if let Some(qualifiers) = qualifiers {
+        let mut keys = HashSet::new();
+        for pair in qualifiers.split('&') {
+            let (key, value) = pair.split_once('=').ok_or_else(|| format!("Qualifier `{}` has no value", pair))?;
+            if key.is_empty() || key.starts_with(|c: char| c.is_ascii_digit())
+                || !key.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '.' || c == '-' || c == '_') {
+                return Err(format!("Invalid qualifier key `{}`", key));
+            }
+            if value.is_empty() {
+                return Err(format!("Qualifier `{}` has an empty value", key));
+            }
+            if !keys.insert(key) {
+                return Err(format!("Duplicate qualifier `{}`", key));
+            }
+            check_percent_encoding(value, "qualifiers")?;
+        }
+    }
+    if let Some(subpath) = subpath {
+        check_percent_encoding(subpath, "subpath")?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deps::project;
+
+    #[test]
+    fn components_are_percent_encoded() {
+        let cases = [
+            ("left-pad", "left-pad"),
+            ("@acme", "%40acme"),
+            ("a b", "a%20b"),
+            ("1.0.0+build", "1.0.0%2Bbuild"),
+            ("github.com/acme/tool", "github.com/acme/tool"),
+            ("c:d~e_f", "c:d~e_f"),
+            ("ü", "%C3%BC"),
+            ("50%", "50%25"),
+        ];
+        for (component, expected) in cases {
+            assert_eq!(percent_encode(component), expected, "{}", component);
+        }
+    }
+
+    #[test]
+    fn purls_of_ecosystems() {
+        let dir = project("purl-manifests", &[
+            ("go.mod", "module \"github.com/acme/tool\"\n\ngo 1.21\n"),
+            ("pom.xml", "<project><parent><groupId>org.parent</groupId></parent><groupId>com.acme</groupId></project>"),
+        ]);
+        let cases = [
+            (Ecosystem::Npm, "@acme/left-pad", Some("1.0.0"), "pkg:npm/%40acme/left-pad@1.0.0"),
+            (Ecosystem::Npm, "left-pad", None, "pkg:npm/left-pad"),
+            (Ecosystem::Composer, "laravel/framework", Some("v10.0.0"), "pkg:composer/laravel/framework@v10.0.0"),
+            (Ecosystem::PyPI, "Django_Rest", None, "pkg:pypi/django-rest"),
+            (Ecosystem::Cargo, "serde", Some("1.0.0+build"), "pkg:cargo/serde@1.0.0%2Bbuild"),
+            // The module path and group id come from the manifest
+            (Ecosystem::Golang, "tool", Some("v1.2.3"), "pkg:golang/github.com/acme/tool@v1.2.3"),
+            (Ecosystem::Maven, "app", Some("1.0"), "pkg:maven/com.acme/app@1.0"),
+            (Ecosystem::NuGet, "Newtonsoft.Json", Some("13.0.1"), "pkg:nuget/Newtonsoft.Json@13.0.1"),
+        ];
+        for (ecosystem, name, version, expected) in cases {
+            let purl = ecosystem.purl(&dir, name, version);
+            assert_eq!(purl, expected);
+            assert_eq!(validate(&purl), Ok(()), "{}", purl);
+        }
+    }
+
+    #[test]
+    fn inherited_maven_group_ids() {
+        let dir = project("purl-maven-parent", &[("pom.xml", "<project><parent><groupId>org.parent</groupId></parent></project>")]);
+        assert_eq!(Ecosystem::Maven.purl(&dir, "app", None), "pkg:maven/org.parent/app");
+    }
+
+    #[test]
+    fn purl_syntax() {
+        let cases = [
+            ("pkg:npm/%40acme/left-pad@1.0.0", Ok(())),
+            ("pkg:maven/org.apache/commons@1.0?type=pom&classifier=sources", Ok(())),
+            ("pkg:golang/github.com/acme/tool@v1.2.3#cmd/tool", Ok(())),
+            ("pkg://generic/openssl@3.0.0", Ok(())),
+            ("npm/left-pad", Err("A purl must start with `pkg:`")),
+            ("pkg:npm", Err("A purl must contain a type and a name")),
+            ("pkg:1npm/left-pad", Err("Invalid purl type `1npm`")),
+            ("pkg:npm/left-pad@", Err("The purl version must not be empty")),
+            ("pkg:npm/", Err("The purl name must not be empty")),
+            ("pkg:npm/left-pad%2", Err("Invalid percent-encoding in namespace or name")),
+            ("pkg:npm/left pad", Err("Unencoded whitespace in namespace or name")),
+            ("pkg:npm/left-pad@1.0%zz", Err("Invalid percent-encoding in version")),
+            ("pkg:npm/left-pad?type", Err("Qualifier `type` has no value")),
+            ("pkg:npm/left-pad?Type=tgz", Err("Invalid qualifier key `Type`")),
+            ("pkg:npm/left-pad?type=", Err("Qualifier `type` has an empty value")),
+            ("pkg:npm/left-pad?a=1&a=2", Err("Duplicate qualifier `a`")),
+            ("pkg:npm/left-pad#lib%z", Err("Invalid percent-encoding in subpath")),
+        ];
+        for (purl, expected) in cases {
+            assert_eq!(validate(purl), expected.map_err(str::to_string), "{}", purl);
+        }
+    }
+}
@@ -0,0 +1,293 @@
+// Copyright (C) 2022  JohnnyJayJay
+
+//! Support for projects following the [REUSE specification](https://reuse.software/spec/).
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::glob::{glob_match, slash_path};
+use crate::scan;
+use crate::scan::{FileHeader, HeaderScan};
+
+const DEP5: &str = ".reuse/dep5";
+const REUSE_TOML: &str = "REUSE.toml";
+
+/// How information from an annotation is combined with the information found in a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Precedence {
+    /// Use the information from the file itself, the annotation only fills in what is missing
+    Closest,
+    /// Use the information from both the file and the annotation
+    Aggregate,
+    /// Use only the information from the annotation, the file is not read
+    Override,
+}
+
+/// Copyright and licensing information for all files matching one of the patterns,
+/// coming from a `Files` paragraph in `.reuse/dep5` or an `[[annotations]]` table in `REUSE.toml`.
+#[derive(Debug, Clone)]
+struct Annotation {
+    patterns: Vec<String>,
+    precedence: Precedence,
+    copyrights: Vec<String>,
+    licenses: Vec<String>,
+    /// Whether `*` also matches `/` (dep5) or only `**` does (REUSE.toml)
+    star_matches_slash: bool,
+    /// File the annotation was read from
+    source: &'static str,
+}
+
+impl Annotation {
+    fn matches(&self, path: &str) -> bool {
+        self.patterns.iter().any(|pattern| glob_match(pattern.as_bytes(), path.as_bytes(), self.star_matches_slash))
+    }
+}
+
+/// A violation of the REUSE specification.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReuseError {
+    /// A file in `LICENSES/` that is not named after an SPDX identifier or `LicenseRef-`
+    BadLicense(PathBuf),
+    /// A license expression in `.reuse/dep5` or `REUSE.toml` that can't be parsed
+    InvalidExpression { expression: String, source: String },
+    DeprecatedLicense(String),
+    /// A license that is used, but has no text in `LICENSES/`
+    MissingLicense(String),
+    /// A license text in `LICENSES/` that is not used by any file
+    UnusedLicense(String),
+    MissingCopyright(PathBuf),
+    MissingLicensing(PathBuf),
+    /// `.reuse/dep5` or `REUSE.toml` could not be read
+    InvalidConfig { source: String, message: String },
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ReuseProject {
+    /// License and exception texts in `LICENSES/` by identifier
+    pub license_texts: BTreeMap<String, PathBuf>,
+    /// Resolved copyright and licensing information of every covered file
+    pub files: Vec<FileHeader>,
+    pub errors: Vec<ReuseError>,
+}
+
+impl ReuseProject {
+    /// Reads all REUSE information of the project in `dir` for the given project files and checks it for compliance.
+    pub fn load(dir: &Path, files: &[PathBuf]) -> Self {
+        let mut project = ReuseProject::default();
+        project.read_license_texts(dir);
+        let mut annotations = Vec::new();
+        match fs::read_to_string(dir.join(DEP5)) {
+            Ok(dep5) => annotations.extend(parse_dep5(&dep5)),
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => project.errors.push(ReuseError::InvalidConfig {
+                source: DEP5.to_string(), message: err.to_string()
+            }),
+            Err(_) => {}
+        }
+        match fs::read_to_string(dir.join(REUSE_TOML)).map(|toml| parse_reuse_toml(&toml)) {
+            Ok(Ok(toml_annotations)) => annotations.extend(toml_annotations),
+            Ok(Err(message)) => project.errors.push(ReuseError::InvalidConfig { source: REUSE_TOML.to_string(), message }),
+            Err(_) => {}
+        }
+        for annotation in &annotations {
+            for expression in &annotation.licenses {
+                if spdx::Expression::parse(expression).is_err() {
+                    project.errors.push(ReuseError::InvalidExpression {
+                        expression: expression.clone(),
+                        source: annotation.source.to_string()
+                    });
+                }
+            }
+        }
+
+        project.files = files.iter()
+            .filter(|path| !is_ignored(path))
+            .map(|path| resolve(dir, path, &annotations))
+            .collect();
+        project.lint();
+        project
+    }
+
+    fn read_license_texts(&mut self, dir: &Path) {
+        let entries = fs::read_dir(dir.join("LICENSES")).into_iter().flatten().filter_map(|entry| entry.ok());
+        for path in entries.map(|entry| entry.path()).filter(|path| path.is_file()) {
+            let id = path.file_stem().and_then(|stem| stem.to_str()).unwrap_or_default().to_string();
+            if spdx::license_id(&id).is_some() || spdx::exception_id(&id).is_some() || id.starts_with("LicenseRef-") {
+                self.license_texts.insert(id, path);
+            } else {
+                self.errors.push(ReuseError::BadLicense(path.strip_prefix(dir).unwrap_or(&path).to_path_buf()));
+            }
+        }
+    }
+
+    fn lint(&mut self) {
+        let mut used: Vec<String> = Vec::new();
+        for file in &self.files {
+            if file.copyrights.is_empty() {
+                self.errors.push(ReuseError::MissingCopyright(file.path.clone()));
+            }
+            if file.licenses.is_empty() {
+                self.errors.push(ReuseError::MissingLicensing(file.path.clone()));
+            }
+            for id in file.licenses.iter().flat_map(|expression| license_symbols(expression)) {
+                if !used.contains(&id) {
+                    used.push(id);
+                }
+            }
+        }
+        for id in &used {
+            if spdx::license_id(id).is_some_and(|license| license.is_deprecated())
+                || spdx::exception_id(id).is_some_and(|exception| exception.is_deprecated()) {
+                self.errors.push(ReuseError::DeprecatedLicense(id.clone()));
+            }
+            if !self.license_texts.contains_key(id) {
+                self.errors.push(ReuseError::MissingLicense(id.clone()));
+            }
+        }
+        for id in self.license_texts.keys() {
+            if !used.contains(id) {
+                self.errors.push(ReuseError::UnusedLicense(id.clone()));
+            }
+        }
+    }
+
+    /// The resolved information of all files in the form of a header scan, so it can be used like the result of a regular scan.
+    pub fn to_header_scan(&self) -> HeaderScan {
+        HeaderScan { files: self.files.clone() }
+    }
+}
+
+/// All license and exception identifiers used in a license expression.
+pub fn license_symbols(expression: &str) -> Vec<String> {
+    let mut symbols = Vec::new();
+    if let Ok(expression) = spdx::Expression::parse(expression) {
+        for req in expression.requirements() {
+            symbols.push(req.req.license.to_string());
+            if let Some(exception) = req.req.exception {
+                symbols.push(exception.name.to_string());
+            }
+        }
+    }
+    symbols
+}
+
+/// Files that don't need copyright and licensing information according to the REUSE specification.
+fn is_ignored(path: &Path) -> bool {
+    let name = path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+    let in_dir = |dir: &str| path.components().next().is_some_and(|first| first.as_os_str() == dir);
+    name == "LICENSE" || name.starts_with("LICENSE.") || name == "COPYING" || name.starts_with("COPYING.")
+        || name.ends_with(".license") || name.ends_with(".spdx") || name == "REUSE.toml"
+        || in_dir("LICENSES") || in_dir(".reuse") || in_dir(".git")
+}
+
+fn resolve(dir: &Path, path: &Path, annotations: &[Annotation]) -> FileHeader {
+    let slash_path = slash_path(path);
+    let dep5_match = annotations.iter().rev().find(|a| a.source == DEP5 && a.matches(&slash_path));
+    let toml_match = annotations.iter().rev().find(|a| a.source == REUSE_TOML && a.matches(&slash_path));
+
+    let (mut licenses, mut copyrights) = if toml_match.is_some_and(|a| a.precedence == Precedence::Override) {
+        (vec![], vec![])
+    } else {
+        // A .license sidecar file replaces the header of the file it belongs to
+        let mut sidecar = path.as_os_str().to_owned();
+        sidecar.push(".license");
+        let sidecar = dir.join(sidecar);
+        let source = if sidecar.is_file() { sidecar } else { dir.join(path) };
+        scan::read_text(&source).map(|content| scan::read_tags(&content)).unwrap_or_default()
+    };
+
+    // The last matching paragraph of a dep5 file applies, and is aggregated with the file's own information
+    if let Some(annotation) = dep5_match {
+        merge(&mut licenses, &annotation.licenses);
+        merge(&mut copyrights, &annotation.copyrights);
+    }
+    if let Some(annotation) = toml_match {
+        match annotation.precedence {
+            Precedence::Closest => {
+                if licenses.is_empty() {
+                    licenses = annotation.licenses.clone();
+                }
+                if copyrights.is_empty() {
+                    copyrights = annotation.copyrights.clone();
+                }
+            }
+            Precedence::Aggregate | Precedence::Override => {
+                merge(&mut licenses, &annotation.licenses);
+                merge(&mut copyrights, &annotation.copyrights);
+            }
+        }
+    }
+    FileHeader { path: path.to_path_buf(), licenses, copyrights }
+}
+
+fn merge(values: &mut Vec<String>, additional: &[String]) {
+    for value in additional {
+        if !values.contains(value) {
+            values.push(value.clone());
+        }
+    }
+}
+
+/// Parses the `Files` paragraphs of a machine-readable Debian copyright file.
+fn parse_dep5(content: &str) -> Vec<Annotation> {
+    let mut annotations = Vec::new();
+    for paragraph in content.split("\n\n").map(str::trim).filter(|p| !p.is_empty()) {
+        let mut fields: Vec<(String, String)> = Vec::new();
+        for line in paragraph.lines() {
+            if line.starts_with(' ') || line.starts_with('\t') {
+                if let Some((_, value)) = fields.last_mut() {
+                    value.push('\n');
+                    value.push_str(line.trim());
+                }
+            } else if let Some((field, value)) = line.split_once(':') {
+                fields.push((field.trim().to_lowercase(), value.trim().to_string()));
+            }
+        }
+        let field = |name: &str| fields.iter().find(|(field, _)| field == name).map(|(_, value)| value.as_str());
+        if let (Some(files), Some(license)) = (field("files"), field("license")) {
+            annotations.push(Annotation {
+                patterns: files.split_whitespace().map(String::from).collect(),
+                precedence: Precedence::Aggregate,
+                copyrights: field("copyright").map(|c| c.lines().map(String::from).filter(|l| !l.is_empty()).collect()).unwrap_or_default(),
+                // Only the first line is the license expression, the rest is the license text
+                licenses: license.lines().next().map(|l| vec![l.to_string()]).unwrap_or_default(),
+                star_matches_slash: true,
+                source: DEP5,
+            });
+        }
+    }
+    annotations
+}
+
+fn string_or_array(value: Option<&toml::Value>) -> Vec<String> {
+    match value {
+        Some(toml::Value::String(s)) => vec![s.clone()],
+        Some(toml::Value::Array(values)) => values.iter().filter_map(|v| v.as_str().map(String::from)).collect(),
+        _ => vec![]
+    }
+}
+
+/// Parses the `[[annotations]]` of a `REUSE.toml` file.
+fn parse_reuse_toml(content: &str) -> Result<Vec<Annotation>, String> {
+    let value: toml::Value = content.parse().map_err(|err: toml::de::Error| err.to_string())?;
+    let annotations = match value.get("annotations") {
+        Some(toml::Value::Array(annotations)) => annotations,
+        Some(_) => return Err("`annotations` must be an array of tables".to_string()),
+        None => return Ok(vec![])
+    };
+    annotations.iter().map(|annotation| {
+        let precedence = match annotation.get("precedence").and_then(|p| p.as_str()) {
+            None | Some("closest") => Precedence::Closest,
+            Some("aggregate") => Precedence::Aggregate,
+            Some("override") => Precedence::Override,
+            Some(other) => return Err(format!("Unknown precedence `{}`", other))
+        };
+        Ok(Annotation {
+            patterns: string_or_array(annotation.get("path")),
+            precedence,
+            copyrights: string_or_array(annotation.get("SPDX-FileCopyrightText")),
+            licenses: string_or_array(annotation.get("SPDX-License-Identifier")),
+            star_matches_slash: false,
+            source: REUSE_TOML,
+        })
+    }).collect()
+}
@@ -0,0 +1,179 @@
+// Copyright (C) 2022  JohnnyJayJay
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::{detect, session};
+use crate::vcs::VcsInfo;
+
+const LICENSE_TAG: &str = "SPDX-License-Identifier:";
+const COPYRIGHT_TAG: &str = "SPDX-FileCopyrightText:";
+/// Comment terminators that may follow a tag value on the same line.
+const COMMENT_ENDS: [&str; 8] = ["*/", "-->", "#}", "%>", "*)", "-}", "\"\"\"", "'''"];
+
+/// SPDX tags found in the header of a single file.
+#[derive(Debug, Clone, Default)]
+pub struct FileHeader {
+    /// Path relative to the project directory
+    pub path: PathBuf,
+    pub licenses: Vec<String>,
+    pub copyrights: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct HeaderScan {
+    pub files: Vec<FileHeader>,
+}
+
+fn push_distinct<'a>(values: &mut Vec<&'a str>, value: &'a str) {
+    if !values.contains(&value) {
+        values.push(value);
+    }
+}
+
+impl HeaderScan {
+    /// Files without an `SPDX-License-Identifier` tag.
+    pub fn unlicensed(&self) -> Vec<&Path> {
+        self.files.iter()
+            .filter(|file| file.licenses.is_empty())
+            .map(|file| file.path.as_path())
+            .collect()
+    }
+
+    /// Distinct license expressions found in all files, in order of appearance.
+    pub fn license_expressions(&self) -> Vec<&str> {
+        let mut expressions = Vec::new();
+        for license in self.files.iter().flat_map(|file| &file.licenses) {
+            push_distinct(&mut expressions, license);
+        }
+        expressions
+    }
+
+    /// Distinct license identifiers that appear in any of the license expressions, as required for `PackageLicenseInfoFromFiles`.
+    pub fn license_ids(&self) -> Vec<String> {
+        let mut ids: Vec<String> = Vec::new();
+        for expression in self.license_expressions() {
+            for id in license_ids(expression) {
+                if !ids.contains(&id) {
+                    ids.push(id);
+                }
+            }
+        }
+        ids
+    }
+
+    /// Distinct copyright notices found in all files, in order of appearance.
+    pub fn copyrights(&self) -> Vec<&str> {
+        let mut copyrights = Vec::new();
+        for copyright in self.files.iter().flat_map(|file| &file.copyrights) {
+            push_distinct(&mut copyrights, copyright);
+        }
+        copyrights
+    }
+}
+
+/// The individual license identifiers (without exceptions) of a license expression.
+pub fn license_ids(expression: &str) -> Vec<String> {
+    spdx::Expression::parse(expression)
+        .map(|expr| expr.requirements().map(|req| req.req.license.to_string()).collect())
+        .unwrap_or_default()
+}
+
+/// Combines license expressions with `AND`, putting compound expressions in parentheses.
+pub fn conjunction<S: AsRef<str>>(expressions: &[S]) -> String {
+    let parts: Vec<String> = expressions.iter()
+        .map(|expression| expression.as_ref())
+        .map(|expression| if expressions.len() > 1 && expression.contains(' ') {
+            format!("({})", expression)
+        } else {
+            expression.to_string()
+        })
+        .collect();
+    parts.join(" AND ")
+}
+
+fn tag_value(line: &str, tag: &str) -> Option<String> {
+    let mut value = line.split_once(tag)?.1.trim();
+    while let Some(end) = COMMENT_ENDS.iter().find(|end| value.ends_with(*end)) {
+        value = value[..value.len() - end.len()].trim_end();
+    }
+    if value.is_empty() {
+        None
+    } else {
+        Some(value.to_string())
+    }
+}
+
+/// Reads the SPDX tags from the content of a file. Invalid license expressions are ignored.
+pub fn read_tags(content: &str) -> (Vec<String>, Vec<String>) {
+    let mut licenses = Vec::new();
+    let mut copyrights = Vec::new();
+    for line in content.lines() {
+        if let Some(license) = tag_value(line, LICENSE_TAG) {
+            if spdx::Expression::parse(&license).is_ok() {
+                licenses.push(license);
+            }
+        } else if let Some(copyright) = tag_value(line, COPYRIGHT_TAG) {
+            if copyright.chars().any(char::is_alphanumeric) {
+                copyrights.push(copyright);
+            }
+        }
+    }
+    (licenses, copyrights)
+}
+
+/// Reads a file as text, returning `None` for unreadable or binary files.
+pub fn read_text(path: &Path) -> Option<String> {
+    let bytes = fs::read(path).ok()?;
+    if bytes.iter().take(8192).any(|b| *b == 0) {
+        None
+    } else {
+        Some(String::from_utf8_lossy(&bytes).into_owned())
+    }
+}
+
+fn walk(dir: &Path, relative: &Path, files: &mut Vec<PathBuf>) {
+    for entry in fs::read_dir(dir).into_iter().flatten().filter_map(|entry| entry.ok()) {
+        let name = entry.file_name();
+        if name.to_str().is_some_and(|name| name.starts_with('.')) {
+            continue;
+        }
+        let path = entry.path();
+        if path.is_dir() {
+            walk(&path, &relative.join(&name), files);
+        } else if path.is_file() {
+            files.push(relative.join(&name));
+        }
+    }
+}
+
+/// All files of the project, relative to `dir`. Uses the file list of the VCS if there is one,
+/// otherwise walks the directory, skipping hidden files and directories.
+/// The state directory of spdx-guide is never part of the project.
+pub fn project_files(dir: &Path, vcs: Option<&VcsInfo>) -> Vec<PathBuf> {
+    match vcs {
+        Some(vcs) => vcs.files.iter()
+            .filter(|path| !path.starts_with(session::STATE_DIR))
+            .cloned()
+            .collect(),
+        None => {
+            let mut files = Vec::new();
+            walk(dir, Path::new(""), &mut files);
+            files.sort();
+            files
+        }
+    }
+}
+
+/// Scans the source files of the project for SPDX tags. License texts, the SPDX document itself and binary files are skipped.
+pub fn scan_headers(dir: &Path, files: &[PathBuf], spdx_file: &str) -> HeaderScan {
+    let license_files = detect::license_files(dir);
+    let files = files.iter()
+        .filter(|path| path.as_path() != Path::new(spdx_file) && !license_files.contains(&dir.join(path)))
+        .filter_map(|path| read_text(&dir.join(path)).map(|content| (path, content)))
+        .map(|(path, content)| {
+            let (licenses, copyrights) = read_tags(&content);
+            FileHeader { path: path.clone(), licenses, copyrights }
+        })
+        .collect();
+    HeaderScan { files }
+}
@@ -0,0 +1,206 @@
+// Copyright (C) 2022  JohnnyJayJay
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::convert::TryFrom;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use toml::Value;
+use toml::value::Table;
+
+/// Directory in the project where spdx-guide keeps its state.
+pub const STATE_DIR: &str = ".spdx-guide";
+const SESSION_FILE: &str = "session.toml";
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// An answer to a prompt that can be saved in the session file.
+pub trait Answer: Sized {
+    fn to_value(&self) -> Value;
+
+    fn from_value(value: &Value) -> Option<Self>;
+
+    /// Indices of the items chosen in a select prompt.
+    fn selection(&self) -> Vec<usize> {
+        Vec::new()
+    }
+}
+
+impl Answer for usize {
+    fn to_value(&self) -> Value {
+        Value::Integer(*self as i64)
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        value.as_integer().and_then(|i| usize::try_from(i).ok())
+    }
+
+    fn selection(&self) -> Vec<usize> {
+        vec![*self]
+    }
+}
+
+impl Answer for bool {
+    fn to_value(&self) -> Value {
+        Value::Boolean(*self)
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        value.as_bool()
+    }
+}
+
+impl Answer for String {
+    fn to_value(&self) -> Value {
+        Value::String(self.clone())
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        value.as_str().map(str::to_string)
+    }
+}
+
+impl Answer for Vec<usize> {
+    fn to_value(&self) -> Value {
+        Value::Array(self.iter().map(Answer::to_value).collect())
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        value.as_array()?.iter().map(usize::from_value).collect()
+    }
+
+    fn selection(&self) -> Vec<usize> {
+        self.clone()
+    }
+}
+
+/// `None` (a skipped or cancelled prompt) is saved as an empty array, `Some` as an array with one element.
+impl<T: Answer> Answer for Option<T> {
+    fn to_value(&self) -> Value {
+        Value::Array(self.iter().map(Answer::to_value).collect())
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        match value.as_array()?.as_slice() {
+            [] => Some(None),
+            [answer] => T::from_value(answer).map(Some),
+            _ => None
+        }
+    }
+
+    fn selection(&self) -> Vec<usize> {
+        self.as_ref().map(Answer::selection).unwrap_or_default()
+    }
+}
+
+fn session_file(dir: &Path) -> PathBuf {
+    dir.join(STATE_DIR).join(SESSION_FILE)
+}
+
+/// The text of the items chosen in a prompt that lists `items`, or `None` if it lists nothing that can change.
+fn selected_items<T: Answer>(answer: &T, items: &[String]) -> Option<Value> {
+    if items.is_empty() {
+        return None;
+    }
+    Some(Value::Array(answer.selection().into_iter()
+        .map(|index| Value::String(items.get(index).cloned().unwrap_or_default()))
+        .collect()))
+}
+
+/// The answers given so far, and those of an interrupted session that are still to be replayed.
+/// Every answer is saved with the key of its prompt and, for select prompts, the text of the chosen items,
+/// because an index picks a different item once the list has changed.
+#[derive(Default)]
+pub struct Session {
+    answers: RefCell<Vec<Value>>,
+    replay: RefCell<VecDeque<Value>>,
+}
+
+impl Session {
+    /// Reads the answers of an interrupted session in `dir`. Sessions of other spdx-guide versions are ignored,
+    /// since their questions may differ.
+    pub fn load(dir: &Path) -> Option<Vec<Value>> {
+        let content = fs::read_to_string(session_file(dir)).ok()?;
+        let session: Value = content.parse().ok()?;
+        if session.get("version")?.as_str()? != VERSION {
+            return None;
+        }
+        let answers = session.get("answer")?.as_array()?.clone();
+        Some(answers).filter(|answers| !answers.is_empty())
+    }
+
+    /// A session that replays the given answers before asking the user again.
+    pub fn resume(answers: Vec<Value>) -> Self {
+        Session {
+            answers: RefCell::default(),
+            replay: RefCell::new(answers.into()),
+        }
+    }
+
+    /// The next answer to replay, if there is one that was given to the prompt `key` and chose the same `items`.
+    /// Replaying stops at the first answer that does not fit.
+    pub fn replay<T: Answer>(&self, key: &str, items: &[String]) -> Option<T> {
+        let mut replay = self.replay.borrow_mut();
+        let answer = replay.pop_front()
+            .filter(|saved| saved.get("prompt").and_then(Value::as_str) == Some(key))
+            .and_then(|saved| T::from_value(saved.get("value")?)
+                .filter(|answer| saved.get("items") == selected_items(answer, items).as_ref()));
+        match answer {
+            Some(answer) => {
+                self.record(key, items, &answer);
+                Some(answer)
+            }
+            None => {
+                replay.clear();
+                None
+            }
+        }
+    }
+
+    /// Records the answer to the prompt `key`, which lists `items` if it is a select prompt.
+    pub fn record<T: Answer>(&self, key: &str, items: &[String], answer: &T) {
+        let mut saved = Table::new();
+        saved.insert("prompt".to_string(), Value::String(key.to_string()));
+        saved.insert("value".to_string(), answer.to_value());
+        if let Some(selected) = selected_items(answer, items) {
+            saved.insert("items".to_string(), selected);
+        }
+        self.answers.borrow_mut().push(Value::Table(saved));
+    }
+
+    pub fn len(&self) -> usize {
+        self.answers.borrow().len()
+    }
+
+    /// Forgets the answers after the first `len`, e.g. when going back. Answers that were not replayed yet are dropped as well.
+    pub fn truncate(&self, len: usize) {
+        self.answers.borrow_mut().truncate(len);
+        self.replay.borrow_mut().clear();
+    }
+
+    pub fn save(&self, dir: &Path) -> io::Result<()> {
+        let mut session = Table::new();
+        session.insert("version".to_string(), Value::String(VERSION.to_string()));
+        session.insert("answer".to_string(), Value::Array(self.answers.borrow().clone()));
+        let content = toml::to_string(&Value::Table(session))
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        let file = session_file(dir);
+        if let Some(parent) = file.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(file, content)
+    }
+
+    /// Deletes the session file once the wizard is done, along with the state directory if nothing else is in it.
+    pub fn remove(dir: &Path) -> io::Result<()> {
+        let file = session_file(dir);
+        if file.exists() {
+            fs::remove_file(&file)?;
+        }
+        let state_dir = dir.join(STATE_DIR);
+        if state_dir.read_dir().is_ok_and(|mut entries| entries.next().is_none()) {
+            fs::remove_dir(state_dir)?;
+        }
+        Ok(())
+    }
+}
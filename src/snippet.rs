@@ -0,0 +1,259 @@
+// Copyright (C) 2022  JohnnyJayJay
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use crate::model::{SnippetSection, LINE_ENDING, SNIPPET_HEADER};
+use crate::glob;
+
+/// First and last element (byte or line) of a snippet, starting at 1.
+pub type Range = (usize, usize);
+
+/// Headings of the sections that follow the snippets in the documents spdx-guide writes.
+const FOLLOWING_HEADERS: [&str; 2] = ["##### Dependency Information", "##### Other Licensing Information Detected"];
+
+#[derive(Debug)]
+pub enum SnippetError {
+    /// Neither a byte range nor a line range was given
+    MissingRange,
+    /// A range whose start is 0 or after its end
+    InvalidRange(usize, usize),
+    /// A range that goes beyond the end of the file, with the size of the file (in bytes or lines)
+    OutOfBounds { range: Range, size: usize },
+    InvalidLicense(String),
+    /// The file has no file section in the SPDX document
+    UndescribedFile(PathBuf),
+    Io(io::Error),
+}
+
+impl From<io::Error> for SnippetError {
+    fn from(e: io::Error) -> Self {
+        SnippetError::Io(e)
+    }
+}
+
+/// Parses a range of the form `start:end`, as used by `SnippetByteRange` and `SnippetLineRange`.
+pub fn parse_range(range: &str) -> Result<Range, String> {
+    let (start, end) = range.split_once(':').ok_or_else(|| format!("expected start:end, got {}", range))?;
+    let start = start.trim().parse().map_err(|_| format!("invalid start: {}", start))?;
+    let end = end.trim().parse().map_err(|_| format!("invalid end: {}", end))?;
+    Ok((start, end))
+}
+
+fn check_range(range: Range, size: usize) -> Result<(), SnippetError> {
+    if range.0 == 0 || range.0 > range.1 {
+        Err(SnippetError::InvalidRange(range.0, range.1))
+    } else if range.1 > size {
+        Err(SnippetError::OutOfBounds { range, size })
+    } else {
+        Ok(())
+    }
+}
+
+/// Byte ranges of all lines in `content`, starting at 1 and including the line break.
+fn line_bounds(content: &[u8]) -> Vec<Range> {
+    let mut bounds = Vec::new();
+    let mut start = 1;
+    for (i, byte) in content.iter().enumerate() {
+        if *byte == b'\n' {
+            bounds.push((start, i + 1));
+            start = i + 2;
+        }
+    }
+    if start <= content.len() {
+        bounds.push((start, content.len()));
+    }
+    bounds
+}
+
+/// Validates the given ranges against the content of the file and derives the byte range from the line range
+/// or the line range from the byte range if only one of them is given.
+pub fn resolve_ranges(content: &[u8], bytes: Option<Range>, lines: Option<Range>) -> Result<(Range, Range), SnippetError> {
+    let bounds = line_bounds(content);
+    let line_of = |byte: usize| bounds.iter().position(|(_, end)| byte <= *end).map_or(bounds.len(), |i| i + 1);
+    match (bytes, lines) {
+        (None, None) => Err(SnippetError::MissingRange),
+        (Some(bytes), lines) => {
+            check_range(bytes, content.len())?;
+            if let Some(lines) = lines {
+                check_range(lines, bounds.len())?;
+            }
+            Ok((bytes, lines.unwrap_or_else(|| (line_of(bytes.0), line_of(bytes.1)))))
+        }
+        (None, Some(lines)) => {
+            check_range(lines, bounds.len())?;
+            Ok(((bounds[lines.0 - 1].0, bounds[lines.1 - 1].1), lines))
+        }
+    }
+}
+
+/// The `SPDXID` of the file section with the given `FileName` in a tag-value document.
+pub fn file_spdx_id(document: &str, file_name: &str) -> Option<String> {
+    let mut lines = document.lines().map(str::trim);
+    lines.find(|line| line.strip_prefix("FileName:").is_some_and(|name| name.trim() == file_name))?;
+    lines.take_while(|line| !line.starts_with("FileName:"))
+        .find_map(|line| line.strip_prefix("SPDXID:"))
+        .map(|id| id.trim().to_string())
+}
+
+/// Byte offset of the first line at or after `from` that starts with one of `headers`.
+fn find_header(document: &str, from: usize, headers: &[&str]) -> Option<usize> {
+    let mut offset = from;
+    for line in document[from..].split_inclusive('\n') {
+        if headers.iter().any(|header| line.starts_with(header)) {
+            return Some(offset);
+        }
+        offset += line.len();
+    }
+    None
+}
+
+/// Adds the snippet after the last snippet of a tag-value document. If there is none yet, a snippet section is started
+/// before the dependencies and extracted licenses, where spdx-guide writes it.
+fn insert_snippet(document: &str, snippet: &SnippetSection) -> String {
+    let (position, separator) = match document.find(SNIPPET_HEADER) {
+        Some(header) => {
+            let position = find_header(document, header + SNIPPET_HEADER.len(), &["#####"]).unwrap_or(document.len());
+            (position, LINE_ENDING.repeat(2))
+        }
+        None => {
+            let position = find_header(document, 0, &FOLLOWING_HEADERS).unwrap_or(document.len());
+            (position, format!("{}{}{}", LINE_ENDING.repeat(3), SNIPPET_HEADER, LINE_ENDING))
+        }
+    };
+    let (before, after) = document.split_at(position);
+    let mut inserted = format!("{}{}{}", before.trim_end(), separator, snippet);
+    if !after.is_empty() {
+        inserted.push_str(&LINE_ENDING.repeat(2));
+        inserted.push_str(after);
+    }
+    inserted
+}
+
+/// A snippet to add to an existing SPDX document.
+#[derive(Debug)]
+pub struct SnippetRequest {
+    /// Path of the file that contains the snippet, relative to the project directory
+    pub file: PathBuf,
+    pub bytes: Option<Range>,
+    pub lines: Option<Range>,
+    pub license: String,
+    pub copyright: Option<String>,
+    pub name: Option<String>,
+}
+
+/// Validates the snippet and adds it to the SPDX document at `spdx_file`, which must already describe the file it belongs to.
+pub fn register(dir: &Path, spdx_file: &Path, request: SnippetRequest) -> Result<SnippetSection, SnippetError> {
+    spdx::Expression::parse(&request.license).map_err(|_| SnippetError::InvalidLicense(request.license.clone()))?;
+    let content = fs::read(dir.join(&request.file))?;
+    let (byte_range, line_range) = resolve_ranges(&content, request.bytes, request.lines)?;
+
+    let document = fs::read_to_string(spdx_file)?;
+    let file_name = format!("./{}", glob::slash_path(&request.file));
+    let file_id = file_spdx_id(&document, &file_name).ok_or(SnippetError::UndescribedFile(request.file))?;
+    let number = document.lines().filter(|line| line.starts_with("SnippetSPDXID:")).count() + 1;
+    let snippet = SnippetSection {
+        id: format!("SPDXRef-Snippet-{}", number),
+        file_id,
+        byte_range,
+        line_range: Some(line_range),
+        license_concluded: request.license,
+        copyright_text: request.copyright,
+        name: request.name,
+    };
+
+    fs::write(spdx_file, insert_snippet(&document, &snippet))?;
+    Ok(snippet)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ranges_are_parsed() {
+        let cases: [(&str, Option<Range>); 6] = [
+            ("5:23", Some((5, 23))),
+            (" 5 : 23 ", Some((5, 23))),
+            ("1:1", Some((1, 1))),
+            ("5", None),
+            ("a:23", None),
+            ("5:-1", None),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(parse_range(input).ok(), expected, "{}", input);
+        }
+    }
+
+    #[test]
+    fn ranges_are_resolved() {
+        // Three lines without a line break at the end
+        let content = b"ab\ncd\nef";
+        let cases = [
+            (None, Some((2, 3)), Some(((4, 8), (2, 3)))),
+            (Some((4, 5)), None, Some(((4, 5), (2, 2)))),
+            // A line break belongs to the line it ends
+            (Some((3, 3)), None, Some(((3, 3), (1, 1)))),
+            (Some((1, 8)), None, Some(((1, 8), (1, 3)))),
+            (Some((1, 2)), Some((1, 1)), Some(((1, 2), (1, 1)))),
+            (Some((1, 9)), None, None),
+            (None, Some((1, 4)), None),
+        ];
+        for (bytes, lines, expected) in cases {
+            assert_eq!(resolve_ranges(content, bytes, lines).ok(), expected, "{:?} {:?}", bytes, lines);
+        }
+        // With a line break at the end, there is no empty line after it
+        assert_eq!(resolve_ranges(b"ab\ncd\n", None, Some((2, 2))).ok(), Some(((4, 6), (2, 2))));
+        assert!(matches!(resolve_ranges(b"ab\ncd\n", None, Some((1, 3))),
+            Err(SnippetError::OutOfBounds { range: (1, 3), size: 2 })));
+        assert!(matches!(resolve_ranges(b"ab\ncd\n", Some((1, 7)), None),
+            Err(SnippetError::OutOfBounds { range: (1, 7), size: 6 })));
+        assert!(matches!(resolve_ranges(content, Some((0, 1)), None), Err(SnippetError::InvalidRange(0, 1))));
+        assert!(matches!(resolve_ranges(content, None, Some((3, 2))), Err(SnippetError::InvalidRange(3, 2))));
+        assert!(matches!(resolve_ranges(content, None, None), Err(SnippetError::MissingRange)));
+    }
+
+    fn snippet(number: usize) -> SnippetSection {
+        SnippetSection {
+            id: format!("SPDXRef-Snippet-{}", number),
+            file_id: "SPDXRef-File-src-lib.rs".to_string(),
+            byte_range: (1, 2),
+            line_range: None,
+            license_concluded: "MIT".to_string(),
+            copyright_text: None,
+            name: None,
+        }
+    }
+
+    /// A document with the given sections, where the line breaks are made to match [`LINE_ENDING`].
+    fn document(sections: &[&str]) -> String {
+        sections.join("\n\n\n").replace(LINE_ENDING, "\n").replace('\n', LINE_ENDING) + LINE_ENDING
+    }
+
+    const FILES: &str = "##### File Information\nFileName: ./src/lib.rs\nSPDXID: SPDXRef-File-src-lib.rs";
+    const DEPENDENCIES: &str = "##### Dependency Information\nPackageName: dep\nSPDXID: SPDXRef-Package-dep-1.0.0";
+    const EXTRACTED: &str = "##### Other Licensing Information Detected\nLicenseID: LicenseRef-Custom";
+
+    #[test]
+    fn snippets_are_added_to_the_snippet_section() {
+        let first = format!("{}\n{}", SNIPPET_HEADER, snippet(1));
+        let both = format!("{}\n{}\n{}", SNIPPET_HEADER, snippet(1), snippet(2));
+        let first = first.trim_end();
+        let both = both.trim_end();
+        let cases = [
+            (document(&[FILES]), document(&[FILES, first])),
+            (document(&[FILES, DEPENDENCIES, EXTRACTED]), document(&[FILES, first, DEPENDENCIES, EXTRACTED])),
+            (document(&[FILES, EXTRACTED]), document(&[FILES, first, EXTRACTED])),
+        ];
+        for (before, after) in cases {
+            assert_eq!(insert_snippet(&before, &snippet(1)), after);
+        }
+        let cases = [
+            (document(&[FILES, first]), document(&[FILES, both])),
+            (document(&[FILES, first, DEPENDENCIES, EXTRACTED]), document(&[FILES, both, DEPENDENCIES, EXTRACTED])),
+        ];
+        for (before, after) in cases {
+            assert_eq!(insert_snippet(&before, &snippet(2)), after);
+        }
+    }
+}
@@ -1,39 +1,290 @@
 // Copyright (C) 2022  JohnnyJayJay
 
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
 use std::fs::File;
 use std::io;
 use std::io::{Write};
-use std::path::{PathBuf};
-use console::{style, Term};
-use dialoguer::{Confirm, Input, Select};
+use std::path::{Path, PathBuf};
+use console::style;
+use dialoguer::{Confirm, Input, MultiSelect, Select};
 use dialoguer::theme::Theme;
 use i18n_embed::fluent::FluentLanguageLoader;
 use i18n_embed_fl::fl;
 use uuid::Uuid;
 use whoami::{realname, username};
-use crate::model::{SpdxDocument};
+use crate::{checksum, config, cpe, purl, scan};
+use crate::checksum::Algorithm;
+use crate::config::{Config, Profile};
+use crate::model::{ExternalRef, Relationship, RelationshipType, SpdxDocument};
+use crate::prompt::Prompter;
+use crate::purl::Ecosystem;
+use crate::reuse::ReuseProject;
+use crate::scan::HeaderScan;
+use crate::session::{Answer, Session};
 use crate::vcs::{User, VcsInfo};
+use crate::vcs::{forge, Vcs};
+use crate::vcs;
+use crate::vcs::git::Git;
+use crate::vcs::forge::ForgeRepo;
+use crate::workspace::{Member, Workspace};
+use license::{DeclaredLicenseStep, ExtractedLicensesStep};
+use deps::DependenciesStep;
+use files::AskFileSectionsStep;
+use relationships::AskRelationshipsStep;
+use review::ReviewStep;
+use reuse::{ReuseFileSectionsStep, ReuseLintStep};
+
+mod deps;
+mod files;
+mod license;
+mod relationships;
+mod reuse;
+mod review;
+#[cfg(test)]
+mod tests;
 
 pub struct SetupData<'a> {
     pub vcs: Option<VcsInfo>,
     pub creators: Vec<String>,
+    pub header_scan: Option<HeaderScan>,
+    pub reuse_mode: bool,
+    /// Whether the same document should be written every time for the same answers and commit
+    pub reproducible: bool,
+    /// Unix timestamp of the document's creation
+    pub created: i64,
+    pub reuse: Option<ReuseProject>,
+    /// Answers fixed by the project configuration
+    pub config: &'a Config,
+    /// Suggestions from the selected profile of the user configuration
+    pub profile: &'a Profile,
     pub doc: &'a mut SpdxDocument,
-    pub term: &'a mut Term,
+    pub prompter: &'a dyn Prompter,
     pub filename: String,
     pub dir: &'a PathBuf,
     pub i18n: &'a FluentLanguageLoader,
     pub theme: &'a dyn Theme,
+    /// Answers given so far, used to tell steps that asked the user something from those that did not
+    pub session: Session,
+    /// The workspace whose members are described, if the user chose to describe them
+    pub workspace: Option<WorkspaceRun>,
+}
+
+/// How the members of a workspace are described.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum WorkspaceMode {
+    /// One document per member, written to the member's directory
+    Documents,
+    /// A single document with a package for every member
+    Packages,
+}
+
+/// Progress through the members of a workspace, along with the answers that are shared by all of them.
+#[derive(Debug, Clone)]
+pub struct WorkspaceRun {
+    workspace: Workspace,
+    mode: WorkspaceMode,
+    /// Index of the member whose package is being described
+    current: usize,
+    /// The document with the creators, which the documents of further members start from
+    template: SpdxDocument,
+    supplier: Option<String>,
+    namespace_base: Option<String>,
+    deterministic: Option<bool>,
+}
+
+/// The parts of [`SetupData`] that steps change, saved before a step runs so its changes can be undone.
+struct Snapshot {
+    doc: SpdxDocument,
+    creators: Vec<String>,
+    header_scan: Option<HeaderScan>,
+    reuse: Option<ReuseProject>,
+    workspace: Option<WorkspaceRun>,
+    answers: usize,
+}
+
+/// What the user enters in a text prompt to go back to the previous question.
+const BACK: &str = "<";
+
+/// Why a step did not lead to the next one.
+#[derive(Debug)]
+pub enum StepError {
+    /// The user wants to go back to the previous question.
+    Back,
+    /// The step failed in a way that may be fixed by answering differently, e.g. with an unknown revision.
+    /// The step is repeated up to [`MAX_RETRIES`] times.
+    Recoverable(String),
+    /// The user ended the wizard, e.g. with Ctrl-C or by closing the terminal.
+    Aborted,
+    /// Reading or writing a file or the terminal failed.
+    Io(io::Error),
+}
+
+impl StepError {
+    /// Exit code of spdx-guide when the wizard ends with this error, following the BSD `sysexits.h` conventions.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            StepError::Back | StepError::Recoverable(_) => 65,
+            StepError::Aborted => 130,
+            StepError::Io(_) => 74,
+        }
+    }
+}
+
+impl Display for StepError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StepError::Back => write!(f, "going back to the previous question"),
+            StepError::Recoverable(message) => write!(f, "{}", message),
+            StepError::Aborted => write!(f, "aborted"),
+            StepError::Io(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl Error for StepError {}
+
+impl From<io::Error> for StepError {
+    fn from(e: io::Error) -> Self {
+        match e.kind() {
+            // What the terminal reports for Ctrl-C and a closed input
+            io::ErrorKind::Interrupted | io::ErrorKind::UnexpectedEof => StepError::Aborted,
+            _ => StepError::Io(e)
+        }
+    }
+}
+
+pub type StepResult = Result<Option<Box<dyn SetupStep>>, StepError>;
+
+/// How often a step is repeated after a recoverable error before the wizard gives up.
+const MAX_RETRIES: usize = 3;
+
+/// Wraps an input validator so that [`BACK`] is always accepted.
+fn allow_back<E>(mut validate: impl FnMut(&String) -> Result<(), E>) -> impl FnMut(&String) -> Result<(), E> {
+    move |input| if input == BACK { Ok(()) } else { validate(input) }
+}
+
+impl SetupData<'_> {
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            doc: self.doc.clone(),
+            creators: self.creators.clone(),
+            header_scan: self.header_scan.clone(),
+            reuse: self.reuse.clone(),
+            workspace: self.workspace.clone(),
+            answers: self.session.len(),
+        }
+    }
+
+    fn restore(&mut self, snapshot: Snapshot) {
+        *self.doc = snapshot.doc;
+        self.creators = snapshot.creators;
+        self.header_scan = snapshot.header_scan;
+        self.reuse = snapshot.reuse;
+        self.workspace = snapshot.workspace;
+        self.session.truncate(snapshot.answers);
+    }
+
+    /// The workspace member whose package is being described, if any.
+    fn member(&self) -> Option<&Member> {
+        self.workspace.as_ref().map(|run| &run.workspace.members[run.current])
+    }
+
+    /// Directory of the package being described, which is the project directory unless a workspace member is described.
+    fn package_dir(&self) -> PathBuf {
+        match self.member() {
+            Some(member) if member.path != Path::new("") => self.dir.join(&member.path),
+            _ => self.dir.clone()
+        }
+    }
+
+    /// Directory the SPDX file is written to. File names in the document are relative to it.
+    fn document_dir(&self) -> PathBuf {
+        match self.workspace {
+            Some(ref run) if run.mode == WorkspaceMode::Documents => self.package_dir(),
+            _ => self.dir.clone()
+        }
+    }
+
+    /// The files of the package being described, relative to [`SetupData::document_dir`].
+    /// Files of a workspace member do not include those of members nested in it.
+    fn package_files(&self) -> Vec<PathBuf> {
+        let files = scan::project_files(self.dir, self.vcs.as_ref());
+        let run = match self.workspace {
+            Some(ref run) => run,
+            None => return files
+        };
+        let member = &run.workspace.members[run.current];
+        files.into_iter()
+            .filter(|path| run.workspace.member_of(path) == Some(run.current))
+            .map(|path| match run.mode {
+                WorkspaceMode::Documents => path.strip_prefix(&member.path).map_or(path.clone(), Path::to_path_buf),
+                WorkspaceMode::Packages => path
+            })
+            .collect()
+    }
+
+    /// Shows the prompt `key` and records the answer, or replays the answer from a resumed session instead.
+    /// A select prompt passes the text of its `items`, so an answer is only replayed if it still chooses the same items.
+    fn prompted<T: Answer>(&self, key: &str, items: &[String], prompt: impl FnOnce() -> io::Result<T>) -> Result<T, StepError> {
+        if let Some(answer) = self.session.replay(key, items) {
+            return Ok(answer);
+        }
+        let answer = prompt()?;
+        self.session.record(key, items, &answer);
+        Ok(answer)
+    }
+
+    /// The answer to a confirm prompt or a select prompt with fixed items, where Esc means going back.
+    fn answer<T: Answer>(&self, key: &str, prompt: impl FnOnce() -> io::Result<Option<T>>) -> Result<T, StepError> {
+        self.prompted(key, &[], prompt)?.ok_or(StepError::Back)
+    }
+
+    /// The answer to a select prompt that lists `items`, where Esc means going back.
+    fn choice<T: Answer>(&self, key: &str, items: &[String], prompt: impl FnOnce() -> io::Result<Option<T>>) -> Result<T, StepError> {
+        self.prompted(key, items, prompt)?.ok_or(StepError::Back)
+    }
+
+    /// The answer to a text prompt, where [`BACK`] means going back.
+    fn input(&self, key: &str, prompt: impl FnOnce() -> io::Result<String>) -> Result<String, StepError> {
+        let input = self.prompted(key, &[], prompt)?;
+        if input == BACK {
+            Err(StepError::Back)
+        } else {
+            Ok(input)
+        }
+    }
 }
 
 pub trait SetupStep: 'static {
-    fn run(&self, data: &mut SetupData) -> io::Result<Option<Box<dyn SetupStep>>>;
+    fn run(&self, data: &mut SetupData) -> StepResult;
 }
 
 const SPDX_VERSION: &str = "SPDX-2.3";
+const DEFAULT_NAMESPACE_BASE: &str = "https://spdx.org/spdxdocs";
+/// Number of project authors suggested as supplier or originator, or named in the suggested copyright text.
+const MAX_SUGGESTED_AUTHORS: usize = 5;
 const TOOL_NAME: &str = env!("CARGO_PKG_NAME");
 const TOOL_VERSION: &str = env!("CARGO_PKG_VERSION");
 
-fn step<S: SetupStep>(step_object: S) -> io::Result<Option<Box<dyn SetupStep>>> {
+/// A valid, unique SPDX identifier of the form `SPDXRef-<kind>-<name>`.
+/// Characters other than letters, numbers, `.` and `-` are replaced with `-`.
+fn spdx_id(kind: &str, name: &str, taken: &mut HashSet<String>) -> String {
+    let sanitized: String = name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '.' || c == '-' { c } else { '-' })
+        .collect();
+    let base = format!("SPDXRef-{}-{}", kind, sanitized);
+    let mut id = base.clone();
+    let mut suffix = 1;
+    while !taken.insert(id.clone()) {
+        suffix += 1;
+        id = format!("{}-{}", base, suffix);
+    }
+    id
+}
+
+fn step<S: SetupStep>(step_object: S) -> StepResult {
     Ok(Some(Box::new(step_object)))
 }
 
@@ -41,10 +292,50 @@ pub fn initial_step() -> Box<dyn SetupStep> {
     Box::new(FixedDocumentPropertiesStep)
 }
 
+/// Runs the wizard from `first` until a step ends it. The steps that asked the user something are kept in a history
+/// so the user can go back, and the answers are saved after every step so an interrupted session can be resumed.
+pub fn run(data: &mut SetupData, first: Box<dyn SetupStep>) -> Result<(), StepError> {
+    let mut history: Vec<(Box<dyn SetupStep>, Snapshot)> = Vec::new();
+    let mut retries = 0;
+    let mut wrapped_step = Some(first);
+    while let Some(step) = wrapped_step.take() {
+        let snapshot = data.snapshot();
+        let answers = data.session.len();
+        match step.run(data) {
+            Ok(next) => {
+                retries = 0;
+                if data.session.len() > answers {
+                    history.push((step, snapshot));
+                }
+                wrapped_step = next;
+            }
+            Err(StepError::Back) => {
+                retries = 0;
+                data.prompter.clear_line()?;
+                let (previous, snapshot) = history.pop().unwrap_or((step, snapshot));
+                data.restore(snapshot);
+                wrapped_step = Some(previous);
+            }
+            Err(StepError::Recoverable(message)) if retries < MAX_RETRIES => {
+                retries += 1;
+                data.prompter.clear_line()?;
+                data.prompter.write_line(&format!("{}: {}", fl!(data.i18n, "error"), style(message).red().bold()))?;
+                data.restore(snapshot);
+                wrapped_step = Some(step);
+            }
+            Err(e) => return Err(e)
+        }
+        // Saving is best effort, the wizard works without it
+        let _result = data.session.save(data.dir);
+    }
+    let _result = Session::remove(data.dir);
+    Ok(())
+}
+
 struct FixedDocumentPropertiesStep;
 
 impl SetupStep for FixedDocumentPropertiesStep {
-    fn run(&self, data: &mut SetupData) -> io::Result<Option<Box<dyn SetupStep>>> {
+    fn run(&self, data: &mut SetupData) -> StepResult {
         let section = &mut data.doc.document_section;
         section.add_entry("SPDXVersion", SPDX_VERSION);
         section.add_entry("DataLicense", "CC0-1.0");
@@ -53,58 +344,60 @@ impl SetupStep for FixedDocumentPropertiesStep {
         section.add_comment("Update DocumentComment if you make further changes to this document");
         section.add_entry("DocumentComment", "This document only gives licensing information about the package it was created for, not its dependencies.");
         section.add_entry("Creator", format!("Tool: {}-{}", TOOL_NAME, TOOL_VERSION));
-        step(CreatorPersonStep)
+        if data.reuse_mode {
+            step(ReuseLintStep)
+        } else {
+            step(CreatorPersonStep)
+        }
     }
 }
 
-/// Display a selection prompt of the provided items + "Other" at the end. If "Other" is selected, the user can enter a custom value in a following input prompt.
-fn select_or_input<T: ToString>(data: &SetupData, items: &[T], select_prompt: &str, input_prompt: &str) -> io::Result<Option<String>> {
-    let last = items.len();
+/// Display a selection prompt of the provided items + "Other" + "Go Back" at the end. If "Other" is selected, the user can enter a custom value in a following input prompt.
+/// Esc skips the question here, which is why going back needs its own item. The prompts are given by their keys.
+fn select_or_input<T: ToString>(data: &SetupData, items: &[T], select_key: &str, input_key: &str) -> Result<Option<String>, StepError> {
+    let mut labels: Vec<String> = items.iter().map(T::to_string).collect();
+    let last = labels.len();
+    labels.push(fl!(data.i18n, "other"));
+    labels.push(fl!(data.i18n, "go-back"));
     let select_res = if items.is_empty() {
         Some(last)
     } else {
-        Select::with_theme(data.theme)
-            .with_prompt(select_prompt)
-            .items(items)
-            .item(fl!(data.i18n, "other"))
-            .default(0)
-            .interact_on_opt(data.term)?
+        data.prompted(select_key, &labels, || data.prompter.select(Select::with_theme(data.theme)
+            .with_prompt(data.i18n.get(select_key))
+            .items(&labels)
+            .default(0)))?
     };
 
-    if let Some(selection) = select_res {
-        let mut value = None;
-        if selection == last {
-            let input_res = Input::<String>::with_theme(data.theme)
-                .with_prompt(input_prompt)
-                .allow_empty(true)
-                .interact_on(data.term)?;
-            if !input_res.is_empty() {
-                value = Some(input_res);
-            }
-        } else {
-            value = Some(items[selection].to_string());
+    match select_res {
+        Some(selection) if selection == last => {
+            let input_res = data.input(input_key, || data.prompter.input(Input::<String>::with_theme(data.theme)
+                .with_prompt(data.i18n.get(input_key))
+                .allow_empty(true)))?;
+            Ok(Some(input_res).filter(|input| !input.is_empty()))
         }
-        Ok(value)
-    } else {
-        Ok(None)
+        Some(selection) if selection > last => Err(StepError::Back),
+        Some(selection) => Ok(Some(labels[selection].clone())),
+        None => Ok(None)
     }
 }
 
 struct CreatorPersonStep;
 
 impl SetupStep for CreatorPersonStep {
-    fn run(&self, data: &mut SetupData) -> io::Result<Option<Box<dyn SetupStep>>> {
-        let mut items = Vec::new();
+    fn run(&self, data: &mut SetupData) -> StepResult {
+        let mut items: Vec<String> = data.profile.person.iter().cloned().collect();
         if let Some(user) = data.vcs.as_ref()
-            .and_then(|vcs| vcs.user.as_ref().map(User::to_string)) {
+            .and_then(|vcs| vcs.user.as_ref().map(User::to_string))
+            .filter(|user| !items.contains(user)) {
             items.push(user);
         }
-        items.push(format!("{} ()", username()));
-        items.push(format!("{} ()", realname()));
+        // The login and real name are only a last resort
+        if data.profile.person.is_none() {
+            items.push(format!("{} ()", username()));
+            items.push(format!("{} ()", realname()));
+        }
 
-        let select_prompt = &fl!(data.i18n, "creator-person-prompt");
-        let input_prompt = &fl!(data.i18n, "creator-custom-person-prompt");
-        match select_or_input(data, items.as_slice(), select_prompt, input_prompt)? {
+        match select_or_input(data, items.as_slice(), "creator-person-prompt", "creator-custom-person-prompt")? {
             Some(person) => {
                 data.doc.document_section.add_entry("Creator", format!("Person: {}", person));
                 data.creators.push(person);
@@ -118,16 +411,20 @@ impl SetupStep for CreatorPersonStep {
 struct CreatorHasOrgStep;
 
 impl SetupStep for CreatorHasOrgStep {
-    fn run(&self, data: &mut SetupData) -> io::Result<Option<Box<dyn SetupStep>>> {
-        let has_org = Confirm::with_theme(data.theme)
+    fn run(&self, data: &mut SetupData) -> StepResult {
+        if let Some(org) = data.config.creator_organization.clone() {
+            data.doc.document_section.add_entry("Creator", format!("Organization: {}", org));
+            data.creators.push(org);
+            return step(WorkspaceStep);
+        }
+        let has_org = data.answer("creator-has-org-prompt", || data.prompter.confirm(Confirm::with_theme(data.theme)
             .with_prompt(fl!(data.i18n, "creator-has-org-prompt"))
-            .default(false)
-            .interact_on(data.term)?;
+            .default(data.profile.organization.is_some())))?;
 
         if has_org {
             step(CreatorOrgStep)
         } else {
-            step(PackageNameStep)
+            step(WorkspaceStep)
         }
     }
 }
@@ -135,28 +432,105 @@ impl SetupStep for CreatorHasOrgStep {
 struct CreatorOrgStep;
 
 impl SetupStep for CreatorOrgStep {
-    fn run(&self, data: &mut SetupData) -> io::Result<Option<Box<dyn SetupStep>>> {
-        let org = Input::<String>::with_theme(data.theme)
-            .with_prompt(fl!(data.i18n, "creator-org-prompt"))
-            .allow_empty(true)
-            .interact_on(data.term)?;
-        if !org.is_empty() {
+    fn run(&self, data: &mut SetupData) -> StepResult {
+        let items: Vec<String> = data.profile.organization.iter().cloned().collect();
+        if let Some(org) = select_or_input(data, items.as_slice(), "creator-org-select-prompt", "creator-org-prompt")? {
             data.doc.document_section.add_entry("Creator", format!("Organization: {}", org));
             data.creators.push(org);
         }
+        step(WorkspaceStep)
+    }
+}
+
+/// Offers to describe the members of a workspace instead of the project as a whole,
+/// either in one document per member or as packages of a single document.
+struct WorkspaceStep;
+
+impl SetupStep for WorkspaceStep {
+    fn run(&self, data: &mut SetupData) -> StepResult {
+        let workspace = match Workspace::detect(data.dir) {
+            Some(workspace) if !data.reuse_mode => workspace,
+            _ => return step(PackageNameStep)
+        };
+        data.prompter.write_line(&fl!(data.i18n, "workspace-found",
+            kind = workspace.kind.name(),
+            count = workspace.members.len()))?;
+        let selection = data.answer("workspace-prompt", || data.prompter.select(Select::with_theme(data.theme)
+            .with_prompt(fl!(data.i18n, "workspace-prompt"))
+            .item(fl!(data.i18n, "workspace-documents"))
+            .item(fl!(data.i18n, "workspace-packages"))
+            .item(fl!(data.i18n, "workspace-root"))
+            .default(0)))?;
+        let mode = match selection {
+            0 => WorkspaceMode::Documents,
+            1 => WorkspaceMode::Packages,
+            _ => return step(PackageNameStep)
+        };
+        data.workspace = Some(WorkspaceRun {
+            workspace,
+            mode,
+            current: 0,
+            template: data.doc.clone(),
+            supplier: None,
+            namespace_base: None,
+            deterministic: None,
+        });
         step(PackageNameStep)
     }
 }
 
+/// Continues with the next member of a workspace that is described in a single document,
+/// or with `next` once all members have been described.
+fn next_package<S: SetupStep>(data: &mut SetupData, next: S) -> StepResult {
+    let run = match data.workspace {
+        Some(ref mut run) if run.mode == WorkspaceMode::Packages => run,
+        _ => return step(next)
+    };
+    if run.current + 1 < run.workspace.members.len() {
+        run.current += 1;
+        let section = std::mem::take(&mut data.doc.package_section);
+        data.doc.member_sections.push(section);
+        data.header_scan = None;
+        return step(PackageNameStep);
+    }
+    let ids: Vec<String> = data.doc.member_sections.iter()
+        .chain(std::iter::once(&data.doc.package_section))
+        .map(|section| section.find("SPDXID")[0].to_string())
+        .collect();
+    for (member, dependency) in run.workspace.dependencies() {
+        data.doc.relationships.push(Relationship::new(&ids[member], RelationshipType::DependsOn, &ids[dependency]));
+    }
+    step(next)
+}
+
+/// Starts the document of the next member of a workspace that is described in one document per member, if there is one.
+fn next_document(data: &mut SetupData) -> StepResult {
+    match data.workspace {
+        Some(ref mut run) if run.mode == WorkspaceMode::Documents && run.current + 1 < run.workspace.members.len() => {
+            run.current += 1;
+            *data.doc = run.template.clone();
+            data.header_scan = None;
+            step(PackageNameStep)
+        }
+        _ => Ok(None)
+    }
+}
+
 struct PackageNameStep;
 
 impl SetupStep for PackageNameStep {
-    fn run(&self, data: &mut SetupData) -> io::Result<Option<Box<dyn SetupStep>>> {
-        let name = Input::<String>::with_theme(data.theme)
+    fn run(&self, data: &mut SetupData) -> StepResult {
+        let default = match data.member() {
+            Some(member) => member.name.clone(),
+            None => data.dir.file_name().and_then(|str| str.to_str()).unwrap_or_default().to_string()
+        };
+        let name = data.input("name-prompt", || data.prompter.input(Input::<String>::with_theme(data.theme)
             .with_prompt(fl!(data.i18n, "name-prompt"))
-            .default(data.dir.file_name().and_then(|str| str.to_str()).unwrap_or_default().to_string())
-            .interact_on(data.term)?;
-        data.doc.package_section.add_entry("SPDXID", format!("SPDXRef-Package-{}", &name));
+            .default(default)))?;
+        let mut taken: HashSet<String> = data.doc.element_ids().into_iter().map(str::to_string).collect();
+        let id = spdx_id("Package", &name, &mut taken);
+        data.doc.relationships.push(Relationship::new("SPDXRef-DOCUMENT", RelationshipType::Describes, &id));
+        data.doc.package_section.add_entry("SPDXID", id);
         data.doc.package_section.add_entry("PackageName", name);
         step(PackageVersionStep)
     }
@@ -165,12 +539,13 @@ impl SetupStep for PackageNameStep {
 struct PackageVersionStep;
 
 impl SetupStep for PackageVersionStep {
-    fn run(&self, data: &mut SetupData) -> io::Result<Option<Box<dyn SetupStep>>> {
-        let version = Input::<String>::with_theme(data.theme)
+    fn run(&self, data: &mut SetupData) -> StepResult {
+        let version = data.input("version-prompt", || data.prompter.input(Input::<String>::with_theme(data.theme)
             .with_prompt(fl!(data.i18n, "version-prompt"))
-            .with_initial_text(data.vcs.as_ref().and_then(|info| info.latest_version.as_ref().map(|s| s.as_str())).unwrap_or_default())
-            .allow_empty(true)
-            .interact_on(data.term)?;
+            .with_initial_text(data.member().and_then(|member| member.version.as_deref())
+                .or_else(|| data.vcs.as_ref().and_then(|info| info.latest_version.as_deref()))
+                .unwrap_or_default())
+            .allow_empty(true)))?;
         if !version.is_empty() {
             data.doc.package_section.add_entry("PackageVersion", version);
         }
@@ -181,15 +556,20 @@ impl SetupStep for PackageVersionStep {
 struct DocumentNameStep;
 
 impl SetupStep for DocumentNameStep {
-    fn run(&self, data: &mut SetupData) -> io::Result<Option<Box<dyn SetupStep>>> {
-        let input = Input::<String>::with_theme(data.theme)
+    fn run(&self, data: &mut SetupData) -> StepResult {
+        // Further members of a workspace that is described in a single document
+        if !data.doc.document_section.find("DocumentName").is_empty() {
+            return step(PackageSupplierStep);
+        }
+        let input = data.input("doc-name-prompt", || data.prompter.input(Input::<String>::with_theme(data.theme)
             .with_prompt(fl!(data.i18n, "doc-name-prompt"))
             .default(
                 format!("{}{}",
                         data.doc.package_section.find("PackageName")[0],
                         data.doc.package_section.find("PackageVersion")
-                            .get(0).map(|v| format!("-{}", v)).unwrap_or_default())
-            ).interact_on(data.term)?;
+                            .first().map(|v| format!("-{}", v)).unwrap_or_default())
+            )))?;
+        data.doc.document_section.add_entry("Created", vcs::spdx_timestamp(data.created));
         data.doc.document_section.add_entry("DocumentName", input);
         step(DocumentNamespaceStep)
     }
@@ -198,11 +578,69 @@ impl SetupStep for DocumentNameStep {
 struct DocumentNamespaceStep;
 
 impl SetupStep for DocumentNamespaceStep {
-    fn run(&self, data: &mut SetupData) -> io::Result<Option<Box<dyn SetupStep>>> {
+    fn run(&self, data: &mut SetupData) -> StepResult {
+        let shared = data.workspace.as_ref().and_then(|run| run.namespace_base.clone());
+        if let Some(base) = data.config.namespace.clone().or(shared) {
+            return step(NamespaceUuidStep { base });
+        }
+        let has_host = data.answer("doc-host-available-prompt", || data.prompter.confirm(Confirm::with_theme(data.theme)
+            .with_prompt(fl!(data.i18n, "doc-host-available-prompt"))
+            .default(data.profile.namespace.is_some())))?;
+        if has_host {
+            step(DocumentHostStep)
+        } else {
+            step(NamespaceUuidStep { base: DEFAULT_NAMESPACE_BASE.to_string() })
+        }
+    }
+}
+
+struct DocumentHostStep;
+
+impl SetupStep for DocumentHostStep {
+    fn run(&self, data: &mut SetupData) -> StepResult {
+        let invalid = fl!(data.i18n, "doc-host-invalid");
+        let base = data.input("doc-host-prompt", || data.prompter.input(Input::<String>::with_theme(data.theme)
+            .with_prompt(fl!(data.i18n, "doc-host-prompt"))
+            .with_initial_text(data.profile.namespace.as_deref().unwrap_or_default())
+            .validate_with(allow_back(|input: &String| if config::is_namespace_base(input) { Ok(()) } else { Err(invalid.clone()) }))))?;
+        step(NamespaceUuidStep { base: base.trim_end_matches('/').to_string() })
+    }
+}
+
+/// Completes the namespace with a UUID that is either random or derived from the package name, version and repository.
+/// The latter gives the same namespace whenever a document is created for the same release.
+struct NamespaceUuidStep {
+    base: String,
+}
+
+impl SetupStep for NamespaceUuidStep {
+    fn run(&self, data: &mut SetupData) -> StepResult {
+        let section = &data.doc.package_section;
+        let name = section.find("PackageName")[0];
+        let version = section.find("PackageVersion").first().copied();
+        // Derived from the content of the document when it is written
+        let deterministic = data.reproducible || match data.workspace.as_ref().and_then(|run| run.deterministic) {
+            Some(deterministic) => deterministic,
+            None => data.answer("namespace-deterministic-prompt", || data.prompter.confirm(Confirm::with_theme(data.theme)
+                .with_prompt(fl!(data.i18n, "namespace-deterministic-prompt"))
+                .default(version.is_some())))?
+        };
+        let uuid = if data.reproducible {
+            Uuid::nil()
+        } else if deterministic {
+            // e.g. https://github.com/owner/repo.git#name@1.0.0
+            let remote = data.vcs.as_ref().and_then(|vcs| vcs.remote_urls.first()).map(String::as_str).unwrap_or_default();
+            Uuid::new_v5(&Uuid::NAMESPACE_URL, format!("{}#{}@{}", remote, name, version.unwrap_or_default()).as_bytes())
+        } else {
+            Uuid::new_v4()
+        };
         let doc_name = data.doc.document_section.find("DocumentName")[0];
-        let random_uuid = Uuid::new_v4();
-        let default_namespace = format!("https://spdx.org/spdxdocs/{}-{}", doc_name, random_uuid);
-        data.doc.document_section.add_entry("DocumentNamespace", &default_namespace);
+        let namespace = format!("{}/{}-{}", self.base, doc_name, uuid);
+        data.doc.document_section.add_entry("DocumentNamespace", namespace);
+        if let Some(ref mut run) = data.workspace {
+            run.namespace_base = Some(self.base.clone());
+            run.deterministic = Some(deterministic);
+        }
         step(PackageSupplierStep)
     }
 }
@@ -211,22 +649,43 @@ impl SetupStep for DocumentNamespaceStep {
 trait AuthorStep: Default {
     fn get_relevant_authors<'a>(&self, vcs: &'a VcsInfo) -> &'a [User];
 
+    /// The answer fixed by the project configuration or already given for another workspace member, if any.
+    fn configured(&self, _data: &SetupData) -> Option<String> {
+        None
+    }
+
+    /// A complete answer (e.g. `Organization: ACME Inc.`) from the profile, suggested before the project authors.
+    fn suggested(&self, _profile: &Profile) -> Option<String> {
+        None
+    }
+
     fn name(&self) -> String;
 
     fn next_step(&self) -> Box<dyn SetupStep>;
 }
 
 impl<T: AuthorStep + FinishStep + Default + Clone + 'static> SetupStep for T {
-    fn run(&self, data: &mut SetupData) -> io::Result<Option<Box<dyn SetupStep>>> {
-        let mut items: Vec<String> = data.vcs.as_ref().map(|vcs| self.get_relevant_authors(vcs).into_iter().map(User::to_string).collect()).unwrap_or_default();
+    fn run(&self, data: &mut SetupData) -> StepResult {
+        if let Some(value) = self.configured(data) {
+            return Ok(Some(self.finish(data, value)));
+        }
+        let suggestion = self.suggested(data.profile);
+        let mut items: Vec<String> = suggestion.iter().cloned().collect();
+        items.extend(data.vcs.as_ref().map(|vcs| self.get_relevant_authors(vcs).iter()
+            .take(MAX_SUGGESTED_AUTHORS)
+            .map(User::to_string)
+            .collect::<Vec<String>>()).unwrap_or_default());
         items.extend_from_slice(&data.creators);
         let noassertion = fl!(data.i18n, "no-assertion");
         items.push(noassertion.clone());
-        let select_prompt = data.i18n.get(&format!("select-{}-prompt", self.name()));
-        let input_prompt = data.i18n.get(&format!("input-{}-prompt", self.name()));
-        match select_or_input(data, items.as_slice(), &select_prompt, &input_prompt)? {
+        let select_key = format!("select-{}-prompt", self.name());
+        let input_key = format!("input-{}-prompt", self.name());
+        match select_or_input(data, items.as_slice(), &select_key, &input_key)? {
             Some(name) => if name == noassertion {
                 Ok(Some(self.finish(data, "NOASSERTION".to_string())))
+            } else if suggestion.as_ref() == Some(&name) {
+                // Already says whether it is a person or an organisation
+                Ok(Some(self.finish(data, name)))
             } else {
                 step(PersonOrOrgStep { name, delegate: Box::new(Self::default()) })
             },
@@ -243,6 +702,14 @@ impl AuthorStep for PackageSupplierStep {
         vcs.active_project_authors.as_slice()
     }
 
+    fn configured(&self, data: &SetupData) -> Option<String> {
+        data.config.supplier.clone().or_else(|| data.workspace.as_ref().and_then(|run| run.supplier.clone()))
+    }
+
+    fn suggested(&self, profile: &Profile) -> Option<String> {
+        profile.supplier.clone()
+    }
+
     fn name(&self) -> String {
         String::from("supplier")
     }
@@ -254,6 +721,9 @@ impl AuthorStep for PackageSupplierStep {
 
 impl FinishStep for PackageSupplierStep {
     fn finish(&self, data: &mut SetupData, value: String) -> Box<dyn SetupStep> {
+        if let Some(ref mut run) = data.workspace {
+            run.supplier = Some(value.clone());
+        }
         data.doc.package_section.add_entry("PackageSupplier", value);
         Box::new(AskDifferentOriginatorStep)
     }
@@ -263,39 +733,26 @@ trait FinishStep {
     fn finish(&self, data: &mut SetupData, value: String) -> Box<dyn SetupStep>;
 }
 
-enum PersonOrOrgSelect {
-    Person,
-    Org,
-    Back,
-}
-
 struct PersonOrOrgStep<S> {
     name: String,
     delegate: Box<S>,
 }
 
-impl<'a, S> SetupStep for PersonOrOrgStep<S> where S: SetupStep + FinishStep + Clone {
-    fn run(&self, data: &mut SetupData) -> io::Result<Option<Box<dyn SetupStep>>> {
-        let items = vec![PersonOrOrgSelect::Person, PersonOrOrgSelect::Org, PersonOrOrgSelect::Back];
-        let selection = Select::with_theme(data.theme)
+impl<S> SetupStep for PersonOrOrgStep<S> where S: SetupStep + FinishStep + Clone {
+    fn run(&self, data: &mut SetupData) -> StepResult {
+        let selection = data.answer("ask-person-or-org", || data.prompter.select(Select::with_theme(data.theme)
             .with_prompt(fl!(data.i18n, "ask-person-or-org"))
             .item(fl!(data.i18n, "person"))
             .item(fl!(data.i18n, "org"))
             .item(fl!(data.i18n, "go-back"))
-            .default(0)
-            .interact_on(data.term)?;
-
-        match &items[selection] {
-            PersonOrOrgSelect::Back => self.delegate.run(data),
-            other => {
-                let type_set = match other {
-                    PersonOrOrgSelect::Person => "Person",
-                    PersonOrOrgSelect::Org => "Organization",
-                    PersonOrOrgSelect::Back => panic!("This should never be reached")
-                };
-                Ok(Some(self.delegate.finish(data, format!("{}: {}", type_set, self.name))))
-            }
-        }
+            .default(0)))?;
+
+        let type_set = match selection {
+            0 => "Person",
+            1 => "Organization",
+            _ => return Err(StepError::Back)
+        };
+        Ok(Some(self.delegate.finish(data, format!("{}: {}", type_set, self.name))))
     }
 }
 
@@ -303,11 +760,10 @@ impl<'a, S> SetupStep for PersonOrOrgStep<S> where S: SetupStep + FinishStep + C
 struct AskDifferentOriginatorStep;
 
 impl SetupStep for AskDifferentOriginatorStep {
-    fn run(&self, data: &mut SetupData) -> io::Result<Option<Box<dyn SetupStep>>> {
-        let choice = Confirm::with_theme(data.theme)
+    fn run(&self, data: &mut SetupData) -> StepResult {
+        let choice = data.answer("ask-different-originator-prompt", || data.prompter.confirm(Confirm::with_theme(data.theme)
             .with_prompt(fl!(data.i18n, "ask-different-originator-prompt"))
-            .default(false)
-            .interact_on(data.term)?;
+            .default(false)))?;
 
         if choice {
             step(PackageOriginatorStep)
@@ -344,24 +800,24 @@ impl FinishStep for PackageOriginatorStep {
 struct DownloadLocationInitialStep;
 
 impl SetupStep for DownloadLocationInitialStep {
-    fn run(&self, data: &mut SetupData) -> io::Result<Option<Box<dyn SetupStep>>> {
+    fn run(&self, data: &mut SetupData) -> StepResult {
         let remotes = data.vcs.as_ref().map(|vcs| vcs.remote_urls.as_slice()).unwrap_or_default();
-        let selection = Select::with_theme(data.theme)
+        let mut items = remotes.to_vec();
+        items.push(fl!(data.i18n, "nowhere"));
+        items.push(fl!(data.i18n, "no-assertion"));
+        items.push(fl!(data.i18n, "other"));
+        let selection = data.choice("download-select-prompt", &items, || data.prompter.select(Select::with_theme(data.theme)
             .with_prompt(fl!(data.i18n, "download-select-prompt"))
-            .items(remotes)
-            .item(fl!(data.i18n, "nowhere"))
-            .item(fl!(data.i18n, "no-assertion"))
-            .item(fl!(data.i18n, "other"))
-            .default(0)
-            .interact_on(data.term)?;
+            .items(&items)
+            .default(0)))?;
 
         const DIRECT_CHOICES: [&str; 2] = ["NONE", "NOASSERTION"];
         let offset = remotes.len();
         if selection < offset {
             step(AddRevisionToVcsDownloadLocationStep { base_url: remotes[selection].clone() })
         } else if selection < offset + DIRECT_CHOICES.len() {
-            data.doc.package_section.add_entry("DownloadLocation", DIRECT_CHOICES[selection]);
-            step(DeclaredLicenseStep)
+            data.doc.package_section.add_entry("PackageDownloadLocation", DIRECT_CHOICES[selection]);
+            step(PackageHomePageStep)
         } else {
             step(OtherDownloadLocationStep)
         }
@@ -374,16 +830,14 @@ struct AddRevisionToVcsDownloadLocationStep {
 }
 
 impl SetupStep for AddRevisionToVcsDownloadLocationStep {
-    fn run(&self, data: &mut SetupData) -> io::Result<Option<Box<dyn SetupStep>>> {
+    fn run(&self, data: &mut SetupData) -> StepResult {
         if let Some(ref vcs) = data.vcs {
             let items = vcs.head_refs.as_slice();
-            let select_prompt = fl!(data.i18n, "download-rev-select-prompt");
-            let input_prompt = fl!(data.i18n, "download-rev-input-prompt");
-            let rev = select_or_input(data, items, &select_prompt, &input_prompt)?;
-            data.doc.package_section.add_entry("DownloadLocation", format!("{}+{}{}", vcs.vcs_name, self.base_url, rev.map(|r| format!("@{}", r)).unwrap_or_default()));
-            step(DeclaredLicenseStep)
+            let rev = select_or_input(data, items, "download-rev-select-prompt", "download-rev-input-prompt")?;
+            data.doc.package_section.add_entry("PackageDownloadLocation", format!("{}+{}{}", vcs.vcs_name, self.base_url, rev.map(|r| format!("@{}", r)).unwrap_or_default()));
+            step(PackageHomePageStep)
         } else {
-            step(DeclaredLicenseStep)
+            step(PackageHomePageStep)
         }
     }
 }
@@ -391,72 +845,331 @@ impl SetupStep for AddRevisionToVcsDownloadLocationStep {
 struct OtherDownloadLocationStep;
 
 impl SetupStep for OtherDownloadLocationStep {
-    fn run(&self, data: &mut SetupData) -> io::Result<Option<Box<dyn SetupStep>>> {
-        let url = Input::<String>::with_theme(data.theme)
-            .with_prompt(fl!(data.i18n, "other-download-prompt"))
-            .interact_on(data.term)?;
-        data.doc.package_section.add_entry("DownloadLocation", url);
-        step(DeclaredLicenseStep)
+    fn run(&self, data: &mut SetupData) -> StepResult {
+        let url = data.input("other-download-prompt", || data.prompter.input(Input::<String>::with_theme(data.theme)
+            .with_prompt(fl!(data.i18n, "other-download-prompt"))))?;
+        data.doc.package_section.add_entry("PackageDownloadLocation", url);
+        step(PackageHomePageStep)
     }
 }
 
-struct DeclaredLicenseStep;
+/// Distinct forge repositories (GitHub, GitLab, ...) among the VCS remotes.
+fn forge_repos(data: &SetupData) -> Vec<ForgeRepo> {
+    data.vcs.as_ref().map(|vcs| forge::detect(&vcs.remote_urls)).unwrap_or_default()
+}
+
+/// The revision the document is created for: the tag of the HEAD commit if there is one, otherwise the commit itself.
+/// The latest tag would name an older revision if there are commits after it.
+fn forge_revision(vcs: &VcsInfo) -> Option<&str> {
+    vcs.head_tag.as_deref().or(vcs.head_commit.as_deref())
+}
+
+struct PackageHomePageStep;
+
+impl SetupStep for PackageHomePageStep {
+    fn run(&self, data: &mut SetupData) -> StepResult {
+        let items: Vec<String> = forge_repos(data).iter().map(ForgeRepo::homepage).collect();
+        if let Some(homepage) = select_or_input(data, items.as_slice(), "homepage-select-prompt", "homepage-input-prompt")? {
+            data.doc.package_section.add_entry("PackageHomePage", homepage);
+        }
+        step(PackageSourceInfoStep)
+    }
+}
+
+struct PackageSourceInfoStep;
+
+impl SetupStep for PackageSourceInfoStep {
+    fn run(&self, data: &mut SetupData) -> StepResult {
+        let repos = forge_repos(data);
+        let suggestion = match (repos.first(), data.vcs.as_ref().and_then(forge_revision)) {
+            (Some(repo), Some(rev)) => fl!(data.i18n, "source-info-suggestion", rev = rev, repo = repo.homepage()),
+            _ => return step(ForgeExternalRefsStep)
+        };
+        let info = data.input("source-info-prompt", || data.prompter.input(Input::<String>::with_theme(data.theme)
+            .with_prompt(fl!(data.i18n, "source-info-prompt"))
+            .with_initial_text(suggestion)
+            .allow_empty(true)))?;
+        if !info.is_empty() {
+            data.doc.package_section.add_entry("PackageSourceInfo", info);
+        }
+        step(ForgeExternalRefsStep)
+    }
+}
+
+struct ForgeExternalRefsStep;
+
+impl SetupStep for ForgeExternalRefsStep {
+    fn run(&self, data: &mut SetupData) -> StepResult {
+        let mut refs: Vec<ExternalRef> = Vec::new();
+        if let Some(ref vcs) = data.vcs {
+            let revision = forge_revision(vcs);
+            refs.extend(forge_repos(data).iter()
+                .map(|repo| ExternalRef::new("PACKAGE-MANAGER", "purl", repo.purl(revision))));
+            if let (Some(commit), "git") = (&vcs.head_commit, vcs.vcs_name.as_str()) {
+                refs.push(ExternalRef::new("PERSISTENT-ID", "gitoid", format!("gitoid:commit:sha1:{}", commit)));
+                refs.push(ExternalRef::new("PERSISTENT-ID", "swh", format!("swh:1:rev:{}", commit)));
+            }
+        }
+        if refs.is_empty() {
+            return step(PackageUrlStep);
+        }
+
+        let items: Vec<String> = refs.iter().map(ExternalRef::to_string).collect();
+        let defaults = vec![true; items.len()];
+        let selection = data.choice("external-refs-prompt", &items, || data.prompter.multi_select(MultiSelect::with_theme(data.theme)
+            .with_prompt(fl!(data.i18n, "external-refs-prompt"))
+            .items(&items)
+            .defaults(defaults.as_slice())))?;
+        for index in selection {
+            data.doc.package_section.add_entry("ExternalRef", items[index].clone());
+        }
+        step(PackageUrlStep)
+    }
+}
 
-impl SetupStep for DeclaredLicenseStep {
-    fn run(&self, data: &mut SetupData) -> io::Result<Option<Box<dyn SetupStep>>> {
-        let license = Input::<String>::with_theme(data.theme)
-            .with_prompt(fl!(data.i18n, "license-input-prompt"))
+struct PackageUrlStep;
+
+impl SetupStep for PackageUrlStep {
+    fn run(&self, data: &mut SetupData) -> StepResult {
+        let name = data.doc.package_section.find("PackageName")[0].to_string();
+        let version = data.doc.package_section.find("PackageVersion").first().map(|v| v.to_string());
+        let dir = data.package_dir();
+        let suggestion = Ecosystem::detect(&dir)
+            .map(|ecosystem| ecosystem.purl(&dir, &name, version.as_deref()))
+            .unwrap_or_default();
+        let purl = data.input("purl-prompt", || data.prompter.input(Input::<String>::with_theme(data.theme)
+            .with_prompt(fl!(data.i18n, "purl-prompt"))
+            .with_initial_text(suggestion)
             .allow_empty(true)
-            .validate_with(|input: &String|
-                (if input.is_empty() { Ok(()) } else { Err("") })
-                    .or(spdx::Expression::parse(input)
-                        .map(|_| ())
-                        .map_err(|err| err.to_string())))
-            .interact_on(data.term)?;
-        if license.is_empty() {
-            data.doc.package_section.add_comment("Edit the line below to specify a license.");
-            data.doc.package_section.add_comment("DeclaredLicense: LICENSE-ID");
+            .validate_with(allow_back(|input: &String| if input.is_empty() { Ok(()) } else { purl::validate(input) }))))?;
+        if !purl.is_empty() {
+            let external_ref = ExternalRef::new("PACKAGE-MANAGER", "purl", purl).to_string();
+            if !data.doc.package_section.find("ExternalRef").contains(&external_ref.as_str()) {
+                data.doc.package_section.add_entry("ExternalRef", external_ref);
+            }
+        }
+        step(AskCpeStep)
+    }
+}
+
+struct AskCpeStep;
+
+impl SetupStep for AskCpeStep {
+    fn run(&self, data: &mut SetupData) -> StepResult {
+        let choice = data.answer("ask-cpe-prompt", || data.prompter.confirm(Confirm::with_theme(data.theme)
+            .with_prompt(fl!(data.i18n, "ask-cpe-prompt"))
+            .default(false)))?;
+        if choice {
+            step(CpeStep)
         } else {
-            data.doc.package_section.add_entry("DeclaredLicense", license);
+            step(AskChecksumStep)
+        }
+    }
+}
+
+struct CpeStep;
+
+impl SetupStep for CpeStep {
+    fn run(&self, data: &mut SetupData) -> StepResult {
+        let vendor = data.input("cpe-vendor-prompt", || data.prompter.input(Input::<String>::with_theme(data.theme)
+            .with_prompt(fl!(data.i18n, "cpe-vendor-prompt"))
+            .default(forge_repos(data).first().map(|repo| repo.owner().to_string()).unwrap_or_default())))?;
+        let product = data.input("cpe-product-prompt", || data.prompter.input(Input::<String>::with_theme(data.theme)
+            .with_prompt(fl!(data.i18n, "cpe-product-prompt"))
+            .default(data.doc.package_section.find("PackageName")[0].to_string())))?;
+        let version = data.input("cpe-version-prompt", || data.prompter.input(Input::<String>::with_theme(data.theme)
+            .with_prompt(fl!(data.i18n, "cpe-version-prompt"))
+            .with_initial_text(data.doc.package_section.find("PackageVersion").first().copied().unwrap_or_default())
+            .allow_empty(true)))?;
+        let cpe = data.input("cpe-confirm-prompt", || data.prompter.input(Input::<String>::with_theme(data.theme)
+            .with_prompt(fl!(data.i18n, "cpe-confirm-prompt"))
+            .with_initial_text(cpe::application(&vendor, &product, &version))
+            .validate_with(allow_back(|input: &String| cpe::validate(input)))))?;
+        data.doc.package_section.add_entry("ExternalRef", ExternalRef::new("SECURITY", "cpe23Type", cpe).to_string());
+        step(AskChecksumStep)
+    }
+}
+
+struct AskChecksumStep;
+
+impl SetupStep for AskChecksumStep {
+    fn run(&self, data: &mut SetupData) -> StepResult {
+        let is_git = data.vcs.as_ref().is_some_and(|vcs| vcs.vcs_name == "git");
+        let mut select = Select::with_theme(data.theme);
+        select.with_prompt(fl!(data.i18n, "checksum-select-prompt"))
+            .item(fl!(data.i18n, "checksum-none"))
+            .item(fl!(data.i18n, "checksum-files"));
+        if is_git {
+            select.item(fl!(data.i18n, "checksum-git-archive"));
+        }
+        match data.answer("checksum-select-prompt", || data.prompter.select(select.default(0)))? {
+            1 => step(ArtifactChecksumStep),
+            2 => step(GitArchiveChecksumStep),
+            _ => step(DeclaredLicenseStep)
+        }
+    }
+}
+
+/// Records the checksums of release artifacts. The first artifact is the file of the package. A package has a single
+/// checksum per algorithm, so the checksums of further artifacts (e.g. a wheel next to the source distribution)
+/// are listed in the package comment.
+struct ArtifactChecksumStep;
+
+impl SetupStep for ArtifactChecksumStep {
+    fn run(&self, data: &mut SetupData) -> StepResult {
+        let dir = data.dir;
+        let missing = fl!(data.i18n, "checksum-file-missing");
+        let mut artifacts = Vec::new();
+        loop {
+            let prompt = if artifacts.is_empty() {
+                fl!(data.i18n, "checksum-file-prompt")
+            } else {
+                fl!(data.i18n, "checksum-more-files-prompt")
+            };
+            let path = data.input("checksum-file-prompt", || data.prompter.input(Input::<String>::with_theme(data.theme)
+                .with_prompt(&prompt)
+                .allow_empty(true)
+                .validate_with(allow_back(|input: &String| if input.is_empty() || dir.join(input).is_file() {
+                    Ok(())
+                } else {
+                    Err(missing.clone())
+                }))))?;
+            if path.is_empty() {
+                break;
+            }
+            let artifact = data.dir.join(path);
+            let checksums = checksum::compute_file(&artifact, &Algorithm::ALL)
+                .map_err(|e| StepError::Recoverable(format!("{}: {}", artifact.display(), e)))?;
+            let file_name = artifact.file_name().and_then(|name| name.to_str()).unwrap_or_default().to_string();
+            artifacts.push((file_name, checksums));
+        }
+        let mut artifacts = artifacts.into_iter();
+        if let Some((file_name, checksums)) = artifacts.next() {
+            data.doc.package_section.add_entry("PackageFileName", file_name);
+            for checksum in checksums {
+                data.doc.package_section.add_entry("PackageChecksum", checksum.to_string());
+            }
         }
-        step(AskVerificationCodeStep)
+        let further: Vec<String> = artifacts
+            .flat_map(|(file_name, checksums)| checksums.into_iter().map(move |checksum| format!("{} {}", file_name, checksum)))
+            .collect();
+        if !further.is_empty() {
+            data.doc.package_section.add_entry("PackageComment",
+                format!("<text>Checksums of further release artifacts:\n{}</text>", further.join("\n")));
+        }
+        step(DeclaredLicenseStep)
+    }
+}
+
+/// Records the checksums of a `git archive` tarball of a revision. The archive is only kept in memory,
+/// the user is told how to create the same file for publishing.
+struct GitArchiveChecksumStep;
+
+impl SetupStep for GitArchiveChecksumStep {
+    fn run(&self, data: &mut SetupData) -> StepResult {
+        let git = match Git::open_at(data.dir) {
+            Some(git) => git,
+            None => return step(DeclaredLicenseStep)
+        };
+        let mut items = data.vcs.as_ref().map(|vcs| vcs.head_refs.clone()).unwrap_or_default();
+        if let Some(tag) = data.vcs.as_ref().and_then(|vcs| vcs.latest_version.clone()) {
+            items.retain(|item| item != &tag);
+            items.insert(0, tag);
+        }
+        if let Some(rev) = select_or_input(data, items.as_slice(), "checksum-rev-select-prompt", "download-rev-input-prompt")? {
+            let name = data.doc.package_section.find("PackageName")[0];
+            let short_rev = rev.rsplit('/').next().unwrap_or(&rev);
+            let prefix = format!("{}-{}", name, short_rev.strip_prefix('v').unwrap_or(short_rev));
+            // Most likely an unknown revision that was entered under "Other"
+            let archive = git.archive(&rev, &prefix).map_err(|e| StepError::Recoverable(e.to_string()))?;
+            let file_name = format!("{}.tar.gz", prefix);
+            let command = format!("git archive --format=tar.gz --prefix={}/ -o {} {}", prefix, file_name, rev);
+            data.prompter.write_line(&fl!(data.i18n, "checksum-archive-computed",
+                file = style(&file_name).green().to_string(),
+                command = style(command).cyan().to_string()))?;
+            data.doc.package_section.add_entry("PackageFileName", file_name);
+            for checksum in checksum::compute(archive.as_slice(), &Algorithm::ALL)? {
+                data.doc.package_section.add_entry("PackageChecksum", checksum.to_string());
+            }
+        }
+        step(DeclaredLicenseStep)
     }
 }
 
 struct AskVerificationCodeStep;
 
 impl SetupStep for AskVerificationCodeStep {
-    fn run(&self, data: &mut SetupData) -> io::Result<Option<Box<dyn SetupStep>>> {
-        let choice = Confirm::with_theme(data.theme)
-            .with_prompt(fl!(data.i18n, "ask-verif-code"))
-            .interact_on(data.term)?;
+    fn run(&self, data: &mut SetupData) -> StepResult {
+        let choice = data.answer("ask-verif-code", || data.prompter.confirm(Confirm::with_theme(data.theme)
+            .with_prompt(fl!(data.i18n, "ask-verif-code"))))?;
         if choice {
             step(VerificationCodeStep)
         } else {
-            step(FileCreateStep)
+            step(AskFileSectionsStep)
         }
     }
 }
 
+/// Adds the package verification code, computed from all files except the SPDX file and those excluded in the configuration.
 struct VerificationCodeStep;
 
 impl SetupStep for VerificationCodeStep {
-    fn run(&self, data: &mut SetupData) -> io::Result<Option<Box<dyn SetupStep>>> {
-        data.term.write_line(&format!("{}", style("Sorry, this feature is not yet implemented.").red()))?;
-        step(FileCreateStep)
+    fn run(&self, data: &mut SetupData) -> StepResult {
+        let files = data.package_files();
+        let dir = data.document_dir();
+        let (excluded, included): (Vec<&PathBuf>, Vec<&PathBuf>) = files.iter()
+            .partition(|path| path.as_path() == Path::new(&data.filename) || data.config.is_excluded(path));
+        let mut sha1s = Vec::new();
+        for path in included {
+            for checksum in checksum::compute_file(&dir.join(path), &[Algorithm::Sha1])? {
+                sha1s.push(checksum.value);
+            }
+        }
+        let excludes: Vec<String> = excluded.iter().map(|path| format!("./{}", crate::glob::slash_path(path))).collect();
+        data.doc.package_section.add_entry("PackageVerificationCode", checksum::verification_code(sha1s, &excludes));
+        step(AskFileSectionsStep)
     }
 }
 
+/// The document as it is written in reproducible mode: sorted, with `\n` line endings on every platform,
+/// and with a namespace UUID that is derived from the rest of the content.
+fn reproducible_content(doc: &mut SpdxDocument) -> String {
+    doc.sort();
+    let content = doc.to_string().replace("\r\n", "\n");
+    let uuid = Uuid::new_v5(&Uuid::NAMESPACE_URL, content.as_bytes());
+    let entries = doc.document_section.entries();
+    if let Some((index, (_, namespace))) = entries.iter().enumerate().find(|(_, (tag, _))| *tag == "DocumentNamespace") {
+        let namespace = namespace.replace(&Uuid::nil().to_string(), &uuid.to_string());
+        doc.document_section.set_value(index, namespace);
+    }
+    doc.to_string().replace("\r\n", "\n")
+}
+
 struct FileCreateStep;
 
 impl SetupStep for FileCreateStep {
-    fn run(&self, data: &mut SetupData) -> io::Result<Option<Box<dyn SetupStep>>> {
-        data.term.write_line(&fl!(data.i18n, "creating-file"))?;
-        let mut file_path = data.dir.clone();
-        file_path.push(&data.filename);
+    fn run(&self, data: &mut SetupData) -> StepResult {
+        let file_path = data.document_dir().join(&data.filename);
+        // Relative to the project directory, which differs from the file name for workspace members
+        let file = file_path.strip_prefix(data.dir).map_or(data.filename.clone(), crate::glob::slash_path);
+        if file_path.exists() {
+            let overwrite = data.answer("overwrite-prompt", || data.prompter.confirm(Confirm::with_theme(data.theme)
+                .with_prompt(fl!(data.i18n, "overwrite-prompt", file = file.as_str()))
+                .default(false)))?;
+            if !overwrite {
+                data.prompter.write_line(&fl!(data.i18n, "file-not-written", file = file.as_str()))?;
+                return next_document(data);
+            }
+        }
+        data.prompter.write_line(&fl!(data.i18n, "creating-file"))?;
+        let content = if data.reproducible {
+            reproducible_content(data.doc)
+        } else {
+            data.doc.to_string()
+        };
         let mut file = File::create(file_path.as_path())?;
-        file.write_all(data.doc.to_string().as_bytes())?;
-        Ok(None)
+        file.write_all(content.as_bytes())?;
+        next_document(data)
     }
 }
 
@@ -0,0 +1,94 @@
+// Copyright (C) 2022  JohnnyJayJay
+
+use std::collections::HashSet;
+use console::style;
+use dialoguer::Confirm;
+use i18n_embed_fl::fl;
+use crate::deps;
+use crate::deps::{Dependency, DependencyGraph, DependencyKind};
+use crate::model::{ExternalRef, Relationship, RelationshipType, SpdxSection};
+use super::{spdx_id, step, ExtractedLicensesStep, SetupData, SetupStep, StepResult};
+
+/// Offers to add the packages pinned in the lockfiles of the project, together with their relationships.
+pub(super) struct DependenciesStep;
+
+impl SetupStep for DependenciesStep {
+    fn run(&self, data: &mut SetupData) -> StepResult {
+        let member_path = data.member().map(|member| member.path.clone()).unwrap_or_default();
+        let graphs: Vec<DependencyGraph> = deps::read_lockfiles(data.dir, &member_path).into_iter()
+            .filter(|graph| !graph.packages.is_empty())
+            .collect();
+        if graphs.is_empty() {
+            return step(ExtractedLicensesStep);
+        }
+        for graph in &graphs {
+            let count = graph.packages.len();
+            data.prompter.write_line(&fl!(data.i18n, "dependencies-found",
+                count = count,
+                lockfile = style(graph.lockfile.display()).green().to_string()))?;
+        }
+        let include = data.answer("ask-dependencies", || data.prompter.confirm(Confirm::with_theme(data.theme)
+            .with_prompt(fl!(data.i18n, "ask-dependencies"))
+            .default(true)))?;
+        if include {
+            for graph in &graphs {
+                add_dependencies(data, graph);
+            }
+        }
+        step(ExtractedLicensesStep)
+    }
+}
+
+/// SPDX identifier of the section of a dependency that is already in the document, e.g. because another member of the
+/// workspace depends on it as well.
+fn existing_id(data: &SetupData, dependency: &Dependency) -> Option<String> {
+    data.doc.dependency_sections.iter()
+        .find(|section| section.find("PackageName") == [dependency.name.as_str()]
+            && section.find("PackageVersion") == [dependency.version.as_str()])
+        .map(|section| section.find("SPDXID")[0].to_string())
+}
+
+fn add_dependencies(data: &mut SetupData, graph: &DependencyGraph) {
+    let package_id = data.doc.package_section.find("SPDXID")[0].to_string();
+    let mut taken: HashSet<String> = data.doc.element_ids().into_iter().map(str::to_string).collect();
+    let existing: Vec<Option<String>> = graph.packages.iter().map(|dependency| existing_id(data, dependency)).collect();
+    let ids: Vec<String> = graph.packages.iter().zip(&existing)
+        .map(|(dependency, existing)| existing.clone().unwrap_or_else(|| {
+            spdx_id("Package", &format!("{}-{}", dependency.name, dependency.version), &mut taken)
+        }))
+        .collect();
+    for ((dependency, id), _) in graph.packages.iter().zip(&ids).zip(&existing).filter(|(_, existing)| existing.is_none()) {
+        let mut section = SpdxSection::default();
+        section.add_entry("PackageName", &dependency.name);
+        section.add_entry("SPDXID", id);
+        section.add_entry("PackageVersion", &dependency.version);
+        section.add_entry("PackageDownloadLocation", dependency.download_location.as_deref().unwrap_or("NOASSERTION"));
+        section.add_entry("FilesAnalyzed", "false");
+        for checksum in &dependency.checksums {
+            section.add_entry("PackageChecksum", checksum.to_string());
+        }
+        if let Some(purl) = &dependency.purl {
+            section.add_entry("ExternalRef", ExternalRef::new("PACKAGE-MANAGER", "purl", purl).to_string());
+        }
+        section.add_entry("PackageLicenseConcluded", "NOASSERTION");
+        section.add_entry("PackageLicenseDeclared", dependency.license.as_deref().unwrap_or("NOASSERTION"));
+        section.add_entry("PackageCopyrightText", "NOASSERTION");
+        data.doc.dependency_sections.push(section);
+    }
+    for (index, kind) in &graph.direct {
+        data.doc.relationships.push(match kind {
+            DependencyKind::Normal => Relationship::new(&package_id, RelationshipType::DependsOn, &ids[*index]),
+            DependencyKind::Dev => Relationship::new(&ids[*index], RelationshipType::DevDependencyOf, &package_id),
+            DependencyKind::Build => Relationship::new(&ids[*index], RelationshipType::BuildDependencyOf, &package_id),
+            DependencyKind::Optional => Relationship::new(&ids[*index], RelationshipType::OptionalDependencyOf, &package_id),
+            DependencyKind::Test => Relationship::new(&ids[*index], RelationshipType::TestDependencyOf, &package_id),
+            DependencyKind::Provided => Relationship::new(&ids[*index], RelationshipType::ProvidedDependencyOf, &package_id),
+            DependencyKind::Runtime => Relationship::new(&ids[*index], RelationshipType::RuntimeDependencyOf, &package_id),
+        });
+    }
+    for ((dependency, id), _) in graph.packages.iter().zip(&ids).zip(&existing).filter(|(_, existing)| existing.is_none()) {
+        for index in &dependency.dependencies {
+            data.doc.relationships.push(Relationship::new(id, RelationshipType::DependsOn, &ids[*index]));
+        }
+    }
+}
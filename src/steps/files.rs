@@ -0,0 +1,93 @@
+// Copyright (C) 2022  JohnnyJayJay
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::Path;
+use dialoguer::Confirm;
+use i18n_embed_fl::fl;
+use crate::{checksum, glob, scan};
+use crate::checksum::Algorithm;
+use crate::model::{Relationship, RelationshipType, SpdxSection};
+use crate::scan::FileHeader;
+use super::{spdx_id, step, DependenciesStep, ReuseFileSectionsStep, SetupData, SetupStep, StepResult};
+
+/// Records that the package contains the file with the given SPDX identifier.
+pub(super) fn add_contains_relationship(data: &mut SetupData, file_id: &str) {
+    let package_id = data.doc.package_section.find("SPDXID")[0].to_string();
+    data.doc.relationships.push(Relationship::new(package_id, RelationshipType::Contains, file_id));
+}
+
+pub(super) struct AskFileSectionsStep;
+
+impl SetupStep for AskFileSectionsStep {
+    fn run(&self, data: &mut SetupData) -> StepResult {
+        if data.reuse.is_some() {
+            return step(ReuseFileSectionsStep);
+        }
+        let choice = data.answer("ask-file-sections", || data.prompter.confirm(Confirm::with_theme(data.theme)
+            .with_prompt(fl!(data.i18n, "ask-file-sections"))
+            .default(false)))?;
+        if choice {
+            step(FileSectionsStep)
+        } else {
+            step(DependenciesStep)
+        }
+    }
+}
+
+/// Adds a file section for every file of the package, using the license and copyright tags found in the file headers.
+struct FileSectionsStep;
+
+impl SetupStep for FileSectionsStep {
+    fn run(&self, data: &mut SetupData) -> StepResult {
+        let files = data.package_files();
+        let dir = data.document_dir();
+        if data.header_scan.is_none() {
+            data.header_scan = Some(scan::scan_headers(&dir, &files, &data.filename));
+        }
+        let headers: HashMap<&Path, &FileHeader> = data.header_scan.iter()
+            .flat_map(|scan| &scan.files)
+            .map(|header| (header.path.as_path(), header))
+            .collect();
+
+        let mut taken: HashSet<String> = data.doc.element_ids().into_iter().map(str::to_string).collect();
+        let mut sections = Vec::new();
+        for path in files.iter().filter(|path| path.as_path() != Path::new(&data.filename)) {
+            let header = headers.get(path.as_path());
+            let licenses = header.map(|header| header.licenses.as_slice()).unwrap_or_default();
+            let copyrights = header.map(|header| header.copyrights.as_slice()).unwrap_or_default();
+
+            let id = spdx_id("File", &glob::slash_path(path), &mut taken);
+            let mut section = SpdxSection::default();
+            section.add_entry("FileName", format!("./{}", glob::slash_path(path)));
+            section.add_entry("SPDXID", &id);
+            for checksum in checksum::compute_file(&dir.join(path), &[Algorithm::Sha1, Algorithm::Sha256])? {
+                section.add_entry("FileChecksum", checksum.to_string());
+            }
+            section.add_entry("LicenseConcluded", if licenses.is_empty() {
+                "NOASSERTION".to_string()
+            } else {
+                scan::conjunction(licenses)
+            });
+            let ids: BTreeSet<String> = licenses.iter().flat_map(|expression| scan::license_ids(expression)).collect();
+            if ids.is_empty() {
+                section.add_entry("LicenseInfoInFile", "NOASSERTION");
+            }
+            for license_id in ids {
+                section.add_entry("LicenseInfoInFile", license_id);
+            }
+            section.add_entry("FileCopyrightText", if copyrights.is_empty() {
+                "NOASSERTION".to_string()
+            } else {
+                format!("<text>{}</text>", copyrights.join("\n"))
+            });
+            sections.push((id, section));
+        }
+
+        data.doc.package_section.add_entry("FilesAnalyzed", "true");
+        for (id, section) in sections {
+            add_contains_relationship(data, &id);
+            data.doc.file_sections.push(section);
+        }
+        step(DependenciesStep)
+    }
+}
@@ -0,0 +1,404 @@
+// Copyright (C) 2022  JohnnyJayJay
+
+use std::path::{Path, PathBuf};
+use console::style;
+use dialoguer::{Confirm, FuzzySelect, Input, Select};
+use i18n_embed_fl::fl;
+use spdx::identifiers;
+use crate::{detect, scan, vcs};
+use crate::model::ExtractedLicense;
+use crate::vcs::VcsInfo;
+use super::{allow_back, next_package, step, AskRelationshipsStep, AskVerificationCodeStep, SetupData, SetupStep, StepError, StepResult, MAX_SUGGESTED_AUTHORS};
+
+pub(super) fn validate_license_expression(input: &str) -> Result<(), String> {
+    spdx::Expression::parse(input)
+        .map(|_| ())
+        .map_err(|err| err.to_string())
+}
+
+/// Wraps multi-line or free-form values in the `<text>` tags required by the tag-value format.
+fn text_value(value: &str) -> String {
+    format!("<text>{}</text>", value)
+}
+
+pub(super) struct DeclaredLicenseStep;
+
+impl SetupStep for DeclaredLicenseStep {
+    fn run(&self, data: &mut SetupData) -> StepResult {
+        if let Some(license) = data.config.license.clone() {
+            data.doc.package_section.add_entry("PackageLicenseDeclared", license);
+            return step(AskScanHeadersStep);
+        }
+        let detected = detect::detect_licenses(&data.package_dir());
+        for detection in &detected {
+            data.prompter.write_line(&fl!(data.i18n, "license-detected",
+                license = style(&detection.license).green().to_string(),
+                file = detection.file.display().to_string(),
+                confidence = format!("{:.0}", detection.confidence * 100.0)))?;
+        }
+        let mut licenses: Vec<&str> = Vec::new();
+        for detection in &detected {
+            if !licenses.contains(&detection.license.as_str()) {
+                licenses.push(&detection.license);
+            }
+        }
+        let suggestions: Vec<String> = match licenses.len() {
+            0 => vec![],
+            1 => vec![licenses[0].to_string()],
+            _ => vec![licenses.join(" AND "), licenses.join(" OR ")]
+        };
+
+        let mut items: Vec<String> = suggestions.iter()
+            .map(|suggestion| fl!(data.i18n, "license-method-detected", expression = suggestion.as_str()))
+            .collect();
+        items.push(fl!(data.i18n, "license-method-search"));
+        items.push(fl!(data.i18n, "license-method-expression"));
+        items.push(fl!(data.i18n, "license-method-skip"));
+        let selection = data.choice("license-method-prompt", &items, || data.prompter.select(Select::with_theme(data.theme)
+            .with_prompt(fl!(data.i18n, "license-method-prompt"))
+            .items(&items)
+            .default(0)))?;
+        let license = match selection.checked_sub(suggestions.len()) {
+            None => confirm_expression(data, suggestions[selection].clone())?,
+            Some(0) => compose_expression(data)?,
+            Some(1) => data.input("license-input-prompt", || data.prompter.input(Input::<String>::with_theme(data.theme)
+                .with_prompt(fl!(data.i18n, "license-input-prompt"))
+                .allow_empty(true)
+                .validate_with(allow_back(|input: &String| if input.is_empty() {
+                    Ok(())
+                } else {
+                    validate_license_expression(input)
+                }))))?,
+            _ => String::new()
+        };
+        if license.is_empty() {
+            data.doc.package_section.add_comment("Edit the line below to specify a license.");
+            data.doc.package_section.add_comment("PackageLicenseDeclared: LICENSE-ID");
+        } else {
+            data.doc.package_section.add_entry("PackageLicenseDeclared", license);
+        }
+        step(AskScanHeadersStep)
+    }
+}
+
+/// Label of a license list entry in the picker, e.g. `MIT - MIT License [OSI] [FSF]`.
+fn license_label(id: &str, full_name: &str, flags: u8) -> String {
+    let mut label = format!("{} - {}", id, full_name);
+    if flags & identifiers::IS_OSI_APPROVED != 0 {
+        label.push_str(" [OSI]");
+    }
+    if flags & identifiers::IS_FSF_LIBRE != 0 {
+        label.push_str(" [FSF]");
+    }
+    if flags & identifiers::IS_DEPRECATED != 0 {
+        label.push_str(" [deprecated]");
+    }
+    label
+}
+
+/// Whether `id` is a valid identifier for a license that is not on the SPDX license list.
+fn is_license_ref(id: &str) -> bool {
+    id.strip_prefix("LicenseRef-").is_some_and(|rest| !rest.is_empty()
+        && rest.chars().all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-'))
+}
+
+/// Asks for the identifier of a license that is not on the SPDX license list.
+fn input_license_ref(data: &SetupData) -> Result<String, StepError> {
+    let invalid = fl!(data.i18n, "license-ref-invalid");
+    let id = data.input("license-ref-prompt", || data.prompter.input(Input::<String>::with_theme(data.theme)
+        .with_prompt(fl!(data.i18n, "license-ref-prompt"))
+        .with_initial_text("LicenseRef-")
+        .validate_with(allow_back(|input: &String| if is_license_ref(input) { Ok(()) } else { Err(invalid.clone()) }))))?;
+    Ok(id)
+}
+
+/// Fuzzy search over the SPDX license list, with an additional entry for custom licenses at the end.
+/// Returns the selected license identifier.
+fn pick_license(data: &SetupData) -> Result<Option<String>, StepError> {
+    let mut items: Vec<String> = identifiers::LICENSES.iter()
+        .map(|(id, full_name, flags)| license_label(id, full_name, *flags))
+        .collect();
+    items.push(fl!(data.i18n, "license-ref-item"));
+    let selection = data.prompted("license-search-prompt", &[], || data.prompter.fuzzy_select(FuzzySelect::with_theme(data.theme)
+        .with_prompt(fl!(data.i18n, "license-search-prompt"))
+        .items(items.as_slice())
+        .default(0)))?;
+    match selection {
+        Some(index) if index == identifiers::LICENSES.len() => input_license_ref(data).map(Some),
+        Some(index) => Ok(Some(identifiers::LICENSES[index].0.to_string())),
+        None => Ok(None)
+    }
+}
+
+/// Fuzzy search over the SPDX license exception list. Returns the selected exception identifier.
+fn pick_exception(data: &SetupData) -> Result<Option<&'static str>, StepError> {
+    let items: Vec<String> = identifiers::EXCEPTIONS.iter()
+        .map(|(id, flags)| if flags & identifiers::IS_DEPRECATED != 0 { format!("{} [deprecated]", id) } else { id.to_string() })
+        .collect();
+    let selection = data.prompted("exception-search-prompt", &[], || data.prompter.fuzzy_select(FuzzySelect::with_theme(data.theme)
+        .with_prompt(fl!(data.i18n, "exception-search-prompt"))
+        .items(items.as_slice())
+        .default(0)))?;
+    Ok(selection.map(|index| identifiers::EXCEPTIONS[index].0))
+}
+
+/// Picks a single license, optionally followed by `WITH` and an exception.
+fn pick_term(data: &SetupData) -> Result<Option<String>, StepError> {
+    let license = match pick_license(data)? {
+        Some(license) => license,
+        None => return Ok(None)
+    };
+    let with_exception = data.answer("ask-exception-prompt", || data.prompter.confirm(Confirm::with_theme(data.theme)
+        .with_prompt(fl!(data.i18n, "ask-exception-prompt", license = license.as_str()))
+        .default(false)))?;
+    let exception = if with_exception { pick_exception(data)? } else { None };
+    Ok(Some(match exception {
+        Some(exception) => format!("{} WITH {}", license, exception),
+        None => license
+    }))
+}
+
+/// Builds a license expression step by step from licenses picked from the license list and `AND`/`OR` operators.
+/// When the operator changes, the expression built so far is put in parentheses,
+/// so the result reads the way it was composed regardless of operator precedence.
+fn compose_expression(data: &SetupData) -> Result<String, StepError> {
+    let mut expression = match pick_term(data)? {
+        Some(term) => term,
+        None => return Ok(String::new())
+    };
+    let mut last_operator: Option<&str> = None;
+    loop {
+        let selection = data.answer("license-combine-prompt", || data.prompter.select(Select::with_theme(data.theme)
+            .with_prompt(fl!(data.i18n, "license-combine-prompt", expression = expression.as_str()))
+            .item(fl!(data.i18n, "license-combine-done"))
+            .item(fl!(data.i18n, "license-combine-and"))
+            .item(fl!(data.i18n, "license-combine-or"))
+            .default(0)))?;
+        let operator = match selection {
+            1 => "AND",
+            2 => "OR",
+            _ => break
+        };
+        if let Some(term) = pick_term(data)? {
+            if last_operator.is_some_and(|last| last != operator) {
+                expression = format!("({})", expression);
+            }
+            expression = format!("{} {} {}", expression, operator, term);
+            last_operator = Some(operator);
+        }
+    }
+    confirm_expression(data, expression)
+}
+
+/// Lets the user review and edit a license expression before it is used.
+fn confirm_expression(data: &SetupData, expression: String) -> Result<String, StepError> {
+    data.input("license-confirm-prompt", || data.prompter.input(Input::<String>::with_theme(data.theme)
+        .with_prompt(fl!(data.i18n, "license-confirm-prompt"))
+        .with_initial_text(expression)
+        .validate_with(allow_back(|input: &String| validate_license_expression(input)))))
+}
+
+struct AskScanHeadersStep;
+
+impl SetupStep for AskScanHeadersStep {
+    fn run(&self, data: &mut SetupData) -> StepResult {
+        if data.reuse.is_some() {
+            return step(ScanHeadersStep);
+        }
+        let choice = data.answer("ask-scan-headers", || data.prompter.confirm(Confirm::with_theme(data.theme)
+            .with_prompt(fl!(data.i18n, "ask-scan-headers"))
+            .default(true)))?;
+        if choice {
+            step(ScanHeadersStep)
+        } else {
+            step(ConcludedLicenseStep)
+        }
+    }
+}
+
+/// Maximum number of files without license header that are listed individually.
+const MAX_LISTED_FILES: usize = 20;
+
+struct ScanHeadersStep;
+
+impl SetupStep for ScanHeadersStep {
+    fn run(&self, data: &mut SetupData) -> StepResult {
+        // In REUSE mode, the information has already been collected from all sources
+        let header_scan = match data.reuse {
+            Some(ref project) => project.to_header_scan(),
+            None => {
+                let files = data.package_files();
+                scan::scan_headers(&data.document_dir(), &files, &data.filename)
+            }
+        };
+        let unlicensed = header_scan.unlicensed();
+        data.prompter.write_line(&fl!(data.i18n, "scan-result",
+            files = header_scan.files.len(),
+            unlicensed = unlicensed.len()))?;
+        for path in unlicensed.iter().take(MAX_LISTED_FILES) {
+            data.prompter.write_line(&format!("  {}", style(path.display()).yellow()))?;
+        }
+        if unlicensed.len() > MAX_LISTED_FILES {
+            let count = unlicensed.len() - MAX_LISTED_FILES;
+            data.prompter.write_line(&fl!(data.i18n, "scan-more-files", count = count))?;
+        }
+
+        for id in header_scan.license_ids() {
+            data.doc.package_section.add_entry("PackageLicenseInfoFromFiles", id);
+        }
+        data.header_scan = Some(header_scan);
+        step(ConcludedLicenseStep)
+    }
+}
+
+struct ConcludedLicenseStep;
+
+impl SetupStep for ConcludedLicenseStep {
+    fn run(&self, data: &mut SetupData) -> StepResult {
+        // The concluded license combines the declared license with all licenses found in file headers
+        let mut expressions: Vec<&str> = data.doc.package_section.find("PackageLicenseDeclared");
+        if let Some(ref header_scan) = data.header_scan {
+            for expression in header_scan.license_expressions() {
+                if !expressions.contains(&expression) {
+                    expressions.push(expression);
+                }
+            }
+        }
+        let default = if expressions.is_empty() {
+            "NOASSERTION".to_string()
+        } else {
+            scan::conjunction(&expressions)
+        };
+        let license = data.input("concluded-license-prompt", || data.prompter.input(Input::<String>::with_theme(data.theme)
+            .with_prompt(fl!(data.i18n, "concluded-license-prompt"))
+            .with_initial_text(default)
+            .validate_with(allow_back(|input: &String| if input == "NOASSERTION" || input == "NONE" {
+                Ok(())
+            } else {
+                validate_license_expression(input)
+            }))))?;
+        data.doc.package_section.add_entry("PackageLicenseConcluded", license);
+        step(CopyrightTextStep)
+    }
+}
+
+/// A copyright notice for the most active authors, spanning the years from the first to the last commit.
+fn vcs_copyright(vcs: &VcsInfo) -> Option<String> {
+    let first = vcs::year_of_timestamp(vcs.first_commit_time?);
+    let last = vcs::year_of_timestamp(vcs.last_commit_time?);
+    let years = if first == last { first.to_string() } else { format!("{}-{}", first, last) };
+    let holders: Vec<&str> = vcs.active_project_authors.iter()
+        .take(MAX_SUGGESTED_AUTHORS)
+        .map(|user| user.name.as_str())
+        .collect();
+    if holders.is_empty() {
+        None
+    } else {
+        Some(format!("Copyright (C) {} {}", years, holders.join(", ")))
+    }
+}
+
+struct CopyrightTextStep;
+
+impl SetupStep for CopyrightTextStep {
+    fn run(&self, data: &mut SetupData) -> StepResult {
+        let file_copyrights = data.header_scan.as_ref().map(|scan| scan.copyrights()).unwrap_or_default();
+        let suggestion = if file_copyrights.is_empty() {
+            data.vcs.as_ref().and_then(vcs_copyright)
+        } else {
+            Some(file_copyrights.join("; "))
+        };
+        let copyright = data.input("copyright-prompt", || data.prompter.input(Input::<String>::with_theme(data.theme)
+            .with_prompt(fl!(data.i18n, "copyright-prompt"))
+            .with_initial_text(suggestion.unwrap_or_else(|| "NOASSERTION".to_string()))))?;
+        let value = match copyright.as_str() {
+            "NONE" | "NOASSERTION" => copyright,
+            text => text_value(text)
+        };
+        data.doc.package_section.add_entry("PackageCopyrightText", value);
+        step(LicenseCommentsStep)
+    }
+}
+
+struct LicenseCommentsStep;
+
+impl SetupStep for LicenseCommentsStep {
+    fn run(&self, data: &mut SetupData) -> StepResult {
+        let comments = data.input("license-comments-prompt", || data.prompter.input(Input::<String>::with_theme(data.theme)
+            .with_prompt(fl!(data.i18n, "license-comments-prompt"))
+            .allow_empty(true)))?;
+        if !comments.is_empty() {
+            data.doc.package_section.add_entry("PackageLicenseComments", text_value(&comments));
+        }
+        step(AskVerificationCodeStep)
+    }
+}
+
+/// `LicenseRef-` identifiers used anywhere in the package or file sections, in order of appearance.
+fn license_refs(data: &SetupData) -> Vec<String> {
+    const PACKAGE_TAGS: [&str; 3] = ["PackageLicenseDeclared", "PackageLicenseConcluded", "PackageLicenseInfoFromFiles"];
+    const FILE_TAGS: [&str; 2] = ["LicenseConcluded", "LicenseInfoInFile"];
+    let package_values = PACKAGE_TAGS.iter().flat_map(|tag| data.doc.package_section.find(tag));
+    let file_values = data.doc.file_sections.iter()
+        .flat_map(|section| FILE_TAGS.iter().flat_map(move |tag| section.find(tag)));
+    let mut refs: Vec<String> = Vec::new();
+    for id in package_values.chain(file_values).flat_map(scan::license_ids) {
+        if id.starts_with("LicenseRef-") && !refs.contains(&id) {
+            refs.push(id);
+        }
+    }
+    refs
+}
+
+/// A file that probably contains the text of the custom license `id`, relative to the project directory.
+fn license_ref_file(data: &SetupData, id: &str) -> Option<PathBuf> {
+    let path = match data.reuse.as_ref().and_then(|project| project.license_texts.get(id)) {
+        Some(path) => path.clone(),
+        None => detect::license_files(data.dir).into_iter()
+            .find(|path| path.file_stem().is_some_and(|stem| stem == id))?
+    };
+    Some(path.strip_prefix(data.dir).map_or(path.clone(), Path::to_path_buf))
+}
+
+/// Collects the "Other Licensing Information" for every `LicenseRef-` license used in the document.
+pub(super) struct ExtractedLicensesStep;
+
+impl SetupStep for ExtractedLicensesStep {
+    fn run(&self, data: &mut SetupData) -> StepResult {
+        let known: Vec<String> = data.doc.extracted_licenses.iter().map(|license| license.id.clone()).collect();
+        for id in license_refs(data).into_iter().filter(|id| !known.contains(id)) {
+            data.prompter.write_line(&fl!(data.i18n, "license-ref-info", license = style(&id).green().to_string()))?;
+            let dir = data.dir;
+            let unreadable = fl!(data.i18n, "license-ref-text-unreadable");
+            let mut text_input = Input::<String>::with_theme(data.theme);
+            text_input.with_prompt(fl!(data.i18n, "license-ref-text-prompt"))
+                .validate_with(allow_back(|input: &String| match scan::read_text(&dir.join(input)) {
+                    Some(_) => Ok(()),
+                    None => Err(unreadable.clone())
+                }));
+            if let Some(file) = license_ref_file(data, &id) {
+                text_input.with_initial_text(file.display().to_string());
+            }
+            let text_file = data.input("license-ref-text-prompt", || data.prompter.input(&mut text_input))?;
+            let text = scan::read_text(&data.dir.join(text_file)).unwrap_or_default();
+
+            let name = data.input("license-ref-name-prompt", || data.prompter.input(Input::<String>::with_theme(data.theme)
+                .with_prompt(fl!(data.i18n, "license-ref-name-prompt"))
+                .with_initial_text(id.trim_start_matches("LicenseRef-"))))?;
+            let cross_references = data.input("license-ref-urls-prompt", || data.prompter.input(Input::<String>::with_theme(data.theme)
+                .with_prompt(fl!(data.i18n, "license-ref-urls-prompt"))
+                .allow_empty(true)))?;
+            let comment = data.input("license-ref-comment-prompt", || data.prompter.input(Input::<String>::with_theme(data.theme)
+                .with_prompt(fl!(data.i18n, "license-ref-comment-prompt"))
+                .allow_empty(true)))?;
+            data.doc.extracted_licenses.push(ExtractedLicense {
+                id,
+                text: text.trim_end().to_string(),
+                name,
+                cross_references: cross_references.split_whitespace().map(str::to_string).collect(),
+                comment: Some(comment).filter(|comment| !comment.is_empty()),
+            });
+        }
+        next_package(data, AskRelationshipsStep)
+    }
+}
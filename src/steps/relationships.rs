@@ -0,0 +1,85 @@
+// Copyright (C) 2022  JohnnyJayJay
+
+use dialoguer::{Confirm, FuzzySelect, Select};
+use i18n_embed_fl::fl;
+use crate::model::{Relationship, RelationshipType};
+use super::{step, ReviewStep, SetupData, SetupStep, StepResult};
+
+/// Relationship types offered first, since they are the ones that usually apply within a single project.
+const COMMON_TYPES: [RelationshipType; 3] = [
+    RelationshipType::DependsOn,
+    RelationshipType::DevDependencyOf,
+    RelationshipType::GeneratedFrom,
+];
+
+pub(super) struct AskRelationshipsStep;
+
+impl SetupStep for AskRelationshipsStep {
+    fn run(&self, data: &mut SetupData) -> StepResult {
+        if data.doc.element_ids().len() < 2 {
+            return step(ReviewStep);
+        }
+        let prompt = if data.doc.relationships.iter().any(|relationship| !is_automatic(relationship)) {
+            fl!(data.i18n, "ask-another-relationship")
+        } else {
+            fl!(data.i18n, "ask-relationships")
+        };
+        let choice = data.answer("ask-relationships", || data.prompter.confirm(Confirm::with_theme(data.theme)
+            .with_prompt(prompt)
+            .default(false)))?;
+        if choice {
+            step(RelationshipStep)
+        } else {
+            step(ReviewStep)
+        }
+    }
+}
+
+/// `DESCRIBES` and `CONTAINS` are added by the guide itself.
+fn is_automatic(relationship: &Relationship) -> bool {
+    matches!(relationship.relationship_type, RelationshipType::Describes | RelationshipType::Contains)
+}
+
+struct RelationshipStep;
+
+impl SetupStep for RelationshipStep {
+    fn run(&self, data: &mut SetupData) -> StepResult {
+        let ids: Vec<String> = data.doc.element_ids().into_iter().map(str::to_string).collect();
+        let element = data.choice("relationship-element-prompt", &ids, || data.prompter.fuzzy_select(FuzzySelect::with_theme(data.theme)
+            .with_prompt(fl!(data.i18n, "relationship-element-prompt"))
+            .items(&ids)
+            .default(0)))?;
+
+        let mut type_items: Vec<String> = COMMON_TYPES.iter().map(RelationshipType::to_string).collect();
+        type_items.push(fl!(data.i18n, "other"));
+        let type_index = data.answer("relationship-type-prompt", || data.prompter.select(Select::with_theme(data.theme)
+            .with_prompt(fl!(data.i18n, "relationship-type-prompt"))
+            .items(&type_items)
+            .default(0)))?;
+        let relationship_type = match COMMON_TYPES.get(type_index) {
+            Some(relationship_type) => *relationship_type,
+            None => {
+                let index = data.answer("relationship-type-prompt", || data.prompter.fuzzy_select(FuzzySelect::with_theme(data.theme)
+                    .with_prompt(fl!(data.i18n, "relationship-type-prompt"))
+                    .items(&RelationshipType::ALL)
+                    .default(0)))?;
+                RelationshipType::ALL[index]
+            }
+        };
+
+        let related_ids: Vec<String> = ids.iter().enumerate()
+            .filter(|(i, _)| *i != element)
+            .map(|(_, id)| id.clone())
+            .collect();
+        let related = data.choice("relationship-related-prompt", &related_ids, || data.prompter.fuzzy_select(FuzzySelect::with_theme(data.theme)
+            .with_prompt(fl!(data.i18n, "relationship-related-prompt", element = ids[element].as_str(), relationship = relationship_type.to_string()))
+            .items(&related_ids)
+            .default(0)))?;
+
+        let relationship = Relationship::new(&ids[element], relationship_type, &related_ids[related]);
+        if !data.doc.relationships.contains(&relationship) {
+            data.doc.relationships.push(relationship);
+        }
+        step(AskRelationshipsStep)
+    }
+}
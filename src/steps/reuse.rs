@@ -0,0 +1,102 @@
+// Copyright (C) 2022  JohnnyJayJay
+
+use console::style;
+use dialoguer::Confirm;
+use i18n_embed::fluent::FluentLanguageLoader;
+use i18n_embed_fl::fl;
+use crate::checksum;
+use crate::checksum::Algorithm;
+use crate::glob;
+use crate::model::SpdxSection;
+use crate::reuse;
+use crate::reuse::{ReuseError, ReuseProject};
+use crate::scan;
+use super::{step, CreatorPersonStep, DependenciesStep, SetupData, SetupStep, StepResult};
+use super::files::add_contains_relationship;
+
+fn describe(error: &ReuseError, i18n: &FluentLanguageLoader) -> String {
+    match error {
+        ReuseError::BadLicense(file) => fl!(i18n, "reuse-bad-license", file = file.display().to_string()),
+        ReuseError::InvalidExpression { expression, source } =>
+            fl!(i18n, "reuse-invalid-expression", expression = expression.as_str(), source = source.as_str()),
+        ReuseError::DeprecatedLicense(license) => fl!(i18n, "reuse-deprecated-license", license = license.as_str()),
+        ReuseError::MissingLicense(license) => fl!(i18n, "reuse-missing-license", license = license.as_str()),
+        ReuseError::UnusedLicense(license) => fl!(i18n, "reuse-unused-license", license = license.as_str()),
+        ReuseError::MissingCopyright(file) => fl!(i18n, "reuse-missing-copyright", file = file.display().to_string()),
+        ReuseError::MissingLicensing(file) => fl!(i18n, "reuse-missing-licensing", file = file.display().to_string()),
+        ReuseError::InvalidConfig { source, message } =>
+            fl!(i18n, "reuse-invalid-config", source = source.as_str(), message = message.as_str()),
+    }
+}
+
+/// Reads the REUSE information of the project and reports compliance errors before the regular dialogue starts.
+pub(super) struct ReuseLintStep;
+
+impl SetupStep for ReuseLintStep {
+    fn run(&self, data: &mut SetupData) -> StepResult {
+        data.prompter.write_line(&fl!(data.i18n, "reuse-loading"))?;
+        let files = scan::project_files(data.dir, data.vcs.as_ref());
+        let project = ReuseProject::load(data.dir, &files);
+        if project.errors.is_empty() {
+            data.prompter.write_line(&format!("{}", style(fl!(data.i18n, "reuse-compliant")).green()))?;
+        } else {
+            let count = project.errors.len();
+            data.prompter.write_line(&format!("{}", style(fl!(data.i18n, "reuse-not-compliant", count = count)).red()))?;
+            for error in &project.errors {
+                data.prompter.write_line(&format!("  {}", describe(error, data.i18n)))?;
+            }
+            let proceed = data.answer("reuse-continue-prompt", || data.prompter.confirm(Confirm::with_theme(data.theme)
+                .with_prompt(fl!(data.i18n, "reuse-continue-prompt"))
+                .default(true)))?;
+            if !proceed {
+                return Ok(None);
+            }
+        }
+        data.reuse = Some(project);
+        step(CreatorPersonStep)
+    }
+}
+
+/// Adds a file section for every file covered by REUSE information, in the same form as `reuse spdx`.
+pub(super) struct ReuseFileSectionsStep;
+
+impl SetupStep for ReuseFileSectionsStep {
+    fn run(&self, data: &mut SetupData) -> StepResult {
+        let mut files = match data.reuse {
+            Some(ref project) => project.files.clone(),
+            None => return step(DependenciesStep)
+        };
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        for file in files {
+            let name = format!("./{}", glob::slash_path(&file.path));
+            let mut section = SpdxSection::default();
+            section.add_entry("FileName", &name);
+            let id = format!("SPDXRef-{:x}", md5::compute(name.as_bytes()));
+            section.add_entry("SPDXID", &id);
+            for checksum in checksum::compute_file(&data.dir.join(&file.path), &[Algorithm::Sha1])? {
+                section.add_entry("FileChecksum", checksum.to_string());
+            }
+            section.add_entry("LicenseConcluded", "NOASSERTION");
+            let mut symbols: Vec<String> = file.licenses.iter().flat_map(|expression| reuse::license_symbols(expression)).collect();
+            symbols.sort();
+            symbols.dedup();
+            if symbols.is_empty() {
+                section.add_entry("LicenseInfoInFile", "NONE");
+            }
+            for symbol in symbols {
+                section.add_entry("LicenseInfoInFile", symbol);
+            }
+            if file.copyrights.is_empty() {
+                section.add_entry("FileCopyrightText", "NONE");
+            } else {
+                let mut copyrights = file.copyrights.clone();
+                copyrights.sort();
+                section.add_entry("FileCopyrightText", format!("<text>{}</text>", copyrights.join("\n")));
+            }
+            add_contains_relationship(data, &id);
+            data.doc.file_sections.push(section);
+        }
+        data.doc.package_section.add_entry("FilesAnalyzed", "true");
+        step(DependenciesStep)
+    }
+}
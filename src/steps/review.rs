@@ -0,0 +1,161 @@
+// Copyright (C) 2022  JohnnyJayJay
+
+use std::collections::HashSet;
+use console::style;
+use dialoguer::{Input, Select};
+use i18n_embed_fl::fl;
+use crate::model::{SpdxDocument, SpdxSection};
+use super::license::validate_license_expression;
+use super::{allow_back, spdx_id, step, FileCreateStep, SetupData, SetupStep, StepResult};
+
+/// Entries that are set by spdx-guide itself or derived from other entries rather than answered by the user.
+/// The SPDXID of a package is derived from its name and the namespace from the document name.
+const FIXED_TAGS: [&str; 6] = ["SPDXVersion", "DataLicense", "SPDXID", "LicenseListVersion", "FilesAnalyzed", "DocumentNamespace"];
+/// Maximum number of characters of a value shown in the list of fields.
+const MAX_LABEL_VALUE: usize = 60;
+
+/// Colours a line of the tag-value format: headers, comments, tags and values.
+fn highlight(line: &str) -> String {
+    if line.starts_with("#####") {
+        style(line).yellow().bold().to_string()
+    } else if line.starts_with('#') {
+        style(line).dim().to_string()
+    } else {
+        match line.split_once(": ") {
+            Some((tag, value)) if !tag.is_empty() && tag.chars().all(|c| c.is_ascii_alphanumeric()) =>
+                format!("{}: {}", style(tag).cyan().bold(), value),
+            _ => line.to_string()
+        }
+    }
+}
+
+fn label(tag: &str, value: &str) -> String {
+    let mut label = format!("{}: {}", tag, value.chars().take(MAX_LABEL_VALUE).collect::<String>());
+    if value.chars().count() > MAX_LABEL_VALUE {
+        label.push('…');
+    }
+    label
+}
+
+/// A section of the document whose entries the user can change.
+#[derive(Debug, Clone, Copy)]
+enum Section {
+    Document,
+    /// A workspace member described before the current package
+    Member(usize),
+    Package,
+}
+
+impl Section {
+    fn of(self, doc: &SpdxDocument) -> &SpdxSection {
+        match self {
+            Section::Document => &doc.document_section,
+            Section::Member(index) => &doc.member_sections[index],
+            Section::Package => &doc.package_section,
+        }
+    }
+
+    fn of_mut(self, doc: &mut SpdxDocument) -> &mut SpdxSection {
+        match self {
+            Section::Document => &mut doc.document_section,
+            Section::Member(index) => &mut doc.member_sections[index],
+            Section::Package => &mut doc.package_section,
+        }
+    }
+}
+
+/// Location of an entry the user can change: its section and its index among the section's entries.
+type Field = (Section, usize);
+
+/// Index of the first entry with the given tag in [`SpdxSection::entries`].
+fn entry_index(section: &SpdxSection, tag: &str) -> Option<usize> {
+    section.entries().iter().position(|(entry, _)| *entry == tag)
+}
+
+/// Gives a package whose name changed the SPDXID derived from the new name, also in the relationships that refer to it.
+fn rename_package(doc: &mut SpdxDocument, section: Section, name: &str) {
+    let old = section.of(doc).find("SPDXID")[0].to_string();
+    let mut taken: HashSet<String> = doc.element_ids().into_iter()
+        .filter(|id| *id != old)
+        .map(str::to_string)
+        .collect();
+    let id = spdx_id("Package", name, &mut taken);
+    let package = section.of_mut(doc);
+    if let Some(index) = entry_index(package, "SPDXID") {
+        package.set_value(index, &id);
+    }
+    for relationship in &mut doc.relationships {
+        if relationship.element == old {
+            relationship.element = id.clone();
+        }
+        if relationship.related_element == old {
+            relationship.related_element = id.clone();
+        }
+    }
+}
+
+/// Puts a new document name into the namespace, which has the form `base/name-uuid`. The UUID stays the same.
+fn rename_namespace(doc: &mut SpdxDocument, old: &str, new: &str) {
+    let section = &mut doc.document_section;
+    let index = match entry_index(section, "DocumentNamespace") {
+        Some(index) => index,
+        None => return
+    };
+    let namespace = section.entries()[index].1.to_string();
+    if let Some(start) = namespace.rfind(&format!("/{}-", old)) {
+        section.set_value(index, format!("{}/{}-{}", &namespace[..start], new, &namespace[start + old.len() + 2..]));
+    }
+}
+
+/// Shows the pending document and lets the user change the answered fields of the document and package sections before it is written.
+pub(super) struct ReviewStep;
+
+impl SetupStep for ReviewStep {
+    fn run(&self, data: &mut SetupData) -> StepResult {
+        data.prompter.write_line(&fl!(data.i18n, "review-header"))?;
+        for line in data.doc.to_string().lines() {
+            data.prompter.write_line(&highlight(line))?;
+        }
+
+        let mut fields: Vec<Field> = Vec::new();
+        let mut items = vec![fl!(data.i18n, "review-write", file = data.filename.as_str())];
+        let sections = std::iter::once(Section::Document)
+            .chain((0..data.doc.member_sections.len()).map(Section::Member))
+            .chain(std::iter::once(Section::Package));
+        for section in sections {
+            for (index, (tag, value)) in section.of(data.doc).entries().into_iter().enumerate() {
+                if !FIXED_TAGS.contains(&tag) {
+                    fields.push((section, index));
+                    items.push(label(tag, value));
+                }
+            }
+        }
+        let selection = data.choice("review-prompt", &items, || data.prompter.select(Select::with_theme(data.theme)
+            .with_prompt(fl!(data.i18n, "review-prompt"))
+            .items(&items)
+            .default(0)))?;
+        let (section, index) = match selection.checked_sub(1) {
+            Some(field) => fields[field],
+            None => return step(FileCreateStep)
+        };
+
+        let (tag, old) = section.of(data.doc).entries()[index];
+        let (tag, old) = (tag.to_string(), old.to_string());
+        let is_license = tag.contains("License") && tag != "PackageLicenseComments";
+        let value = data.input("review-edit-prompt", || data.prompter.input(Input::<String>::with_theme(data.theme)
+            .with_prompt(fl!(data.i18n, "review-edit-prompt", tag = tag.as_str()))
+            .with_initial_text(old.as_str())
+            .validate_with(allow_back(|input: &String| if !is_license || input == "NOASSERTION" || input == "NONE" {
+                Ok(())
+            } else {
+                validate_license_expression(input)
+            }))))?;
+        match tag.as_str() {
+            "PackageName" => rename_package(data.doc, section, &value),
+            "DocumentName" => rename_namespace(data.doc, &old, &value),
+            _ => {}
+        }
+        section.of_mut(data.doc).set_value(index, value);
+        step(ReviewStep)
+    }
+}
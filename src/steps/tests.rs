@@ -0,0 +1,326 @@
+// Copyright (C) 2022  JohnnyJayJay
+
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::rc::Rc;
+use dialoguer::{Confirm, FuzzySelect, Input, MultiSelect, Select};
+use dialoguer::theme::SimpleTheme;
+use i18n_embed::LanguageLoader;
+use i18n_embed::fluent::{fluent_language_loader, FluentLanguageLoader};
+use crate::Localizations;
+use crate::config::{Config, Profile};
+use crate::model::SpdxDocument;
+use crate::prompt::Prompter;
+use crate::session::Session;
+use super::{run, SetupData, SetupStep, StepError, StepResult, MAX_RETRIES};
+
+mod wizard;
+
+/// A scripted answer to a prompt.
+#[derive(Debug, Clone)]
+pub(super) enum Reply {
+    /// Index of the item chosen in a select or fuzzy select prompt
+    Select(usize),
+    MultiSelect(Vec<usize>),
+    Confirm(bool),
+    Input(&'static str),
+    /// Escape in a select, multi select or confirm prompt
+    Esc,
+}
+
+/// Answers prompts with scripted replies, in order, and keeps the output. Behaves like a closed terminal once the script is used up.
+/// Panics if a reply does not fit the kind of prompt, which means the script is out of step with the wizard.
+/// Every reply is labelled with the prompt it is meant for, to tell where that happened.
+pub(super) struct ScriptedPrompter {
+    replies: RefCell<VecDeque<(String, Reply)>>,
+    output: RefCell<Vec<String>>,
+}
+
+impl ScriptedPrompter {
+    pub(super) fn new(replies: &[Reply]) -> Self {
+        Self::labelled(replies.iter().enumerate().map(|(i, reply)| (format!("#{}", i + 1), reply.clone())).collect())
+    }
+
+    pub(super) fn labelled(replies: Vec<(String, Reply)>) -> Self {
+        ScriptedPrompter { replies: RefCell::new(replies.into_iter().collect()), output: RefCell::default() }
+    }
+
+    fn next(&self) -> io::Result<(String, Reply)> {
+        self.replies.borrow_mut().pop_front().ok_or_else(|| io::ErrorKind::UnexpectedEof.into())
+    }
+
+    /// Number of replies that have not been used.
+    pub(super) fn remaining(&self) -> usize {
+        self.replies.borrow().len()
+    }
+
+    /// The lines written so far.
+    pub(super) fn output(&self) -> Vec<String> {
+        self.output.borrow().clone()
+    }
+}
+
+impl Prompter for ScriptedPrompter {
+    fn select(&self, _select: &Select) -> io::Result<Option<usize>> {
+        match self.next()? {
+            (_, Reply::Select(index)) => Ok(Some(index)),
+            (_, Reply::Esc) => Ok(None),
+            (prompt, reply) => panic!("Expected a select reply, got {:?} meant for {}", reply, prompt)
+        }
+    }
+
+    fn fuzzy_select(&self, _select: &FuzzySelect) -> io::Result<Option<usize>> {
+        match self.next()? {
+            (_, Reply::Select(index)) => Ok(Some(index)),
+            (_, Reply::Esc) => Ok(None),
+            (prompt, reply) => panic!("Expected a select reply, got {:?} meant for {}", reply, prompt)
+        }
+    }
+
+    fn multi_select(&self, _select: &MultiSelect) -> io::Result<Option<Vec<usize>>> {
+        match self.next()? {
+            (_, Reply::MultiSelect(indices)) => Ok(Some(indices)),
+            (_, Reply::Esc) => Ok(None),
+            (prompt, reply) => panic!("Expected a multi select reply, got {:?} meant for {}", reply, prompt)
+        }
+    }
+
+    fn confirm(&self, _confirm: &Confirm) -> io::Result<Option<bool>> {
+        match self.next()? {
+            (_, Reply::Confirm(choice)) => Ok(Some(choice)),
+            (_, Reply::Esc) => Ok(None),
+            (prompt, reply) => panic!("Expected a confirm reply, got {:?} meant for {}", reply, prompt)
+        }
+    }
+
+    fn input(&self, _input: &mut Input<String>) -> io::Result<String> {
+        match self.next()? {
+            (_, Reply::Input(input)) => Ok(input.to_string()),
+            (prompt, reply) => panic!("Expected an input reply, got {:?} meant for {}", reply, prompt)
+        }
+    }
+
+    fn write_line(&self, line: &str) -> io::Result<()> {
+        self.output.borrow_mut().push(console::strip_ansi_codes(line).into_owned());
+        Ok(())
+    }
+
+    fn clear_line(&self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+pub(super) fn loader() -> FluentLanguageLoader {
+    let i18n: FluentLanguageLoader = fluent_language_loader!();
+    i18n.load_fallback_language(&Localizations).unwrap();
+    i18n.set_use_isolating(false);
+    i18n
+}
+
+/// An empty directory for the session file, unique to the test.
+pub(super) fn test_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("spdx-guide-test-{}-{}", name, std::process::id()));
+    let _result = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Runs the wizard from `first`, answering the text prompts with `script`, and returns the result along with the document and the output.
+fn run_script(dir: &PathBuf, session: Session, script: &[&'static str], first: Box<dyn SetupStep>) -> (Result<(), StepError>, SpdxDocument, Vec<String>) {
+    let replies: Vec<Reply> = script.iter().map(|input| Reply::Input(input)).collect();
+    run_replies(dir, session, &replies, first)
+}
+
+/// Runs the wizard from `first` like [`run_script`], answering any kind of prompt with `replies`.
+fn run_replies(dir: &PathBuf, session: Session, replies: &[Reply], first: Box<dyn SetupStep>) -> (Result<(), StepError>, SpdxDocument, Vec<String>) {
+    let prompter = ScriptedPrompter::new(replies);
+    let i18n = loader();
+    let config = Config::default();
+    let profile = Profile::default();
+    let mut doc = SpdxDocument::default();
+    let mut data = SetupData {
+        vcs: None,
+        creators: Vec::new(),
+        header_scan: None,
+        reuse_mode: false,
+        reproducible: false,
+        created: 0,
+        reuse: None,
+        config: &config,
+        profile: &profile,
+        doc: &mut doc,
+        prompter: &prompter,
+        filename: "LICENSE.spdx".to_string(),
+        dir,
+        i18n: &i18n,
+        theme: &SimpleTheme,
+        session,
+        workspace: None,
+    };
+    let result = run(&mut data, first);
+    (result, doc, prompter.output())
+}
+
+/// Asks for a value for each of the tags in turn and adds it to the document section.
+struct InputStep(&'static [&'static str]);
+
+impl SetupStep for InputStep {
+    fn run(&self, data: &mut SetupData) -> StepResult {
+        let value = data.input(self.0[0], || data.prompter.input(&mut Input::new()))?;
+        data.doc.document_section.add_entry(self.0[0], value);
+        match &self.0[1..] {
+            [] => Ok(None),
+            rest => Ok(Some(Box::new(InputStep(rest))))
+        }
+    }
+}
+
+/// Asks for one of the items and adds it to the document section, then asks for a value for `A`.
+struct SelectStep(&'static [&'static str]);
+
+impl SetupStep for SelectStep {
+    fn run(&self, data: &mut SetupData) -> StepResult {
+        let items: Vec<String> = self.0.iter().map(|item| item.to_string()).collect();
+        let index = data.choice("Item", &items, || data.prompter.select(Select::new().items(&items)))?;
+        data.doc.document_section.add_entry("Item", items[index].clone());
+        Ok(Some(Box::new(InputStep(&["A"]))))
+    }
+}
+
+/// Adds an entry, then fails with the given error as long as `failures` is greater than 0.
+struct FailingStep {
+    runs: Rc<Cell<usize>>,
+    failures: usize,
+    error: fn() -> StepError,
+}
+
+impl SetupStep for FailingStep {
+    fn run(&self, data: &mut SetupData) -> StepResult {
+        self.runs.set(self.runs.get() + 1);
+        data.doc.document_section.add_entry("Run", self.runs.get().to_string());
+        if self.runs.get() <= self.failures {
+            Err((self.error)())
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+fn failing(failures: usize, error: fn() -> StepError) -> (FailingStep, Rc<Cell<usize>>) {
+    let runs = Rc::new(Cell::new(0));
+    (FailingStep { runs: runs.clone(), failures, error }, runs)
+}
+
+#[test]
+fn answers_end_up_in_the_document() {
+    let dir = test_dir("answers");
+    let (result, doc, _) = run_script(&dir, Session::default(), &["a", "b"], Box::new(InputStep(&["A", "B"])));
+    assert!(result.is_ok());
+    assert_eq!(doc.document_section.entries(), vec![("A", "a"), ("B", "b")]);
+    assert!(Session::load(&dir).is_none());
+}
+
+#[test]
+fn going_back_undoes_the_previous_answer() {
+    let dir = test_dir("back");
+    let script = ["a", "<", "changed", "b", "c"];
+    let (result, doc, _) = run_script(&dir, Session::default(), &script, Box::new(InputStep(&["A", "B", "C"])));
+    assert!(result.is_ok());
+    assert_eq!(doc.document_section.entries(), vec![("A", "changed"), ("B", "b"), ("C", "c")]);
+}
+
+#[test]
+fn going_back_at_the_first_question_repeats_it() {
+    let dir = test_dir("back-first");
+    let (result, doc, _) = run_script(&dir, Session::default(), &["<", "a"], Box::new(InputStep(&["A"])));
+    assert!(result.is_ok());
+    assert_eq!(doc.document_section.entries(), vec![("A", "a")]);
+}
+
+#[test]
+fn recoverable_errors_are_retried_with_the_changes_undone() {
+    let dir = test_dir("recoverable");
+    let (step, runs) = failing(2, || StepError::Recoverable("try again".to_string()));
+    let (result, doc, output) = run_script(&dir, Session::default(), &[], Box::new(step));
+    assert!(result.is_ok());
+    assert_eq!(runs.get(), 3);
+    assert_eq!(output, vec!["Error: try again", "Error: try again"]);
+    assert_eq!(doc.document_section.entries(), vec![("Run", "3")]);
+}
+
+#[test]
+fn recoverable_errors_give_up_after_the_retry_limit() {
+    let dir = test_dir("retry-limit");
+    let (step, runs) = failing(usize::MAX, || StepError::Recoverable("try again".to_string()));
+    let (result, _, _) = run_script(&dir, Session::default(), &[], Box::new(step));
+    let error = result.unwrap_err();
+    assert!(matches!(error, StepError::Recoverable(_)));
+    assert_eq!(error.exit_code(), 65);
+    assert_eq!(runs.get(), MAX_RETRIES + 1);
+}
+
+#[test]
+fn io_errors_are_fatal() {
+    let dir = test_dir("io");
+    let (step, runs) = failing(usize::MAX, || io::Error::from(io::ErrorKind::PermissionDenied).into());
+    let (result, _, _) = run_script(&dir, Session::default(), &[], Box::new(step));
+    let error = result.unwrap_err();
+    assert!(matches!(error, StepError::Io(_)));
+    assert_eq!(error.exit_code(), 74);
+    assert_eq!(runs.get(), 1);
+}
+
+#[test]
+fn closed_input_aborts_and_keeps_the_session() {
+    let dir = test_dir("closed");
+    let (result, _, _) = run_script(&dir, Session::default(), &["a"], Box::new(InputStep(&["A", "B"])));
+    let error = result.unwrap_err();
+    assert!(matches!(error, StepError::Aborted));
+    assert_eq!(error.exit_code(), 130);
+    assert_eq!(Session::load(&dir).map(|answers| answers.len()), Some(1));
+}
+
+#[test]
+fn resumed_sessions_replay_saved_answers() {
+    let dir = test_dir("resume");
+    let (result, _, _) = run_script(&dir, Session::default(), &["a"], Box::new(InputStep(&["A", "B"])));
+    assert!(result.is_err());
+    let session = Session::resume(Session::load(&dir).unwrap());
+    let (result, doc, _) = run_script(&dir, session, &["b"], Box::new(InputStep(&["A", "B"])));
+    assert!(result.is_ok());
+    assert_eq!(doc.document_section.entries(), vec![("A", "a"), ("B", "b")]);
+    assert!(Session::load(&dir).is_none());
+}
+
+#[test]
+fn replaying_stops_at_a_different_prompt() {
+    let dir = test_dir("resume-prompt");
+    let (result, _, _) = run_script(&dir, Session::default(), &["a", "b"], Box::new(InputStep(&["A", "B", "C"])));
+    assert!(result.is_err());
+    // The saved answer to B is not replayed for C, nor any answer after it
+    let session = Session::resume(Session::load(&dir).unwrap());
+    let (result, doc, _) = run_script(&dir, session, &["c", "d"], Box::new(InputStep(&["A", "C", "B"])));
+    assert!(result.is_ok());
+    assert_eq!(doc.document_section.entries(), vec![("A", "a"), ("C", "c"), ("B", "d")]);
+}
+
+#[test]
+fn replaying_stops_when_the_selected_item_changed() {
+    let dir = test_dir("resume-items");
+    let (result, _, _) = run_replies(&dir, Session::default(), &[Reply::Select(1)], Box::new(SelectStep(&["x", "y"])));
+    assert!(result.is_err());
+    let answers = Session::load(&dir).unwrap();
+
+    // The item is still there at the same index
+    let (result, doc, _) = run_replies(&dir, Session::resume(answers.clone()), &[Reply::Input("a")], Box::new(SelectStep(&["w", "y"])));
+    assert!(result.is_ok());
+    assert_eq!(doc.document_section.entries(), vec![("Item", "y"), ("A", "a")]);
+
+    // A new item moved it, so the user is asked again
+    let (result, doc, _) = run_replies(&dir, Session::resume(answers), &[Reply::Select(2), Reply::Input("a")], Box::new(SelectStep(&["w", "x", "y"])));
+    assert!(result.is_ok());
+    assert_eq!(doc.document_section.entries(), vec![("Item", "y"), ("A", "a")]);
+}
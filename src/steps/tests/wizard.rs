@@ -0,0 +1,746 @@
+// Copyright (C) 2022  JohnnyJayJay
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use dialoguer::theme::SimpleTheme;
+use git2::{IndexAddOption, Repository, Signature, Time};
+use toml::Value;
+use uuid::Uuid;
+use crate::config::{Config, ConfigError, Profile};
+use crate::model::SpdxDocument;
+use crate::session::Session;
+use crate::vcs::Vcs;
+use crate::vcs::git::Git;
+use crate::workspace::Workspace;
+use super::{loader, test_dir, Reply, ScriptedPrompter};
+use super::super::{initial_step, run, SetupData, StepError};
+use Reply::{Confirm, Esc, Input, MultiSelect, Select};
+
+/// 2022-07-01, the time of the only commit in the test repositories.
+const COMMIT_TIME: i64 = 1_656_633_600;
+
+/// A git repository with the given files in a single commit by Jane Doe, who is also the configured user.
+fn git_repo(name: &str, files: &[(&str, &str)]) -> PathBuf {
+    let dir = test_dir(name);
+    let repo = Repository::init(&dir).unwrap();
+    let mut config = repo.config().unwrap();
+    config.set_str("user.name", "Jane Doe").unwrap();
+    config.set_str("user.email", "jane@example.com").unwrap();
+    for (path, content) in files {
+        let path = dir.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+    let mut index = repo.index().unwrap();
+    index.add_all(["*"].iter(), IndexAddOption::DEFAULT, None).unwrap();
+    index.write().unwrap();
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+    let signature = Signature::new("Jane Doe", "jane@example.com", &Time::new(COMMIT_TIME, 0)).unwrap();
+    repo.commit(Some("HEAD"), &signature, &signature, "Initial commit", &tree, &[]).unwrap();
+    dir
+}
+
+/// What the wizard runs with, apart from the replies.
+#[derive(Default)]
+struct Setup {
+    config: Config,
+    profile: Profile,
+    reproducible: bool,
+    /// Answers of an interrupted session to resume
+    answers: Option<Vec<Value>>,
+}
+
+/// Replies for a run through the whole wizard, by prompt. Every prompt gets the reply that describes the least
+/// unless it is overridden. Prompts that are only asked in some projects get no replies by default.
+struct Script {
+    prompts: Vec<(String, Vec<Reply>)>,
+}
+
+/// Prompts answered once for all members of a workspace.
+const SHARED_PROMPTS: [&str; 6] = ["person", "organisation", "workspace", "host", "deterministic", "supplier"];
+
+impl Script {
+    /// A package and document named `name`.
+    fn new(name: &'static str) -> Self {
+        let prompts = vec![
+            ("person", vec![Esc]),
+            ("organisation", vec![Confirm(false)]),
+            ("workspace", vec![]),
+            ("name", vec![Input(name)]),
+            ("version", vec![Input("")]),
+            ("document-name", vec![Input(name)]),
+            ("host", vec![Confirm(false)]),
+            ("deterministic", vec![Confirm(false)]),
+            ("supplier", vec![Select(1)]),
+            ("originator", vec![Confirm(false)]),
+            ("download-location", vec![Select(1)]),
+            ("homepage", vec![Input("")]),
+            ("source-info", vec![]),
+            ("external-refs", vec![MultiSelect(vec![])]),
+            ("purl", vec![Input("")]),
+            ("cpe", vec![Confirm(false)]),
+            ("checksums", vec![Select(0)]),
+            ("declared-license", vec![Select(2)]),
+            ("scan-headers", vec![Confirm(false)]),
+            ("concluded-license", vec![Input("NOASSERTION")]),
+            ("copyright", vec![Input("NOASSERTION")]),
+            ("license-comments", vec![Input("")]),
+            ("verification-code", vec![Confirm(false)]),
+            ("file-sections", vec![Confirm(false)]),
+            ("dependencies", vec![]),
+            ("members", vec![]),
+            ("relationships", vec![]),
+            ("review", vec![Select(0)]),
+        ];
+        Script { prompts: prompts.into_iter().map(|(prompt, replies)| (prompt.to_string(), replies)).collect() }
+    }
+
+    /// Replaces the replies to `prompt`.
+    fn with(mut self, prompt: &str, replies: Vec<Reply>) -> Self {
+        match self.prompts.iter_mut().find(|(name, _)| name == prompt) {
+            Some((_, old)) => *old = replies,
+            None => panic!("The script has no prompt {}", prompt)
+        }
+        self
+    }
+
+    /// Leaves out the replies to the given prompts.
+    fn without(self, prompts: &[&str]) -> Self {
+        prompts.iter().fold(self, |script, prompt| script.with(prompt, Vec::new()))
+    }
+
+    /// Answers the prompts of a further workspace member in the same document after the package questions.
+    fn with_member(mut self, member: Script) -> Self {
+        let member = member.without(&SHARED_PROMPTS).without(&["document-name", "members", "relationships", "review"]);
+        let position = self.prompts.iter().position(|(name, _)| name == "members").unwrap() + 1;
+        let name = member.name();
+        self.prompts.splice(position..position, member.prompts.into_iter()
+            .map(|(prompt, replies)| (format!("{} {}", name, prompt), replies)));
+        self
+    }
+
+    /// Answers the prompts of a further workspace member with a document of its own after the whole script.
+    fn then(mut self, member: Script) -> Self {
+        let member = member.without(&SHARED_PROMPTS);
+        let name = member.name();
+        self.prompts.extend(member.prompts.into_iter().map(|(prompt, replies)| (format!("{} {}", name, prompt), replies)));
+        self
+    }
+
+    fn name(&self) -> &'static str {
+        match self.prompts.iter().find(|(name, _)| name == "name").and_then(|(_, replies)| replies.first()) {
+            Some(Input(name)) => name,
+            _ => "member"
+        }
+    }
+
+    /// The replies in order, each labelled with its prompt.
+    fn replies(&self) -> Vec<(String, Reply)> {
+        self.prompts.iter()
+            .flat_map(|(prompt, replies)| replies.iter().map(move |reply| (prompt.clone(), reply.clone())))
+            .collect()
+    }
+}
+
+/// Runs the wizard in `dir`, answering with `prompter`. The document is created at the time of the commit.
+fn wizard_result(dir: &Path, setup: &Setup, prompter: &ScriptedPrompter) -> Result<(), StepError> {
+    let i18n = loader();
+    let dir = dir.to_path_buf();
+    let mut doc = SpdxDocument::default();
+    let mut data = SetupData {
+        vcs: Git::open_at(&dir).map(|git| git.read_info()),
+        creators: Vec::new(),
+        header_scan: None,
+        reuse_mode: false,
+        reproducible: setup.reproducible,
+        created: COMMIT_TIME,
+        reuse: None,
+        config: &setup.config,
+        profile: &setup.profile,
+        doc: &mut doc,
+        prompter,
+        filename: "LICENSE.spdx".to_string(),
+        dir: &dir,
+        i18n: &i18n,
+        theme: &SimpleTheme,
+        session: setup.answers.clone().map_or_else(Session::default, Session::resume),
+        workspace: None,
+    };
+    run(&mut data, initial_step())
+}
+
+/// Runs the whole wizard in `dir` with the scripted replies and returns its output.
+fn answer_wizard(dir: &Path, setup: &Setup, script: &Script) -> Vec<String> {
+    let prompter = ScriptedPrompter::labelled(script.replies());
+    if let Err(e) = wizard_result(dir, setup, &prompter) {
+        panic!("The wizard failed with {} replies left: {}", prompter.remaining(), e);
+    }
+    assert_eq!(prompter.remaining(), 0, "Not all replies were used");
+    prompter.output()
+}
+
+/// Runs the whole wizard in `dir` with the scripted replies and returns the generated SPDX file.
+fn run_wizard(dir: &Path, setup: &Setup, script: &Script) -> String {
+    answer_wizard(dir, setup, script);
+    read_document(&dir.join("LICENSE.spdx"))
+}
+
+/// Reads an SPDX file, replacing a random UUID in the document namespace with `<uuid>`.
+fn read_document(path: &Path) -> String {
+    fs::read_to_string(path).unwrap()
+        .lines()
+        .map(|line| match line.strip_prefix("DocumentNamespace: ")
+            .and_then(|namespace| namespace.len().checked_sub(36).map(|uuid_start| namespace.split_at(uuid_start)))
+            .filter(|(_, uuid)| Uuid::parse_str(uuid).is_ok_and(|uuid| uuid.get_version_num() == 4)) {
+            Some((start, _)) => format!("DocumentNamespace: {}<uuid>", start),
+            None => line.to_string()
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// The lines at the start of every document, up to the document name.
+fn document_start() -> String {
+    format!("##### Document Information
+SPDXVersion: SPDX-2.3
+DataLicense: CC0-1.0
+SPDXID: SPDXRef-DOCUMENT
+LicenseListVersion: {}
+#Update DocumentComment if you make further changes to this document
+DocumentComment: This document only gives licensing information about the package it was created for, not its dependencies.
+Creator: Tool: spdx-guide-{}", spdx::identifiers::VERSION, env!("CARGO_PKG_VERSION"))
+}
+
+#[test]
+fn minimal_document_without_license() {
+    let dir = git_repo("wizard-minimal", &[("README.md", "# Minimal\n")]);
+    let output = answer_wizard(&dir, &Setup::default(), &Script::new("minimal"));
+    let document = read_document(&dir.join("LICENSE.spdx"));
+    assert_eq!(document, format!("{}
+Created: 2022-07-01T00:00:00Z
+DocumentName: minimal
+DocumentNamespace: https://spdx.org/spdxdocs/minimal-<uuid>
+
+
+##### Package Information
+SPDXID: SPDXRef-Package-minimal
+PackageName: minimal
+PackageSupplier: NOASSERTION
+PackageDownloadLocation: NOASSERTION
+#Edit the line below to specify a license.
+#PackageLicenseDeclared: LICENSE-ID
+PackageLicenseConcluded: NOASSERTION
+PackageCopyrightText: NOASSERTION
+Relationship: SPDXRef-DOCUMENT DESCRIBES SPDXRef-Package-minimal", document_start()));
+    // The review shows exactly what is written
+    let reviewed: Vec<&str> = output.iter()
+        .skip_while(|line| *line != "This is the document that will be written:")
+        .skip(1)
+        .take_while(|line| *line != "Information collected. Creating file...")
+        .map(String::as_str)
+        .collect();
+    assert_eq!(reviewed.join("\n"), fs::read_to_string(dir.join("LICENSE.spdx")).unwrap().trim_end());
+}
+
+#[test]
+fn full_document_with_files() {
+    let dir = git_repo("wizard-full", &[
+        ("README.md", "# Project\n"),
+        ("src/lib.rs", "// SPDX-FileCopyrightText: 2022 Jane Doe\n// SPDX-License-Identifier: MIT\n"),
+    ]);
+    let document = run_wizard(&dir, &Setup::default(), &Script::new("project")
+        .with("person", vec![Select(0)]) // The configured git user
+        .with("organisation", vec![Confirm(true), Input("ACME Inc.")])
+        .with("version", vec![Input("1.0.0")])
+        .with("document-name", vec![Input("project-1.0.0")])
+        .with("host", vec![Confirm(true), Input("https://spdx.example.com/docs/")])
+        .with("deterministic", vec![Confirm(true)])
+        .with("supplier", vec![
+            Select(2), // ACME Inc.
+            Select(2), // Person or organisation? Go back
+            Select(2), // ACME Inc.
+            Select(1), // Person or organisation? Organisation
+        ])
+        .with("originator", vec![Confirm(true), Select(0), Select(0)]) // Jane Doe, a person
+        .with("download-location", vec![Select(2), Input("https://example.com/project-1.0.0.tar.gz")])
+        .with("homepage", vec![Input("https://example.com")])
+        .with("purl", vec![Input("pkg:generic/project@1.0.0")])
+        .with("declared-license", vec![Select(1), Input("MIT")])
+        .with("scan-headers", vec![Confirm(true)])
+        .with("concluded-license", vec![Input("MIT")])
+        .with("copyright", vec![Input("Copyright (C) 2022 Jane Doe")])
+        .with("license-comments", vec![Input("Checked by hand")])
+        .with("file-sections", vec![Confirm(true)])
+        .with("relationships", vec![Confirm(false)]));
+    assert_eq!(document, format!("{}
+Creator: Person: Jane Doe (jane@example.com)
+Creator: Organization: ACME Inc.
+Created: 2022-07-01T00:00:00Z
+DocumentName: project-1.0.0
+DocumentNamespace: https://spdx.example.com/docs/project-1.0.0-169f8a7a-9333-5266-9a5c-235015b9d8b5
+
+
+##### Package Information
+SPDXID: SPDXRef-Package-project
+PackageName: project
+PackageVersion: 1.0.0
+PackageSupplier: Organization: ACME Inc.
+PackageOriginator: Person: Jane Doe (jane@example.com)
+PackageDownloadLocation: https://example.com/project-1.0.0.tar.gz
+PackageHomePage: https://example.com
+ExternalRef: PACKAGE-MANAGER purl pkg:generic/project@1.0.0
+PackageLicenseDeclared: MIT
+PackageLicenseInfoFromFiles: MIT
+PackageLicenseConcluded: MIT
+PackageCopyrightText: <text>Copyright (C) 2022 Jane Doe</text>
+PackageLicenseComments: <text>Checked by hand</text>
+FilesAnalyzed: true
+Relationship: SPDXRef-DOCUMENT DESCRIBES SPDXRef-Package-project
+Relationship: SPDXRef-Package-project CONTAINS SPDXRef-File-README.md
+Relationship: SPDXRef-Package-project CONTAINS SPDXRef-File-src-lib.rs
+
+
+##### File Information
+FileName: ./README.md
+SPDXID: SPDXRef-File-README.md
+FileChecksum: SHA1: 44dad063ffd8d5e9deab362b89f9431ac4b2a341
+FileChecksum: SHA256: aef277fb6a70a89681a85e1b6d23f44ee2a6cc58490f9f5c95fc99db6d2d3542
+LicenseConcluded: NOASSERTION
+LicenseInfoInFile: NOASSERTION
+FileCopyrightText: NOASSERTION
+
+FileName: ./src/lib.rs
+SPDXID: SPDXRef-File-src-lib.rs
+FileChecksum: SHA1: 791bcbac30609cf3dbe25d0f1413738c2b4ffb55
+FileChecksum: SHA256: 14b598136776086ac32f590a0eeec7bff0aa732380e5323db11011bce3ee2d64
+LicenseConcluded: MIT
+LicenseInfoInFile: MIT
+FileCopyrightText: <text>2022 Jane Doe</text>", document_start()));
+}
+
+#[test]
+fn review_changes_a_field() {
+    let dir = git_repo("wizard-review", &[("README.md", "# Reviewed\n")]);
+    let document = run_wizard(&dir, &Setup::default(), &Script::new("reviewed")
+        .with("download-location", vec![Select(0)]) // Nowhere
+        .with("declared-license", vec![Select(1), Input("Apache-2.0")])
+        .with("concluded-license", vec![Input("Apache-2.0")])
+        .with("review", vec![
+            Select(8), // PackageLicenseDeclared
+            Input("MIT"),
+            Esc, // Back to the review before the change
+            Select(8), // PackageLicenseDeclared
+            Input("Apache-2.0 OR MIT"),
+            Select(0), // Write the file
+        ]));
+    assert!(document.contains("\nPackageDownloadLocation: NONE\nPackageLicenseDeclared: Apache-2.0 OR MIT\n"));
+}
+
+#[test]
+fn review_updates_derived_fields() {
+    let dir = git_repo("wizard-review-derived", &[("README.md", "# Reviewed\n")]);
+    let document = run_wizard(&dir, &Setup::default(), &Script::new("reviewed")
+        .with("review", vec![
+            Select(4), // DocumentName
+            Input("renamed-1.0"),
+            Select(5), // PackageName
+            Input("renamed"),
+            Select(0), // Write the file
+        ]));
+    assert!(document.contains("\nDocumentName: renamed-1.0\nDocumentNamespace: https://spdx.org/spdxdocs/renamed-1.0-<uuid>\n"));
+    assert!(document.contains("\nSPDXID: SPDXRef-Package-renamed\nPackageName: renamed\n"));
+    assert!(document.ends_with("\nRelationship: SPDXRef-DOCUMENT DESCRIBES SPDXRef-Package-renamed"));
+    assert!(!document.contains("SPDXRef-Package-reviewed"));
+}
+
+#[test]
+fn review_changes_workspace_members() {
+    let dir = git_repo("wizard-review-members", &CARGO_WORKSPACE);
+    let document = run_wizard(&dir, &Setup::default(), &Script::new("cli")
+        .with("workspace", vec![Select(1)]) // One document with a package for every member
+        .with("version", vec![Input("0.1.0")])
+        .with("document-name", vec![Input("workspace")])
+        .with_member(Script::new("core").with("version", vec![Input("0.3.0")]))
+        .with("relationships", vec![Confirm(false)])
+        .with("review", vec![
+            Select(5), // PackageName of cli
+            Input("command-line"),
+            Select(0), // Write the file
+        ]));
+    assert!(document.contains("\nSPDXID: SPDXRef-Package-command-line\nPackageName: command-line\n"));
+    assert!(document.contains("\nRelationship: SPDXRef-DOCUMENT DESCRIBES SPDXRef-Package-command-line\n"));
+    assert!(document.contains("\nRelationship: SPDXRef-Package-command-line DEPENDS_ON SPDXRef-Package-core"));
+    assert!(!document.contains("SPDXRef-Package-cli"));
+}
+
+#[test]
+fn configured_answers_are_not_asked_for() {
+    let dir = git_repo("wizard-config", &[
+        ("README.md", "# Configured\n"),
+        ("vendor/lib.js", "// Not ours\n"),
+    ]);
+    let config = Config::parse(r#"
+        [creator]
+        organization = "ACME Inc."
+
+        [package]
+        supplier = "Organization: ACME Inc."
+        license = "MIT"
+
+        [document]
+        namespace = "https://spdx.acme.com/spdxdocs/"
+
+        [verification-code]
+        exclude = "vendor/**"
+    "#).unwrap();
+    let document = run_wizard(&dir, &Setup { config, ..Setup::default() }, &Script::new("configured")
+        .without(&["organisation", "host", "supplier", "declared-license"])
+        .with("concluded-license", vec![Input("MIT")])
+        .with("verification-code", vec![Confirm(true)]));
+    assert_eq!(document, format!("{}
+Creator: Organization: ACME Inc.
+Created: 2022-07-01T00:00:00Z
+DocumentName: configured
+DocumentNamespace: https://spdx.acme.com/spdxdocs/configured-<uuid>
+
+
+##### Package Information
+SPDXID: SPDXRef-Package-configured
+PackageName: configured
+PackageSupplier: Organization: ACME Inc.
+PackageDownloadLocation: NOASSERTION
+PackageLicenseDeclared: MIT
+PackageLicenseConcluded: MIT
+PackageCopyrightText: NOASSERTION
+PackageVerificationCode: 160bb7b072579bff3d155e361b96e08a4a65d17d (excludes: ./vendor/lib.js)
+Relationship: SPDXRef-DOCUMENT DESCRIBES SPDXRef-Package-configured", document_start()));
+}
+
+#[test]
+fn profile_values_are_suggested_first() {
+    let dir = git_repo("wizard-profile", &[("README.md", "# Work\n")]);
+    let profile = Profile::parse(r#"
+        default-profile = "personal"
+
+        [profile.personal]
+        person = "Jane Doe (jane@example.com)"
+
+        [profile.work]
+        person = "Jane Doe (jane@acme.com)"
+        organization = "ACME Inc."
+        supplier = "Organization: ACME Inc."
+        namespace = "https://spdx.acme.com/spdxdocs"
+    "#, Some("work")).unwrap();
+    let document = run_wizard(&dir, &Setup { profile, ..Setup::default() }, &Script::new("work")
+        .with("person", vec![Select(0)]) // The profile person
+        .with("organisation", vec![Confirm(true), Select(0)]) // The profile organisation
+        .with("host", vec![Confirm(true), Input("https://spdx.acme.com/spdxdocs")]) // Suggested by the profile
+        .with("supplier", vec![Select(0)]) // The profile supplier, no need to say that it is an organisation
+        // The author of the first commit, Jane Doe (jane@example.com), a person
+        .with("originator", vec![Confirm(true), Select(0), Select(0)]));
+    assert_eq!(document, format!("{}
+Creator: Person: Jane Doe (jane@acme.com)
+Creator: Organization: ACME Inc.
+Created: 2022-07-01T00:00:00Z
+DocumentName: work
+DocumentNamespace: https://spdx.acme.com/spdxdocs/work-<uuid>
+
+
+##### Package Information
+SPDXID: SPDXRef-Package-work
+PackageName: work
+PackageSupplier: Organization: ACME Inc.
+PackageOriginator: Person: Jane Doe (jane@example.com)
+PackageDownloadLocation: NOASSERTION
+#Edit the line below to specify a license.
+#PackageLicenseDeclared: LICENSE-ID
+PackageLicenseConcluded: NOASSERTION
+PackageCopyrightText: NOASSERTION
+Relationship: SPDXRef-DOCUMENT DESCRIBES SPDXRef-Package-work", document_start()));
+}
+
+#[test]
+fn unknown_profiles_are_an_error() {
+    assert!(matches!(Profile::parse("[profile.work]", Some("personal")), Err(ConfigError::UnknownProfile(_))));
+    assert!(Profile::parse("", None).is_ok_and(|profile| profile.person.is_none()));
+}
+
+#[test]
+fn reproducible_documents_are_identical() {
+    let files = [
+        ("README.md", "# Reproducible\r\n"),
+        ("src/main.rs", "// SPDX-License-Identifier: MIT\r\nfn main() {}\r\n"),
+    ];
+    let script = Script::new("reproducible")
+        .with("version", vec![Input("1.0.0")])
+        .with("document-name", vec![Input("reproducible-1.0.0")])
+        .without(&["deterministic"]) // No question about the namespace UUID
+        .with("download-location", vec![Select(0)])
+        .with("declared-license", vec![Select(1), Input("MIT")])
+        .with("scan-headers", vec![Confirm(true)])
+        .with("concluded-license", vec![Input("MIT")])
+        .with("verification-code", vec![Confirm(true)])
+        .with("file-sections", vec![Confirm(true)])
+        .with("relationships", vec![Confirm(false)]);
+    let setup = Setup { reproducible: true, ..Setup::default() };
+    let first = run_wizard(&git_repo("wizard-reproducible-1", &files), &setup, &script);
+    let second = run_wizard(&git_repo("wizard-reproducible-2", &files), &setup, &script);
+    assert_eq!(first, second);
+    assert!(first.contains("\nCreated: 2022-07-01T00:00:00Z\n"));
+    let namespace = first.lines().find_map(|line| line.strip_prefix("DocumentNamespace: https://spdx.org/spdxdocs/reproducible-1.0.0-")).unwrap();
+    assert_eq!(Uuid::parse_str(namespace).map(|uuid| uuid.get_version_num()), Ok(5));
+}
+
+#[test]
+fn files_are_written_before_dependencies() {
+    let dir = git_repo("wizard-dependencies", &[
+        ("Cargo.toml", "[package]\nname = \"app\"\nversion = \"0.1.0\"\n\n[dependencies]\ndep = \"1\"\n"),
+        ("Cargo.lock", "version = 3\n\n[[package]]\nname = \"app\"\nversion = \"0.1.0\"\ndependencies = [\"dep\"]\n\n\
+            [[package]]\nname = \"dep\"\nversion = \"1.0.0\"\nsource = \"registry+https://github.com/rust-lang/crates.io-index\"\n"),
+        ("src/main.rs", "fn main() {}\n"),
+    ]);
+    let document = run_wizard(&dir, &Setup::default(), &Script::new("app")
+        .with("version", vec![Input("0.1.0")])
+        .with("document-name", vec![Input("app-0.1.0")])
+        .with("file-sections", vec![Confirm(true)])
+        .with("dependencies", vec![Confirm(true)])
+        .with("relationships", vec![Confirm(false)]));
+    // A file section belongs to the package section before it
+    let files = document.find("##### File Information").unwrap();
+    let dependencies = document.find("##### Dependency Information").unwrap();
+    assert!(files < dependencies);
+    assert!(!document[dependencies..].contains("FileName: "));
+    assert!(document[dependencies..].contains("PackageName: dep\n"));
+}
+
+/// A Cargo workspace with the members `cli` and `core`, where `cli` depends on `core`.
+const CARGO_WORKSPACE: [(&str, &str); 5] = [
+    ("Cargo.toml", "[workspace]\nmembers = [\"crates/*\"]\n\n[workspace.package]\nversion = \"0.3.0\"\n"),
+    ("crates/cli/Cargo.toml", "[package]\nname = \"cli\"\nversion = \"0.1.0\"\n\n[dependencies]\ncore = { path = \"../core\" }\n"),
+    ("crates/cli/src/main.rs", "fn main() {}\n"),
+    ("crates/core/Cargo.toml", "[package]\nname = \"core\"\nversion.workspace = true\n"),
+    ("crates/core/src/lib.rs", "// SPDX-License-Identifier: MIT\n"),
+];
+
+#[test]
+fn workspace_members_are_found() {
+    let dir = git_repo("wizard-workspace-detect", &CARGO_WORKSPACE);
+    let workspace = Workspace::detect(&dir).unwrap();
+    let members: Vec<(&str, Option<&str>, &Path)> = workspace.members.iter()
+        .map(|member| (member.name.as_str(), member.version.as_deref(), member.path.as_path()))
+        .collect();
+    assert_eq!(members, vec![
+        ("cli", Some("0.1.0"), Path::new("crates/cli")),
+        ("core", Some("0.3.0"), Path::new("crates/core")),
+    ]);
+    assert_eq!(workspace.dependencies(), vec![(0, 1)]);
+    assert_eq!(workspace.member_of(Path::new("crates/core/src/lib.rs")), Some(1));
+    assert_eq!(workspace.member_of(Path::new("Cargo.toml")), None);
+}
+
+#[test]
+fn workspace_members_as_packages_of_one_document() {
+    let dir = git_repo("wizard-workspace-packages", &CARGO_WORKSPACE);
+    let document = run_wizard(&dir, &Setup::default(), &Script::new("cli")
+        .with("workspace", vec![Select(1)]) // One document with a package for every member
+        .with("version", vec![Input("0.1.0")])
+        .with("document-name", vec![Input("workspace")])
+        .with("file-sections", vec![Confirm(true)])
+        // The document and supplier are shared
+        .with_member(Script::new("core")
+            .with("version", vec![Input("0.3.0")])
+            .with("file-sections", vec![Confirm(true)]))
+        .with("relationships", vec![Confirm(false)]));
+    // Every member is followed by its own files, so that tag-value readers attribute them to it
+    let sections: Vec<&str> = document.split("\n\n\n##### ").skip(1).collect();
+    assert_eq!(sections.len(), 4);
+    assert_eq!(sections[0], "Package Information
+SPDXID: SPDXRef-Package-cli
+PackageName: cli
+PackageVersion: 0.1.0
+PackageSupplier: NOASSERTION
+PackageDownloadLocation: NOASSERTION
+#Edit the line below to specify a license.
+#PackageLicenseDeclared: LICENSE-ID
+PackageLicenseConcluded: NOASSERTION
+PackageCopyrightText: NOASSERTION
+FilesAnalyzed: true");
+    assert!(sections[1].starts_with("File Information\nFileName: ./crates/cli/Cargo.toml\n"));
+    assert!(sections[1].contains("FileName: ./crates/cli/src/main.rs\n"));
+    assert!(!sections[1].contains("crates/core"));
+    assert_eq!(sections[2], "Package Information
+SPDXID: SPDXRef-Package-core
+PackageName: core
+PackageVersion: 0.3.0
+PackageSupplier: NOASSERTION
+PackageDownloadLocation: NOASSERTION
+#Edit the line below to specify a license.
+#PackageLicenseDeclared: LICENSE-ID
+PackageLicenseConcluded: NOASSERTION
+PackageCopyrightText: NOASSERTION
+FilesAnalyzed: true
+Relationship: SPDXRef-DOCUMENT DESCRIBES SPDXRef-Package-cli
+Relationship: SPDXRef-Package-cli CONTAINS SPDXRef-File-crates-cli-Cargo.toml
+Relationship: SPDXRef-Package-cli CONTAINS SPDXRef-File-crates-cli-src-main.rs
+Relationship: SPDXRef-DOCUMENT DESCRIBES SPDXRef-Package-core
+Relationship: SPDXRef-Package-core CONTAINS SPDXRef-File-crates-core-Cargo.toml
+Relationship: SPDXRef-Package-core CONTAINS SPDXRef-File-crates-core-src-lib.rs
+Relationship: SPDXRef-Package-cli DEPENDS_ON SPDXRef-Package-core");
+    assert!(sections[3].starts_with("File Information\nFileName: ./crates/core/Cargo.toml\n"));
+    assert!(sections[3].contains("FileName: ./crates/core/src/lib.rs\n"));
+    assert!(!sections[3].contains("crates/cli"));
+    assert_eq!(document.matches("DocumentName: ").count(), 1);
+}
+
+#[test]
+fn workspace_members_in_documents_of_their_own() {
+    let dir = git_repo("wizard-workspace-documents", &[
+        ("package.json", r#"{ "private": true, "workspaces": ["packages/*"] }"#),
+        ("packages/a/package.json", r#"{ "name": "@acme/a", "version": "1.0.0" }"#),
+        ("packages/b/package.json", r#"{ "name": "@acme/b", "version": "2.0.0" }"#),
+    ]);
+    answer_wizard(&dir, &Setup::default(), &Script::new("@acme/a")
+        .with("workspace", vec![Select(0)]) // One document per member
+        .with("version", vec![Input("1.0.0")])
+        .with("document-name", vec![Input("a-1.0.0")])
+        .with("host", vec![Confirm(true), Input("https://spdx.acme.com/spdxdocs")])
+        .with("supplier", vec![Select(0), Select(1)]) // Jane Doe, an organisation
+        .with("file-sections", vec![Confirm(true)])
+        .with("relationships", vec![Confirm(false)])
+        // The namespace and supplier are not asked for again
+        .then(Script::new("@acme/b")
+            .with("version", vec![Input("2.0.0")])
+            .with("document-name", vec![Input("b-2.0.0")])
+            .with("file-sections", vec![Confirm(true)])
+            .with("relationships", vec![Confirm(false)])));
+
+    assert!(!dir.join("LICENSE.spdx").exists());
+    for (member, name) in [("a", "a-1.0.0"), ("b", "b-2.0.0")].iter() {
+        let document = read_document(&dir.join("packages").join(member).join("LICENSE.spdx"));
+        assert!(document.contains(&format!("\nDocumentNamespace: https://spdx.acme.com/spdxdocs/{}-<uuid>\n", name)));
+        assert!(document.contains("\nPackageSupplier: Organization: Jane Doe (jane@example.com)\n"));
+        let files: Vec<&str> = document.lines().filter(|line| line.starts_with("FileName: ")).collect();
+        assert_eq!(files, vec!["FileName: ./package.json"]);
+    }
+}
+#[test]
+fn workspace_members_depend_on_their_own_dependencies() {
+    let dir = git_repo("wizard-workspace-dependencies", &[
+        ("Cargo.toml", "[workspace]\nmembers = [\"util\"]\n\n[package]\nname = \"app\"\nversion = \"0.1.0\"\n\n\
+            [dependencies]\nutil = { path = \"util\" }\nserde = \"1\"\n"),
+        ("Cargo.lock", "version = 3\n\n[[package]]\nname = \"app\"\nversion = \"0.1.0\"\ndependencies = [\"serde\", \"util\"]\n\n\
+            [[package]]\nname = \"itoa\"\nversion = \"1.0.0\"\nsource = \"registry+https://github.com/rust-lang/crates.io-index\"\n\n\
+            [[package]]\nname = \"serde\"\nversion = \"1.0.0\"\nsource = \"registry+https://github.com/rust-lang/crates.io-index\"\n\n\
+            [[package]]\nname = \"util\"\nversion = \"0.1.0\"\ndependencies = [\"itoa\", \"serde\"]\n"),
+        ("src/main.rs", "fn main() {}\n"),
+        ("util/Cargo.toml", "[package]\nname = \"util\"\nversion = \"0.1.0\"\n\n[dependencies]\nitoa = \"1\"\nserde = \"1\"\n"),
+        ("util/src/lib.rs", "\n"),
+    ]);
+    let document = run_wizard(&dir, &Setup::default(), &Script::new("app")
+        .with("workspace", vec![Select(1)]) // One document with a package for every member
+        .with("version", vec![Input("0.1.0")])
+        .with("dependencies", vec![Confirm(true)])
+        // The lockfile at the root of the workspace is read for the member as well
+        .with_member(Script::new("util")
+            .with("version", vec![Input("0.1.0")])
+            .with("dependencies", vec![Confirm(true)]))
+        .with("relationships", vec![Confirm(false)]));
+    let relationships: Vec<&str> = document.lines().filter(|line| line.contains(" DEPENDS_ON ")).collect();
+    assert_eq!(relationships, vec![
+        "Relationship: SPDXRef-Package-app DEPENDS_ON SPDXRef-Package-serde-1.0.0",
+        "Relationship: SPDXRef-Package-util DEPENDS_ON SPDXRef-Package-itoa-1.0.0",
+        // Both members depend on serde, which is described once
+        "Relationship: SPDXRef-Package-util DEPENDS_ON SPDXRef-Package-serde-1.0.0",
+        "Relationship: SPDXRef-Package-app DEPENDS_ON SPDXRef-Package-util",
+    ]);
+    assert_eq!(document.matches("PackageName: serde\n").count(), 1);
+    assert_eq!(document.matches("PackageName: itoa\n").count(), 1);
+}
+
+
+#[test]
+fn resumed_sessions_leave_out_the_session_file() {
+    let dir = git_repo("wizard-resume", &[("README.md", "# Resumed\n")]);
+    let script = Script::new("resumed")
+        .with("verification-code", vec![Confirm(true)])
+        .with("file-sections", vec![Confirm(true)])
+        .with("relationships", vec![Confirm(false)]);
+    // The input is closed after the name
+    let interrupted = wizard_result(&dir, &Setup::default(), &ScriptedPrompter::labelled(script.replies().into_iter().take(3).collect()));
+    assert!(matches!(interrupted, Err(StepError::Aborted)));
+    // The session file is untracked, so git lists it among the files of the working directory
+    let setup = Setup { answers: Session::load(&dir), ..Setup::default() };
+    assert!(setup.answers.is_some());
+    let document = run_wizard(&dir, &setup, &script.without(&["person", "organisation", "name"]));
+    assert!(document.contains("FileName: ./README.md\n"));
+    assert!(!document.contains(".spdx-guide"));
+    assert!(!document.contains("(excludes:"));
+}
+
+#[test]
+fn checksums_of_several_release_artifacts() {
+    let dir = git_repo("wizard-artifacts", &[("dist/app-1.0.tar.gz", "a"), ("dist/app-1.0-py3-none-any.whl", "b")]);
+    let document = run_wizard(&dir, &Setup::default(), &Script::new("app")
+        .with("checksums", vec![
+            Select(1), // Release artifacts
+            Input("dist/app-1.0.tar.gz"),
+            Input("dist/app-1.0-py3-none-any.whl"),
+            Input(""),
+        ]));
+    assert!(document.contains("\nPackageFileName: app-1.0.tar.gz\nPackageChecksum: SHA1: 86f7e437faa5a7fce15d1ddcb9eaeaea377667b8\n"));
+    // There is one checksum per algorithm for the package, the other artifact is described in the comment
+    assert_eq!(document.matches("PackageChecksum: ").count(), 4);
+    let comment = document.split_once("PackageComment: <text>").unwrap().1.split_once("</text>").unwrap().0;
+    let lines: Vec<&str> = comment.lines().collect();
+    assert_eq!(lines.len(), 5);
+    assert_eq!(lines[0], "Checksums of further release artifacts:");
+    assert_eq!(lines[1], "app-1.0-py3-none-any.whl SHA1: e9d71f5ee7c92d6dc9e92ffdad17b8bd49418f98");
+    assert!(lines[2].starts_with("app-1.0-py3-none-any.whl SHA256: "));
+}
+
+#[test]
+fn git_archive_checksums_leave_the_project_untouched() {
+    let dir = git_repo("wizard-git-archive", &[("README.md", "# Archived\n")]);
+    let output = answer_wizard(&dir, &Setup::default(), &Script::new("archived")
+        .with("checksums", vec![Select(2), Select(0)])); // A git archive of the branch at HEAD
+    let document = read_document(&dir.join("LICENSE.spdx"));
+    let file_name = document.lines()
+        .find_map(|line| line.strip_prefix("PackageFileName: "))
+        .unwrap();
+    assert!(file_name.starts_with("archived-") && file_name.ends_with(".tar.gz"));
+    assert_eq!(document.matches("PackageChecksum: ").count(), 4);
+    assert!(!dir.join(file_name).exists());
+    // The user is told how to create the archive that was checksummed
+    let command = format!("git archive --format=tar.gz --prefix={}/ -o {} ", file_name.trim_end_matches(".tar.gz"), file_name);
+    assert!(output.iter().any(|line| line.contains(&command)));
+}
+
+#[test]
+fn commits_after_the_latest_tag_are_referred_to_by_id() {
+    let dir = git_repo("wizard-untagged-head", &[("README.md", "# Untagged\n")]);
+    let repo = Repository::open(&dir).unwrap();
+    repo.remote("origin", "https://github.com/acme/untagged.git").unwrap();
+    let tagged = repo.head().unwrap().peel_to_commit().unwrap();
+    repo.tag_lightweight("v1.0.0", tagged.as_object(), false).unwrap();
+    let signature = Signature::new("Jane Doe", "jane@example.com", &Time::new(COMMIT_TIME, 0)).unwrap();
+    let head = repo.commit(Some("HEAD"), &signature, &signature, "After the release", &tagged.tree().unwrap(), &[&tagged]).unwrap();
+    let document = run_wizard(&dir, &Setup::default(), &Script::new("untagged")
+        .with("homepage", vec![Esc])
+        .with("source-info", vec![Input("")])
+        .with("external-refs", vec![MultiSelect(vec![0])])); // purl of the GitHub repository
+    assert!(document.contains(&format!("ExternalRef: PACKAGE-MANAGER purl pkg:github/acme/untagged@{}\n", head)));
+}
+
+#[test]
+fn licenses_in_files_are_listed_once() {
+    let dir = git_repo("wizard-file-licenses", &[
+        ("src/lib.rs", "// SPDX-License-Identifier: MIT\n// SPDX-License-Identifier: Apache-2.0 OR MIT\n"),
+    ]);
+    let document = run_wizard(&dir, &Setup::default(), &Script::new("dual")
+        .with("file-sections", vec![Confirm(true)])
+        .with("relationships", vec![Confirm(false)]));
+    assert!(document.ends_with("LicenseConcluded: MIT AND (Apache-2.0 OR MIT)
+LicenseInfoInFile: Apache-2.0
+LicenseInfoInFile: MIT
+FileCopyrightText: NOASSERTION"), "{}", document);
+}
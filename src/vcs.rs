@@ -1,7 +1,10 @@
 // Copyright (C) 2022  JohnnyJayJay
 
-use std::path::PathBuf;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
 
+pub(crate) mod forge;
 pub(crate) mod git;
 
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
@@ -14,22 +17,66 @@ pub struct User {
 pub struct VcsInfo {
     pub vcs_name: String,
     pub user: Option<User>,
+    /// Authors of the commits reachable from HEAD, the one with the most commits first
     pub active_project_authors: Vec<User>,
+    /// Authors of the commits reachable from HEAD, in the order of their first commit
     pub oldest_project_authors: Vec<User>,
     pub remote_urls: Vec<String>,
     pub head_refs: Vec<String>,
+    pub head_commit: Option<String>,
+    /// Unix timestamp of the HEAD commit
+    pub head_commit_time: Option<i64>,
+    /// Unix timestamp of the oldest commit reachable from HEAD
+    pub first_commit_time: Option<i64>,
+    /// Unix timestamp of the most recent commit reachable from HEAD
+    pub last_commit_time: Option<i64>,
+    /// Files in the working directory that are not ignored by the VCS, relative to the repository root
+    pub files: Vec<PathBuf>,
+    /// Name of the nearest tag reachable from HEAD
     pub latest_version: Option<String>,
+    /// Name of a tag that points at the HEAD commit
+    pub head_tag: Option<String>,
+}
+
+/// Converts a Unix timestamp to the (UTC) date it falls on: year, month (1-12) and day of the month.
+fn date_of_timestamp(seconds: i64) -> (i64, i64, i64) {
+    // Civil-from-days algorithm by Howard Hinnant
+    let days = seconds.div_euclid(86_400) + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let year = year_of_era + era * 400;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    if month_index >= 10 {
+        (year + 1, month_index - 9, day)
+    } else {
+        (year, month_index + 3, day)
+    }
+}
+
+/// Converts a Unix timestamp to the (UTC) year it falls in.
+pub fn year_of_timestamp(seconds: i64) -> i64 {
+    date_of_timestamp(seconds).0
+}
+
+/// Formats a Unix timestamp the way SPDX requires it, e.g. `2022-07-01T12:30:00Z`.
+pub fn spdx_timestamp(seconds: i64) -> String {
+    let (year, month, day) = date_of_timestamp(seconds);
+    let time = seconds.rem_euclid(86_400);
+    format!("{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z", year, month, day, time / 3600, time % 3600 / 60, time % 60)
 }
 
 pub trait Vcs: Sized {
-    fn open_at(path: &PathBuf) -> Option<Self>;
+    fn open_at(path: &Path) -> Option<Self>;
 
     fn read_info(&self) -> VcsInfo;
 }
 
-impl ToString for User {
-    fn to_string(&self) -> String {
-        format!("{}{}", self.name, self.email.as_ref()
+impl Display for User {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.name, self.email.as_ref()
             .map_or_else(|| "".to_string(), |email| format!(" ({})", email)))
     }
-}
\ No newline at end of file
+}
@@ -1,8 +1,10 @@
 // Copyright (C) 2022  JohnnyJayJay
-use std::cmp::{min, Reverse};
+use std::cmp::Reverse;
 use std::collections::{HashMap};
-use std::path::PathBuf;
-use git2::{Oid, Repository};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use git2::{Oid, Repository, StatusOptions};
 use crate::vcs::{User, VcsInfo, Vcs};
 
 pub struct Git {
@@ -13,15 +15,32 @@ impl Git {
     fn tagged(&self, commit: Oid) -> Option<String> {
         self.repo.references_glob("refs/tags/*").ok()?
             .filter_map(|r| r.ok())
-            .find(|r| r.peel_to_commit().ok().map_or(false, |c| c.id() == commit))
+            .find(|r| r.peel_to_commit().ok().is_some_and(|c| c.id() == commit))
             .and_then(|r| r.name().map(String::from))
     }
+
+    /// Creates a gzipped tarball of `rev` using `git archive`, with every path inside it prefixed by `prefix/`.
+    pub fn archive(&self, rev: &str, prefix: &str) -> io::Result<Vec<u8>> {
+        let output = Command::new("git")
+            .arg("--git-dir").arg(self.repo.path())
+            .arg("archive")
+            .arg("--format=tar.gz")
+            .arg(format!("--prefix={}/", prefix))
+            .arg(rev)
+            .output()?;
+        if output.status.success() {
+            Ok(output.stdout)
+        } else {
+            Err(io::Error::other(String::from_utf8_lossy(&output.stderr).trim().to_string()))
+        }
+    }
 }
 
 
 
+
 impl Vcs for Git {
-    fn open_at(path: &PathBuf) -> Option<Self> {
+    fn open_at(path: &Path) -> Option<Self> {
         Repository::open(path).ok().map(|r| Git { repo: r })
     }
 
@@ -36,12 +55,12 @@ impl Vcs for Git {
 
         let remote_urls = self.repo.remotes().ok()
             .map_or(vec![], |s| s.into_iter()
-                .filter_map(|name| name)
+                .flatten()
                 .filter_map(|name| self.repo.find_remote(name).ok())
                 .filter_map(|remote| remote.url().map(String::from))
                 .collect());
 
-        let head_tag = self.repo.revwalk()
+        let latest_tag = self.repo.revwalk()
             .and_then(|mut w| w.push_head().map(|_| w))
             .ok()
             .and_then(|w| w.filter_map(|c| c.ok())
@@ -49,16 +68,25 @@ impl Vcs for Git {
 
         let head_ref = self.repo.head().ok()
             .and_then(|reference| reference.name().map(String::from));
-        let head_commit = self.repo.head().ok()
-            .and_then(|reference| reference.peel_to_commit().ok())
-            .map(|commit| commit.id().to_string());
+        let head = self.repo.head().ok()
+            .and_then(|reference| reference.peel_to_commit().ok());
+        let head_commit = head.as_ref().map(|commit| commit.id().to_string());
+        let head_commit_time = head.as_ref().map(|commit| commit.time().seconds());
+        let head_tag = head.as_ref()
+            .and_then(|commit| self.tagged(commit.id()))
+            .and_then(|name| name.strip_prefix("refs/tags/").map(String::from));
 
         let mut authors = Vec::new();
         let mut authors_seen = HashMap::new();
+        let mut first_commit_time = None;
+        let mut last_commit_time = None;
 
         if let Ok(revwalk) = self.repo.revwalk().and_then(|mut walk| { walk.push_head()?; Ok(walk) }) {
             for commit in revwalk.map(|node| node.and_then(|id| self.repo.find_commit(id))).filter_map(|res| res.ok()) {
                 //println!("{:?}: {} ({})", commit.time(), commit.message().unwrap_or_default(), commit.author());
+                let time = commit.time().seconds();
+                first_commit_time = Some(first_commit_time.map_or(time, |first: i64| first.min(time)));
+                last_commit_time = Some(last_commit_time.map_or(time, |last: i64| last.max(time)));
                 let author = commit.author();
                 if let Some(name) = author.name() {
                     let name = name.to_string();
@@ -76,21 +104,72 @@ impl Vcs for Git {
              }
         }
 
-        let max = min(5, authors.len());
+        let mut status_options = StatusOptions::new();
+        status_options.include_untracked(true)
+            .recurse_untracked_dirs(true)
+            .include_unmodified(true)
+            .include_ignored(false);
+        let workdir = self.repo.workdir();
+        let mut files: Vec<PathBuf> = self.repo.statuses(Some(&mut status_options)).ok()
+            .map_or(vec![], |statuses| statuses.iter()
+                .filter_map(|entry| entry.path().map(PathBuf::from))
+                .filter(|path| workdir.is_some_and(|dir| dir.join(path).is_file()))
+                .collect());
+        files.sort();
+
         authors.sort_by_key(|u| Reverse(authors_seen[u].0));
-        let active_authors = Vec::from(&authors[..max]);
+        let active_authors = authors.clone();
         authors.sort_by_key(|u| authors_seen[u].1);
-        let oldest_authors = Vec::from(&authors[..max]);
+        let oldest_authors = authors;
 
-        let version_str = head_tag.as_ref().and_then(|name| name.strip_prefix("refs/tags/").map(String::from));
+        let version_str = latest_tag.as_ref().and_then(|name| name.strip_prefix("refs/tags/").map(String::from));
         VcsInfo {
             vcs_name: "git".to_string(),
             user: username.map(|name| User { name, email }),
             active_project_authors: active_authors,
             oldest_project_authors: oldest_authors,
             remote_urls,
-            head_refs: vec![head_ref, head_tag, head_commit].into_iter().filter_map(|h| h).collect(),
-            latest_version: version_str
+            head_refs: vec![head_ref, latest_tag, head_commit.clone()].into_iter().flatten().collect(),
+            head_commit,
+            head_commit_time,
+            first_commit_time,
+            last_commit_time,
+            files,
+            latest_version: version_str,
+            head_tag,
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use git2::{IndexAddOption, Signature, Time};
+    use super::*;
+
+    fn repo(name: &str) -> Git {
+        let dir = std::env::temp_dir().join(format!("spdx-guide-test-{}-{}", name, std::process::id()));
+        let _result = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("README.md"), "# Archived\n").unwrap();
+        let repo = Repository::init(&dir).unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_all(["*"].iter(), IndexAddOption::DEFAULT, None).unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = Signature::new("Jane Doe", "jane@example.com", &Time::new(1_656_633_600, 0)).unwrap();
+        repo.commit(Some("HEAD"), &signature, &signature, "Initial commit", &tree, &[]).unwrap();
+        drop(tree);
+        Git { repo }
+    }
+
+    #[test]
+    fn archives_are_gzipped_tarballs_of_the_revision() {
+        let git = repo("git-archive");
+        let archive = git.archive("HEAD", "archived-1.0.0").unwrap();
+        assert_eq!(archive[..2], [0x1f, 0x8b]);
+        // The checksums recorded for an archive only help if the same revision gives the same archive again
+        assert_eq!(git.archive("HEAD", "archived-1.0.0").unwrap(), archive);
+        assert_ne!(git.archive("HEAD", "archived-2.0.0").unwrap(), archive);
+        assert!(git.archive("v3.0.0", "archived-3.0.0").is_err());
+    }
+}
@@ -0,0 +1,240 @@
+// Copyright (C) 2022  JohnnyJayJay
+
+//! Detection of the member packages of Cargo, npm and pnpm workspaces and of Go repositories with several modules.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use serde_json::Value as Json;
+use serde_yaml::Value as Yaml;
+use toml::Value;
+use crate::deps::go;
+use crate::glob;
+
+/// Directories that never contain workspace members.
+const SKIPPED_DIRS: [&str; 3] = ["node_modules", "target", "vendor"];
+/// How many directory levels below the workspace root are searched for members.
+const MAX_DEPTH: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorkspaceKind {
+    Cargo,
+    Npm,
+    Pnpm,
+    Go,
+}
+
+impl WorkspaceKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            WorkspaceKind::Cargo => "Cargo",
+            WorkspaceKind::Npm => "npm",
+            WorkspaceKind::Pnpm => "pnpm",
+            WorkspaceKind::Go => "Go",
+        }
+    }
+}
+
+/// A package of the workspace.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Member {
+    /// Package name, or module path for Go
+    pub name: String,
+    pub version: Option<String>,
+    /// Directory of the package, relative to the workspace root. Empty for the root package.
+    pub path: PathBuf,
+    /// Names of the packages this package depends on, which may include other members
+    pub dependencies: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Workspace {
+    pub kind: WorkspaceKind,
+    /// Members in order of their paths
+    pub members: Vec<Member>,
+}
+
+impl Workspace {
+    /// Detects the workspace whose root is `dir`. Returns `None` if there is none or it has no members.
+    pub fn detect(dir: &Path) -> Option<Workspace> {
+        // pnpm ignores the `workspaces` field of package.json
+        let detectors: [fn(&Path) -> Option<Workspace>; 4] = [cargo, pnpm, npm, go_modules];
+        detectors.iter()
+            .filter_map(|detect| detect(dir))
+            .find(|workspace| !workspace.members.is_empty())
+    }
+
+    /// Index of the member a file (relative to the workspace root) belongs to, which is the innermost member containing it.
+    pub fn member_of(&self, path: &Path) -> Option<usize> {
+        self.members.iter().enumerate()
+            .filter(|(_, member)| path.starts_with(&member.path))
+            .max_by_key(|(_, member)| member.path.components().count())
+            .map(|(index, _)| index)
+    }
+
+    /// Pairs of member indices where the first member depends on the second.
+    pub fn dependencies(&self) -> Vec<(usize, usize)> {
+        let mut pairs = Vec::new();
+        for (index, member) in self.members.iter().enumerate() {
+            for dependency in &member.dependencies {
+                if let Some(other) = self.members.iter().position(|other| &other.name == dependency).filter(|other| *other != index) {
+                    pairs.push((index, other));
+                }
+            }
+        }
+        pairs
+    }
+}
+
+/// A member path or pattern without leading `./` and trailing `/`. The workspace root itself becomes empty.
+fn normalise(path: &str) -> &str {
+    match path.trim_start_matches("./").trim_end_matches('/') {
+        "." => "",
+        path => path
+    }
+}
+
+fn walk_dirs(dir: &Path, relative: &Path, depth: usize, dirs: &mut Vec<PathBuf>) {
+    if depth == MAX_DEPTH {
+        return;
+    }
+    for entry in fs::read_dir(dir).into_iter().flatten().filter_map(|entry| entry.ok()) {
+        let name = entry.file_name();
+        if name.to_str().is_some_and(|name| name.starts_with('.') || SKIPPED_DIRS.contains(&name)) {
+            continue;
+        }
+        let path = entry.path();
+        if path.is_dir() {
+            dirs.push(relative.join(&name));
+            walk_dirs(&path, &relative.join(&name), depth + 1, dirs);
+        }
+    }
+}
+
+/// Directories that contain `manifest`, match one of `patterns` and none of `excluded`, relative to `dir` and sorted.
+fn member_dirs(dir: &Path, patterns: &[String], excluded: &[String], manifest: &str) -> Vec<PathBuf> {
+    let mut dirs = vec![PathBuf::new()];
+    walk_dirs(dir, Path::new(""), 0, &mut dirs);
+    dirs.sort();
+    let matches = |patterns: &[String], path: &str| patterns.iter()
+        .any(|pattern| glob::glob_match(normalise(pattern).as_bytes(), path.as_bytes(), false));
+    dirs.into_iter()
+        .filter(|path| {
+            let path = glob::slash_path(path);
+            matches(patterns, &path) && !matches(excluded, &path)
+        })
+        .filter(|path| dir.join(path).join(manifest).is_file())
+        .collect()
+}
+
+fn read_toml(path: &Path) -> Option<Value> {
+    fs::read_to_string(path).ok()?.parse().ok()
+}
+
+fn toml_strings(value: Option<&Value>) -> Vec<String> {
+    value.and_then(Value::as_array).into_iter().flatten()
+        .filter_map(|value| value.as_str().map(String::from))
+        .collect()
+}
+
+/// A Cargo workspace, where the root package (if there is one) is a member as well.
+fn cargo(dir: &Path) -> Option<Workspace> {
+    let root = read_toml(&dir.join("Cargo.toml"))?;
+    let workspace = root.get("workspace")?;
+    let patterns = toml_strings(workspace.get("members"));
+    let excluded = toml_strings(workspace.get("exclude"));
+    let mut paths = member_dirs(dir, &patterns, &excluded, "Cargo.toml");
+    if root.get("package").is_some() && !paths.contains(&PathBuf::new()) {
+        paths.insert(0, PathBuf::new());
+    }
+    let shared_version = workspace.get("package").and_then(|package| package.get("version")).and_then(Value::as_str);
+    let members = paths.into_iter()
+        .filter_map(|path| {
+            let manifest = read_toml(&dir.join(&path).join("Cargo.toml"))?;
+            let package = manifest.get("package")?;
+            let version = match package.get("version") {
+                Some(Value::String(version)) => Some(version.clone()),
+                // `version.workspace = true`
+                Some(_) => shared_version.map(String::from),
+                None => None
+            };
+            let dependencies = manifest.get("dependencies").and_then(Value::as_table).into_iter().flatten()
+                .map(|(key, value)| value.get("package").and_then(Value::as_str).unwrap_or(key).to_string())
+                .collect();
+            Some(Member { name: package.get("name")?.as_str()?.to_string(), version, path, dependencies })
+        })
+        .collect();
+    Some(Workspace { kind: WorkspaceKind::Cargo, members })
+}
+
+fn read_package_json(path: &Path) -> Option<Json> {
+    serde_json::from_str(&fs::read_to_string(path).ok()?).ok()
+}
+
+/// The packages matched by the patterns of an npm or pnpm workspace, where patterns starting with `!` exclude packages.
+fn node_workspace(dir: &Path, kind: WorkspaceKind, patterns: Vec<String>) -> Workspace {
+    let (excluded, included): (Vec<String>, Vec<String>) = patterns.into_iter().partition(|pattern| pattern.starts_with('!'));
+    let excluded: Vec<String> = excluded.iter().map(|pattern| pattern[1..].to_string()).collect();
+    let members = member_dirs(dir, &included, &excluded, "package.json").into_iter()
+        .filter_map(|path| {
+            let package = read_package_json(&dir.join(&path).join("package.json"))?;
+            Some(Member {
+                name: package.get("name")?.as_str()?.to_string(),
+                version: package.get("version").and_then(Json::as_str).map(String::from),
+                dependencies: package.get("dependencies").and_then(Json::as_object).into_iter().flatten()
+                    .map(|(name, _)| name.clone())
+                    .collect(),
+                path,
+            })
+        })
+        .collect();
+    Workspace { kind, members }
+}
+
+/// An npm or Yarn workspace, declared with `workspaces` in package.json as a list or as `{ "packages": [...] }`.
+fn npm(dir: &Path) -> Option<Workspace> {
+    let root = read_package_json(&dir.join("package.json"))?;
+    let workspaces = root.get("workspaces")?;
+    let patterns = workspaces.get("packages").unwrap_or(workspaces).as_array()?.iter()
+        .filter_map(|pattern| pattern.as_str().map(String::from))
+        .collect();
+    Some(node_workspace(dir, WorkspaceKind::Npm, patterns))
+}
+
+fn pnpm(dir: &Path) -> Option<Workspace> {
+    let config: Yaml = serde_yaml::from_str(&fs::read_to_string(dir.join("pnpm-workspace.yaml")).ok()?).ok()?;
+    let patterns = config.get("packages")?.as_sequence()?.iter()
+        .filter_map(|pattern| pattern.as_str().map(String::from))
+        .collect();
+    Some(node_workspace(dir, WorkspaceKind::Pnpm, patterns))
+}
+
+/// The modules listed in `go.work`, or without one, all modules of the repository if there are several.
+fn go_modules(dir: &Path) -> Option<Workspace> {
+    let paths: Vec<PathBuf> = match fs::read_to_string(dir.join("go.work")) {
+        Ok(content) => go::directives(&content).into_iter()
+            .filter(|(verb, _)| verb == "use")
+            .map(|(_, path)| PathBuf::from(normalise(path.trim_matches('"'))))
+            .filter(|path| !path.starts_with("..") && dir.join(path).join("go.mod").is_file())
+            .collect(),
+        Err(_) => {
+            let paths = member_dirs(dir, &["**".to_string()], &[], "go.mod");
+            if paths.len() < 2 {
+                return None;
+            }
+            paths
+        }
+    };
+    let members = paths.into_iter()
+        .filter_map(|path| {
+            let directives = go::directives(&fs::read_to_string(dir.join(&path).join("go.mod")).ok()?);
+            let name = directives.iter().find(|(verb, _)| verb == "module")?.1.trim_matches('"').to_string();
+            let dependencies = directives.iter()
+                .filter(|(verb, _)| verb == "require")
+                .filter_map(|(_, arguments)| arguments.split_whitespace().next())
+                .map(|path| path.trim_matches('"').to_string())
+                .collect();
+            Some(Member { name, version: None, path, dependencies })
+        })
+        .collect();
+    Some(Workspace { kind: WorkspaceKind::Go, members })
+}